@@ -0,0 +1,34 @@
+use rig::completion::Prompt;
+use rig::prelude::*;
+use rig::vector_store::in_memory_store::InMemoryVectorStore;
+use rig_bailian::RerankClient;
+use rig_extend_core::RerankedIndex;
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    // Create Bailian client and models
+    let client = rig_bailian::Client::from_env();
+    let embedding_model = client.embedding_model(rig_bailian::TEXT_EMBEDDING_V4);
+
+    let embeddings = client
+        .embeddings(rig_bailian::TEXT_EMBEDDING_V4)
+        .document("Transformers are attention-based architectures.".to_string())?
+        .document("Reranking orders documents by relevance.".to_string())?
+        .document("Rust is a systems programming language.".to_string())?
+        .build()
+        .await?;
+
+    // Over-fetch from the vector index, then rerank with gte-rerank-v2
+    let index = InMemoryVectorStore::from_documents(embeddings).index(embedding_model);
+    let reranked = RerankedIndex::new(index, client.rerank_model(rig_bailian::GTE_RERANK_V2, None))
+        .over_fetch(3);
+
+    let agent = client
+        .agent(rig_bailian::QWEN3_MAX)
+        .dynamic_context(1, reranked)
+        .build();
+
+    let response = agent.prompt("What is a transformer?").await?;
+    println!("Bailian: {response}");
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rig::prelude::*;
+use rig_bailian::RerankClient;
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
@@ -12,10 +12,10 @@ async fn main() -> Result<(), anyhow::Error> {
         "Reranking orders documents by relevance.".to_string(),
     ];
     let results = model
-        .rerank("what is a transformer?", &docs, Some(2), true)
+        .rerank_documents("what is a transformer?", &docs, Some(2), true)
         .await?;
     for r in results {
-        println!("#{}/{} => {}", r.index, r.relevance_score, r.text);
+        println!("#{}/{} => {:?}", r.index, r.relevance_score, r.text);
     }
     Ok(())
 }
@@ -1,186 +1,106 @@
-//! Category: client.rs (Client and Builder; implements Provider/Verify/Completion/Embedding)
+//! Category: client.rs (BaiLian profile and Client aliases over rig-extend-core)
 
-use rig::client::{CompletionClient, EmbeddingsClient, ProviderClient, VerifyClient, VerifyError};
-use rig::http_client::{self, HttpClientExt};
+use rig::http_client::HttpClientExt;
+use rig_extend_core::Profile;
+use rig_extend_core::completion::Thinking;
+#[cfg(feature = "reqwest")]
+use rig_extend_core::registry::ProviderFactory;
+use rig_extend_core::vision::ImageLimits;
+use serde_json::{Value, json};
 
 use super::BAILIAN_API_BASE_URL;
+#[cfg(feature = "reqwest")]
 use super::completion::CompletionModel;
+#[cfg(feature = "reqwest")]
 use super::embedding::EmbeddingModel;
 use super::rerank::RerankModel;
 
-/// Provider client: Client<T>
-#[derive(Clone)]
-pub struct Client<T = reqwest::Client> {
-    pub(crate) base_url: String,
-    pub(crate) api_key: String,
-    pub(crate) http_client: T,
-}
-
-impl<T> std::fmt::Debug for Client<T>
-where
-    T: std::fmt::Debug,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Client")
-            .field("base_url", &self.base_url)
-            .field("http_client", &self.http_client)
-            .field("api_key", &"<REDACTED>")
-            .finish()
-    }
-}
-
-/// Client builder: ClientBuilder<'a, T>
-#[derive(Clone)]
-pub struct ClientBuilder<'a, T = reqwest::Client> {
-    api_key: &'a str,
-    base_url: &'a str,
-    http_client: T,
-}
-
-impl<'a, T> ClientBuilder<'a, T>
-where
-    T: Default,
-{
-    pub fn new(api_key: &'a str) -> Self {
-        Self {
-            api_key,
-            base_url: BAILIAN_API_BASE_URL,
-            http_client: Default::default(),
+/// BaiLian (DashScope compatible-mode) provider profile.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Bailian;
+
+impl Profile for Bailian {
+    const NAME: &'static str = "bailian";
+    const DISPLAY_NAME: &'static str = "Bailian";
+    const BASE_URL: &'static str = BAILIAN_API_BASE_URL;
+    const API_KEY_ENV: &'static str = "BAILIAN_API_KEY";
+    const BASE_URL_ENV: &'static str = "BAILIAN_BASE_URL";
+
+    /// Qwen3 and QwQ: `enable_thinking`, capped by `thinking_budget`.
+    fn thinking_params(thinking: Thinking) -> Value {
+        match thinking {
+            Thinking::Disabled => json!({ "enable_thinking": false }),
+            Thinking::Enabled { budget } => {
+                json!({ "enable_thinking": true, "thinking_budget": budget })
+            }
+            Thinking::Auto => json!({}),
         }
     }
-}
-
-impl<'a, T> ClientBuilder<'a, T> {
-    pub fn base_url(mut self, base_url: &'a str) -> Self {
-        self.base_url = base_url;
-        self
-    }
 
-    pub fn with_client<U>(self, http_client: U) -> ClientBuilder<'a, U> {
-        ClientBuilder {
-            api_key: self.api_key,
-            base_url: self.base_url,
-            http_client,
+    /// Qwen-VL: BMP, JPEG, PNG, TIFF, WEBP and HEIC, up to 10 MB of base64.
+    fn image_limits() -> ImageLimits {
+        ImageLimits {
+            max_base64_bytes: 10 * 1024 * 1024,
+            media_types: &[
+                "image/bmp",
+                "image/jpeg",
+                "image/png",
+                "image/tiff",
+                "image/webp",
+                "image/heic",
+            ],
         }
     }
 
-    pub fn build(self) -> Client<T> {
-        Client {
-            base_url: self.base_url.to_string(),
-            api_key: self.api_key.to_string(),
-            http_client: self.http_client,
+    /// text-embedding-v3 and v4 take 10 inputs per call, v1 and v2 take 25.
+    fn embedding_batch_size(model: &str) -> usize {
+        match model {
+            "text-embedding-v1" | "text-embedding-v2" => 25,
+            _ => 10,
         }
     }
 }
 
-impl<T> Client<T>
-where
-    T: Default,
-{
-    pub fn builder(api_key: &str) -> ClientBuilder<'_, T> {
-        ClientBuilder::new(api_key)
-    }
+/// Provider client: Client<T>
+pub type Client<
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> = rig_extend_core::Client<Bailian, T>;
 
-    pub fn new(api_key: &str) -> Self {
-        Self::builder(api_key).build()
-    }
+/// Client builder: ClientBuilder<'a, T>
+pub type ClientBuilder<
+    'a,
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> = rig_extend_core::ClientBuilder<'a, Bailian, T>;
+
+/// DashScope-specific helpers on the BaiLian client.
+pub trait RerankClient {
+    /// The type of rerank model used by the client.
+    type RerankModel;
+
+    /// Create a rerank model bound to this client. `endpoint` defaults to the client's
+    /// `rerank` endpoint, then the DashScope URL.
+    fn rerank_model(&self, model: &str, endpoint: Option<String>) -> Self::RerankModel;
 }
 
-impl<T> Client<T>
+impl<T> RerankClient for Client<T>
 where
-    T: HttpClientExt,
+    T: HttpClientExt + Clone + 'static,
 {
-    pub(crate) fn url(&self, path: &str) -> String {
-        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
-    }
-
-    fn req(
-        &self,
-        method: http_client::Method,
-        path: &str,
-    ) -> http_client::Result<http_client::Builder> {
-        let url = self.url(path);
-        http_client::with_bearer_auth(
-            http_client::Builder::new().method(method).uri(url),
-            &self.api_key,
-        )
-    }
+    type RerankModel = RerankModel<T>;
 
-    pub(crate) fn get(&self, path: &str) -> http_client::Result<http_client::Builder> {
-        self.req(http_client::Method::GET, path)
-    }
-
-    pub(crate) fn post(&self, path: &str) -> http_client::Result<http_client::Builder> {
-        self.req(http_client::Method::POST, path)
-    }
-}
-
-impl Client<reqwest::Client> {
-    /// Create a rerank model bound to this client (DashScope endpoint).
-    pub fn rerank_model(&self, model: &str, endpoint: Option<String>) -> RerankModel {
+    fn rerank_model(&self, model: &str, endpoint: Option<String>) -> RerankModel<T> {
+        let endpoint = endpoint.or_else(|| self.endpoint("rerank").map(str::to_string));
         RerankModel::new(self.clone(), model, endpoint)
     }
 }
 
-impl ProviderClient for Client<reqwest::Client> {
-    type Input = String;
-
-    fn from_env() -> Self {
-        let api_key = std::env::var("BAILIAN_API_KEY").expect("BAILIAN_API_KEY not set");
-        let base_url = std::env::var("BAILIAN_BASE_URL")
-            .ok()
-            .unwrap_or_else(|| BAILIAN_API_BASE_URL.to_string());
-        Self::builder(&api_key).base_url(&base_url).build()
-    }
-
-    fn from_val(input: String) -> Self {
-        Self::new(&input)
-    }
-}
-
-impl CompletionClient for Client<reqwest::Client> {
-    type CompletionModel = CompletionModel<reqwest::Client>;
-
-    fn completion_model(&self, model: impl Into<String>) -> Self::CompletionModel {
-        CompletionModel::new(self.clone(), &model.into())
-    }
-}
-
-impl EmbeddingsClient for Client<reqwest::Client> {
-    type EmbeddingModel = EmbeddingModel<reqwest::Client>;
-
-    fn embedding_model(&self, model: impl Into<String>) -> Self::EmbeddingModel {
-        EmbeddingModel::new(self.clone(), &model.into(), 0)
-    }
-
-    fn embedding_model_with_ndims(
-        &self,
-        model: impl Into<String>,
-        ndims: usize,
-    ) -> Self::EmbeddingModel {
-        EmbeddingModel::new(self.clone(), &model.into(), ndims)
-    }
-}
-
-impl VerifyClient for Client<reqwest::Client> {
-    async fn verify(&self) -> Result<(), VerifyError> {
-        let req = self
-            .get("/models")?
-            .body(rig::http_client::NoBody)
-            .map_err(rig::http_client::Error::from)?;
-
-        let response = HttpClientExt::send(&self.http_client, req).await?;
-
-        match response.status() {
-            reqwest::StatusCode::OK => Ok(()),
-            reqwest::StatusCode::UNAUTHORIZED => Err(VerifyError::InvalidAuthentication),
-            reqwest::StatusCode::INTERNAL_SERVER_ERROR
-            | reqwest::StatusCode::SERVICE_UNAVAILABLE
-            | reqwest::StatusCode::BAD_GATEWAY => {
-                let text = rig::http_client::text(response).await?;
-                Err(VerifyError::ProviderError(text))
-            }
-            _ => Ok(()),
-        }
-    }
+/// Registry entry for `provider = "bailian"`: completion, embedding and rerank models.
+#[cfg(feature = "reqwest")]
+pub fn provider() -> ProviderFactory {
+    ProviderFactory::new::<Client>()
+        .completion::<CompletionModel>()
+        .embedding::<EmbeddingModel>()
+        .rerank::<RerankModel>()
 }
@@ -0,0 +1,372 @@
+//! Category: image_generation.rs (text-to-image, DashScope async tasks: submit, poll, download)
+
+use std::time::Duration;
+
+use rig::http_client::{self, HttpClientExt};
+use rig::image_generation::{
+    self, ImageGenerationError, ImageGenerationRequest, ImageGenerationResponse,
+};
+use rig_extend_core::ImageGenerationProfile;
+use rig_extend_core::error::{ErrorClass, ProviderError};
+use rig_extend_core::json_utils;
+use rig_extend_core::rt::{Instant, sleep};
+use rig_extend_core::usage::{Operation, TokenUsage};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::client::{Bailian, Client};
+
+/// Wanx 2.1 text-to-image, fastest of the Wanx models.
+pub const WANX2_1_T2I_TURBO: &str = "wanx2.1-t2i-turbo";
+pub const WANX2_1_T2I_PLUS: &str = "wanx2.1-t2i-plus";
+/// Qwen-Image: strong at rendering text (including Chinese) inside the picture.
+pub const QWEN_IMAGE: &str = "qwen-image";
+
+/// Default DashScope text-to-image endpoint; task status lives under `{api}/tasks/{id}`.
+pub const IMAGE_SYNTHESIS_URL: &str =
+    "https://dashscope.aliyuncs.com/api/v1/services/aigc/text2image/image-synthesis";
+
+/// Status of a DashScope async task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaskStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Canceled,
+    /// The task id is unknown or the task expired (results are kept for 24 hours).
+    #[serde(other)]
+    Unknown,
+}
+
+impl TaskStatus {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, Self::Pending | Self::Running)
+    }
+}
+
+/// `output` of a task status response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageTask {
+    pub task_id: String,
+    pub task_status: TaskStatus,
+    /// One entry per requested image; entries that failed carry `code` / `message`.
+    #[serde(default)]
+    pub results: Vec<ImageResult>,
+    /// Why the whole task failed.
+    pub code: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageResult {
+    /// Image URL, valid for 24 hours.
+    pub url: Option<String>,
+    pub orig_prompt: Option<String>,
+    /// The prompt after DashScope's prompt extension, when `prompt_extend` is on.
+    pub actual_prompt: Option<String>,
+    pub code: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageUsage {
+    pub image_count: Option<u32>,
+}
+
+/// A finished text-to-image task: the `Response` of [`ImageGenerationModel`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageTaskResponse {
+    pub output: ImageTask,
+    pub usage: Option<ImageUsage>,
+    pub request_id: Option<String>,
+}
+
+impl ImageTaskResponse {
+    /// URLs of the generated images, in order.
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.output
+            .results
+            .iter()
+            .filter_map(|result| result.url.as_deref())
+    }
+}
+
+/// Submit and status responses; DashScope reports request errors as top-level
+/// `code` / `message` without `output`.
+#[derive(Debug, Deserialize)]
+struct TaskResponse {
+    output: Option<ImageTask>,
+    usage: Option<ImageUsage>,
+    request_id: Option<String>,
+    message: Option<String>,
+}
+
+/// Text-to-image model (Wanx, Qwen-Image) bound to a Bailian client.
+///
+/// DashScope only runs these models asynchronously: the request is submitted as a task,
+/// polled every `poll_interval` until it finishes or `timeout` passes, and the first image
+/// is downloaded. The response keeps every result URL; with `with_download(false)` the
+/// image bytes are left empty and only the URLs are returned.
+#[derive(Debug, Clone)]
+pub struct ImageGenerationModel<
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
+    pub(crate) client: Client<T>,
+    pub model: String,
+    /// Full submit URL, e.g. ".../services/aigc/text2image/image-synthesis"
+    pub endpoint: String,
+    /// Task status URL prefix, e.g. ".../api/v1/tasks"
+    pub tasks_endpoint: String,
+    poll_interval: Duration,
+    timeout: Duration,
+    download: bool,
+}
+
+impl<T> ImageGenerationModel<T> {
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+    /// Wanx usually finishes within a minute; queues grow at peak times.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+    /// Create an image generation model using the Bailian client.
+    /// - `endpoint`: optional submit URL (defaults to the DashScope text-to-image service)
+    /// - tasks are polled under the same API root, e.g. `https://host/api/v1/tasks/{id}`
+    pub fn new(client: Client<T>, model: impl Into<String>, endpoint: Option<String>) -> Self {
+        let endpoint = endpoint.unwrap_or_else(|| IMAGE_SYNTHESIS_URL.to_string());
+        let tasks_endpoint = match endpoint.split_once("/services/") {
+            Some((api, _)) => format!("{api}/tasks"),
+            None => format!("{}/tasks", endpoint.trim_end_matches('/')),
+        };
+        Self {
+            client,
+            model: model.into(),
+            endpoint,
+            tasks_endpoint,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            timeout: Self::DEFAULT_TIMEOUT,
+            download: true,
+        }
+    }
+
+    /// Wait between two task status requests.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Give up on a task that has not finished this long after it was submitted.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Download the first image (default) or only return the result URLs.
+    pub fn with_download(mut self, download: bool) -> Self {
+        self.download = download;
+        self
+    }
+
+    /// Task status URL prefix; by default derived from the submit endpoint.
+    pub fn with_tasks_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.tasks_endpoint = url.into();
+        self
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl<T> ImageGenerationModel<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    /// Submit a text-to-image task and return its id.
+    ///
+    /// `additional_params` is deep-merged into the request body, so DashScope fields go
+    /// under `input` or `parameters`, e.g.
+    /// `{"input": {"negative_prompt": ".."}, "parameters": {"n": 2, "seed": 42}}`.
+    pub async fn submit(
+        &self,
+        request: ImageGenerationRequest,
+    ) -> Result<String, ImageGenerationError> {
+        let mut body = json!({
+            "model": self.model,
+            "input": { "prompt": request.prompt },
+            "parameters": { "size": format!("{}*{}", request.width, request.height), "n": 1 },
+        });
+        if let Some(params) = request.additional_params {
+            body = json_utils::merge(body, params);
+        }
+
+        let req = self
+            .client
+            .post_full(&self.endpoint)?
+            .header("Content-Type", "application/json")
+            .header("X-DashScope-Async", "enable")
+            .body(serde_json::to_vec(&body)?)
+            .map_err(|e| ImageGenerationError::HttpError(e.into()))?;
+        let response = self.send(req).await?;
+
+        match response.output {
+            Some(task) if task.task_status == TaskStatus::Failed => Err(task_error(&task, None)),
+            Some(task) => {
+                tracing::debug!(task_id = %task.task_id, model = %self.model, "image task submitted");
+                Ok(task.task_id)
+            }
+            None => Err(ImageGenerationError::ResponseError(
+                "No task in response".to_string(),
+            )),
+        }
+    }
+
+    /// Current state of a task.
+    pub async fn task(&self, task_id: &str) -> Result<ImageTaskResponse, ImageGenerationError> {
+        let url = format!("{}/{task_id}", self.tasks_endpoint);
+        let req = self
+            .client
+            .get_full(&url)?
+            .body(Vec::new())
+            .map_err(|e| ImageGenerationError::HttpError(e.into()))?;
+        let response = self.send(req).await?;
+
+        let Some(output) = response.output else {
+            return Err(ImageGenerationError::ResponseError(
+                "No task in response".to_string(),
+            ));
+        };
+        Ok(ImageTaskResponse {
+            output,
+            usage: response.usage,
+            request_id: response.request_id,
+        })
+    }
+
+    /// Poll a task until it succeeds, fails or the timeout passes.
+    pub async fn wait(&self, task_id: &str) -> Result<ImageTaskResponse, ImageGenerationError> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let response = self.task(task_id).await?;
+            match response.output.task_status {
+                TaskStatus::Succeeded => return Ok(response),
+                status if status.is_finished() => {
+                    return Err(task_error(&response.output, response.request_id));
+                }
+                _ => {}
+            }
+            if Instant::now() + self.poll_interval > deadline {
+                return Err(ImageGenerationError::ProviderError(format!(
+                    "image task {task_id} did not finish within {:?}",
+                    self.timeout
+                )));
+            }
+            sleep(self.poll_interval).await;
+        }
+    }
+
+    async fn send(
+        &self,
+        req: http_client::Request<Vec<u8>>,
+    ) -> Result<TaskResponse, ImageGenerationError> {
+        let response = HttpClientExt::send(self.client.http_client(), req).await?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let raw_text = http_client::text(response).await?;
+        if !status.is_success() {
+            return Err(ImageGenerationError::HttpError(
+                ProviderError::from_response(status, &headers, &raw_text).into(),
+            ));
+        }
+
+        let response: TaskResponse = serde_json::from_str(&raw_text)
+            .map_err(|e| ImageGenerationError::ResponseError(e.to_string()))?;
+        if response.output.is_none() && response.message.is_some() {
+            return Err(ImageGenerationError::HttpError(
+                ProviderError::from_body(None, &raw_text).into(),
+            ));
+        }
+        Ok(response)
+    }
+}
+
+/// A task that failed, was canceled or expired, as a [`ProviderError`].
+fn task_error(task: &ImageTask, request_id: Option<String>) -> ImageGenerationError {
+    let message = task.message.clone().unwrap_or_else(|| {
+        format!(
+            "image task {} ended as {:?}",
+            task.task_id, task.task_status
+        )
+    });
+    ImageGenerationError::HttpError(
+        ProviderError {
+            code: task.code.clone(),
+            request_id,
+            class: ErrorClass::classify(None, task.code.as_deref(), None, &message),
+            ..ProviderError::new(None, message)
+        }
+        .into(),
+    )
+}
+
+impl<T> image_generation::ImageGenerationModel for ImageGenerationModel<T>
+where
+    T: HttpClientExt + Clone + Send + Sync + 'static,
+{
+    type Response = ImageTaskResponse;
+    type Client = Client<T>;
+
+    /// Uses the client's `image_generation` endpoint when set.
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self::new(
+            client.clone(),
+            model,
+            client.endpoint("image_generation").map(str::to_string),
+        )
+    }
+
+    async fn image_generation(
+        &self,
+        request: ImageGenerationRequest,
+    ) -> Result<ImageGenerationResponse<Self::Response>, ImageGenerationError> {
+        let task_id = self.submit(request).await?;
+        let response = self.wait(&task_id).await?;
+
+        let image = match response.urls().next() {
+            Some(url) if self.download => {
+                rig_extend_core::image_generation::download(&self.client, url).await?
+            }
+            Some(_) => Vec::new(),
+            None => {
+                let reason = response
+                    .output
+                    .results
+                    .iter()
+                    .find_map(|r| r.message.clone());
+                return Err(ImageGenerationError::ResponseError(reason.unwrap_or_else(
+                    || format!("image task {task_id} succeeded without images"),
+                )));
+            }
+        };
+
+        self.client.record_usage(
+            &self.model,
+            Operation::ImageGeneration,
+            TokenUsage::default(),
+        );
+        Ok(ImageGenerationResponse { image, response })
+    }
+}
+
+impl<T> ImageGenerationProfile<T> for Bailian
+where
+    T: HttpClientExt + Clone + Send + Sync + 'static,
+{
+    type ImageGenerationModel = ImageGenerationModel<T>;
+}
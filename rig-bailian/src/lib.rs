@@ -1,15 +1,47 @@
+//! Category: mod.rs (top-level module and constant exports)
+//! Chat, embeddings and streaming come from rig-extend-core; this crate is the BaiLian profile:
+//! - client.rs: Bailian profile, Client/ClientBuilder aliases, rerank helpers
+//! - completion.rs: Chat completion model alias (OpenAI-compatible)
+//! - embedding.rs: Text embedding model alias and model constants
+//! - rerank.rs: Text reranking (DashScope gte-rerank-v2)
+//! - image_generation.rs: Text-to-image (DashScope async tasks, feature `image`)
+
 pub mod client;
 pub mod completion;
 pub mod embedding;
+#[cfg(feature = "image")]
+pub mod image_generation;
 pub mod rerank;
-pub mod streaming;
-pub mod types;
 
-pub use client::Client;
+#[cfg(feature = "reqwest")]
+pub use client::provider;
+pub use client::{Bailian, Client, ClientBuilder, RerankClient};
 pub use completion::CompletionModel;
 pub use embedding::{EmbeddingModel, TEXT_EMBEDDING_V4};
+#[cfg(feature = "image")]
+pub use image_generation::{
+    ImageGenerationModel, ImageTaskResponse, QWEN_IMAGE, TaskStatus, WANX2_1_T2I_PLUS,
+    WANX2_1_T2I_TURBO,
+};
 pub use rerank::{GTE_RERANK_V2, RerankError, RerankModel, RerankResult};
+pub use rig_extend_core::batching::{BatchFailure, EmbeddingBatchError, EmbeddingBatching};
+pub use rig_extend_core::circuit_breaker::{CircuitBreakerPolicy, CircuitState};
+pub use rig_extend_core::completion::Thinking;
+pub use rig_extend_core::config::{Config, ConfigError};
+pub use rig_extend_core::error::{ErrorClass, ProviderError, ProviderErrorExt};
+#[cfg(feature = "workers")]
+pub use rig_extend_core::fetch::FetchClient;
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+pub use rig_extend_core::http::{HttpOptions, HttpOptionsExt};
+pub use rig_extend_core::registry::ProviderRegistry;
+pub use rig_extend_core::rerank::{Reranker, RerankerDyn};
+pub use rig_extend_core::structured::{ResponseFormat, StructuredExtractor, StructuredOutputError};
+pub use rig_extend_core::usage::{ModelPrice, Operation, PriceTable, UsageLedger, UsageSnapshot};
+pub use rig_extend_core::vision::{self, ImageLimits, VisionError};
+pub use schemars;
 
 // Constants (aligned with original single-file version)
 pub const BAILIAN_API_BASE_URL: &str = "https://dashscope.aliyuncs.com/compatible-mode/v1";
 pub const QWEN3_MAX: &str = "qwen3-max";
+/// Qwen-VL: images, multi-image turns and video (frame lists or files).
+pub const QWEN_VL_MAX: &str = "qwen-vl-max";
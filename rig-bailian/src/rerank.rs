@@ -1,7 +1,15 @@
 //! Category: rerank.rs (text reranking, DashScope gte-rerank-v2)
 
+use rig::http_client::{self, HttpClientExt};
+#[cfg(feature = "reqwest")]
+use rig_extend_core::config::{ConfigError, FromModelConfig, ModelConfig, ModelKind};
+use rig_extend_core::error::ProviderError;
+use rig_extend_core::rerank::{self, Reranker};
+use rig_extend_core::usage::{Operation, TokenUsage};
 use serde::{Deserialize, Serialize};
 
+pub use rig_extend_core::rerank::{RerankError, RerankResult};
+
 use super::client::Client;
 
 /// Default DashScope rerank endpoint for gte-rerank-v2
@@ -60,33 +68,24 @@ pub struct Document {
     pub text: String,
 }
 
-#[derive(thiserror::Error, Debug)]
-pub enum RerankError {
-    #[error("validation error: {0}")]
-    ValidationError(String),
-    #[error("http error: {0}")]
-    Http(#[from] reqwest::Error),
-    #[error("http status {0}: {1}")]
-    HttpStatus(u16, String),
-    #[error("response error: {0}")]
-    ResponseError(String),
-}
-
 /// Rerank model bound to Bailian client
 #[derive(Debug, Clone)]
-pub struct RerankModel {
-    pub(crate) client: Client<reqwest::Client>,
+pub struct RerankModel<
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
+    pub(crate) client: Client<T>,
     pub model: String,
     /// Full endpoint URL (base + model), e.g. ".../text-re-rank/gte-rerank-v2"
     pub endpoint: String,
 }
 
-impl RerankModel {
+impl<T> RerankModel<T> {
     /// Create a rerank model using the Bailian client.
     /// - `endpoint_base`: optional base URL (defaults to DashScope base)
     /// - final endpoint = endpoint_base + model
     pub fn new(
-        client: Client<reqwest::Client>,
+        client: Client<T>,
         model: impl Into<String>,
         endpoint_base: Option<String>, // base URL, not the full endpoint
     ) -> Self {
@@ -98,27 +97,39 @@ impl RerankModel {
             endpoint,
         }
     }
+}
+
+/// `kind = "rerank"`; the client's `endpoints.rerank` replaces the DashScope URL.
+#[cfg(feature = "reqwest")]
+impl FromModelConfig for RerankModel<reqwest::Client> {
+    type Client = Client<reqwest::Client>;
+    const KIND: ModelKind = ModelKind::Rerank;
+
+    fn from_model_config(client: &Self::Client, config: &ModelConfig) -> Result<Self, ConfigError> {
+        Ok(Self::new(
+            client.clone(),
+            &config.model,
+            client.endpoint("rerank").map(str::to_string),
+        ))
+    }
+}
 
-    /// Rerank the given documents based on the query.
+impl<T> RerankModel<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    /// Rerank the given documents based on the query; with `return_documents` the results
+    /// carry the document text. [`Reranker::rerank`] is the same without the text.
     ///
     /// Returns a Vec<RerankResult>. If top_n is provided, the result will be truncated accordingly.
-    pub async fn rerank(
+    pub async fn rerank_documents(
         &self,
         query: &str,
         documents: &[String],
         top_n: Option<usize>,
         return_documents: bool,
     ) -> Result<Vec<RerankResult>, RerankError> {
-        if query.trim().is_empty() {
-            return Err(RerankError::ValidationError(
-                "Query cannot be empty".to_string(),
-            ));
-        }
-        if documents.is_empty() {
-            return Err(RerankError::ValidationError(
-                "Documents cannot be empty".to_string(),
-            ));
-        }
+        rerank::validate(query, documents)?;
 
         let request = RerankRequest {
             model: self.model.clone(),
@@ -132,60 +143,74 @@ impl RerankModel {
             }),
         };
 
-        let resp = self
+        let req = self
             .client
-            .http_client
-            .post(&self.endpoint)
-            .bearer_auth(&self.client.api_key)
+            .post_full(&self.endpoint)?
             .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+            .body(serde_json::to_vec(&request)?)
+            .map_err(|e| RerankError::HttpError(e.into()))?;
+
+        let response = HttpClientExt::send(self.client.http_client(), req).await?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let raw_text = http_client::text(response).await?;
+        if !status.is_success() {
+            return Err(RerankError::HttpError(
+                ProviderError::from_response(status, &headers, &raw_text).into(),
+            ));
+        }
 
-        let status = resp.status();
-        let raw_text = resp.text().await?;
         let resp_json: RerankResponse = serde_json::from_str(&raw_text)
             .map_err(|e| RerankError::ResponseError(e.to_string()))?;
 
-        if status.is_success() {
-            if let Some(output) = resp_json.output {
-                let mut results: Vec<RerankResult> = output
-                    .results
-                    .into_iter()
-                    .map(|item| RerankResult {
-                        index: item.index,
-                        relevance_score: item.relevance_score,
-                        text: item.document.map(|d| d.text).unwrap_or_default(),
-                    })
-                    .collect();
-
-                if let Some(n) = top_n {
-                    results.truncate(n);
-                }
-
-                Ok(results)
-            } else {
-                Err(RerankError::ResponseError(
-                    "No output in response".to_string(),
-                ))
+        let tokens = resp_json.usage.as_ref().and_then(|u| u.total_tokens);
+        let Some(output) = resp_json.output else {
+            // DashScope can report failures in a 200 body (`code` / `message`, no `output`).
+            if resp_json.message.is_some() {
+                return Err(RerankError::HttpError(
+                    ProviderError::from_body(None, &raw_text).into(),
+                ));
             }
-        } else {
-            Err(RerankError::HttpStatus(
-                status.as_u16(),
-                resp_json
-                    .message
-                    .unwrap_or_else(|| "Unknown HTTP error".to_string()),
-            ))
+            return Err(RerankError::ResponseError(
+                "No output in response".to_string(),
+            ));
+        };
+
+        self.client.record_usage(
+            &self.model,
+            Operation::Rerank,
+            TokenUsage::input_only(tokens.map_or(0, u64::from)),
+        );
+
+        let mut results: Vec<RerankResult> = output
+            .results
+            .into_iter()
+            .map(|item| RerankResult {
+                index: item.index,
+                relevance_score: item.relevance_score,
+                text: item.document.map(|d| d.text),
+            })
+            .collect();
+
+        if let Some(n) = top_n {
+            results.truncate(n);
         }
+
+        Ok(results)
     }
 }
 
-/// Public result returned by rerank()
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RerankResult {
-    pub index: usize,
-    pub relevance_score: f64,
-    /// Flattened document text (empty if server omitted it)
-    #[serde(default)]
-    pub text: String,
+impl<T> Reranker for RerankModel<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[String],
+        top_n: Option<usize>,
+    ) -> Result<Vec<RerankResult>, RerankError> {
+        self.rerank_documents(query, documents, top_n, false).await
+    }
 }
@@ -0,0 +1,105 @@
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+
+use futures::StreamExt;
+use rig::completion::{AssistantContent, CompletionModel as _};
+use rig::embeddings::EmbeddingModel as _;
+use rig::streaming::StreamedAssistantContent;
+use rig_bailian::{Client, CompletionModel, EmbeddingModel, QWEN3_MAX, TEXT_EMBEDDING_V4};
+use rig_extend_mock::{Cassette, MockServer, Mode, Recorder, defaults};
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "rig-bailian-cassette-{}-{name}.json",
+        std::process::id()
+    ))
+}
+
+fn client(recorder: Recorder, base_url: &str) -> Client<Recorder> {
+    Client::builder("sk-secret")
+        .base_url(base_url)
+        .with_client(recorder)
+        .build()
+}
+
+async fn exercise(client: &Client<Recorder>) -> (String, String, usize) {
+    let model = CompletionModel::new(client.clone(), QWEN3_MAX);
+    let response = model.completion_request("hi").send().await.unwrap();
+    let AssistantContent::Text(reply) = response.choice.first() else {
+        panic!("expected text");
+    };
+
+    let mut stream = model.completion_request("stream").stream().await.unwrap();
+    let mut streamed = String::new();
+    while let Some(item) = stream.next().await {
+        if let StreamedAssistantContent::Text(t) = item.unwrap() {
+            streamed.push_str(&t.text);
+        }
+    }
+
+    let embedder = EmbeddingModel::new(client.clone(), TEXT_EMBEDDING_V4, 8);
+    let embeddings = embedder
+        .embed_texts(vec!["a".to_string(), "b".to_string()])
+        .await
+        .unwrap();
+
+    (reply.text, streamed, embeddings.len())
+}
+
+#[tokio::test]
+async fn record_then_replay_without_network() {
+    let path = cassette_path("roundtrip");
+    let base_url;
+    let recorded = {
+        let server = MockServer::start().await;
+        base_url = server.dashscope_base_url();
+        let recorder = Recorder::record(reqwest::Client::new(), &path);
+        exercise(&client(recorder, &base_url)).await
+    };
+    assert_eq!(recorded.0, defaults::DEFAULT_REPLY);
+    assert_eq!(recorded.1, defaults::DEFAULT_REPLY);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(!text.contains("sk-secret"));
+    assert!(text.contains("<REDACTED>"));
+    let cassette = Cassette::load(&path).unwrap();
+    assert_eq!(cassette.interactions.len(), 3);
+    assert!(cassette.interactions[1].response.streaming);
+
+    // The server is gone; everything comes from the cassette.
+    let recorder = Recorder::replay(&path).unwrap();
+    assert_eq!(recorder.mode(), Mode::Replay);
+    let replayed = exercise(&client(recorder.clone(), &base_url)).await;
+    assert_eq!(replayed, recorded);
+    recorder.assert_played();
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn replay_mismatch_fails_loudly() {
+    let path = cassette_path("mismatch");
+    let server = MockServer::start().await;
+    let base_url = server.dashscope_base_url();
+    {
+        let recorder = Recorder::record(reqwest::Client::new(), &path);
+        let model = CompletionModel::new(client(recorder, &base_url), QWEN3_MAX);
+        model.completion_request("hi").send().await.unwrap();
+    }
+
+    let recorder = Recorder::replay(&path).unwrap();
+    let model = CompletionModel::new(client(recorder.clone(), &base_url), QWEN3_MAX);
+    let err = model
+        .completion_request("a different prompt")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("cassette mismatch"), "{err}");
+
+    let unplayed = std::panic::catch_unwind(AssertUnwindSafe(|| recorder.assert_played()));
+    assert!(unplayed.is_err());
+
+    // Only the recording run reached the server.
+    assert_eq!(server.requests().len(), 1);
+    std::fs::remove_file(&path).ok();
+}
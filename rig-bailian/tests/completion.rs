@@ -0,0 +1,236 @@
+use futures::StreamExt;
+use rig::completion::{AssistantContent, CompletionModel as _, Prompt, ToolDefinition};
+use rig::prelude::*;
+use rig::streaming::StreamedAssistantContent;
+use rig_bailian::{Client, ErrorClass, ProviderErrorExt, QWEN3_MAX, UsageLedger};
+use rig_extend_mock::{Mock, MockResponse, MockServer, defaults, fixtures, paths};
+use serde_json::json;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder("test-key")
+        .base_url(&server.dashscope_base_url())
+        .build()
+}
+
+#[tokio::test]
+async fn agent_prompt_returns_reply() {
+    let server = MockServer::start().await;
+    let agent = client(&server)
+        .agent(QWEN3_MAX)
+        .preamble("You are terse.")
+        .build();
+
+    let reply = agent.prompt("hi").await.unwrap();
+    assert_eq!(reply, defaults::DEFAULT_REPLY);
+
+    let requests = server.requests_to(paths::DASHSCOPE_CHAT);
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+    let body = requests[0].json();
+    assert_eq!(body["model"], QWEN3_MAX);
+    assert_eq!(body["messages"][0]["role"], "system");
+}
+
+#[tokio::test]
+async fn streaming_yields_text_deltas() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::sse(
+        fixtures::chat_stream(QWEN3_MAX, &["你好", "，", "世界"]),
+    )));
+
+    let model = client(&server).completion_model(QWEN3_MAX);
+    let mut stream = model.completion_request("hi").stream().await.unwrap();
+
+    let mut text = String::new();
+    while let Some(item) = stream.next().await {
+        if let StreamedAssistantContent::Text(t) = item.unwrap() {
+            text.push_str(&t.text);
+        }
+    }
+    assert_eq!(text, "你好，世界");
+
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[0].json();
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["stream_options"]["include_usage"], true);
+}
+
+#[tokio::test]
+async fn tool_call_is_returned() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::json(fixtures::chat_tool_call(
+            QWEN3_MAX,
+            "call_1",
+            "get_weather",
+            json!({ "city": "Hangzhou" }),
+        ))),
+    );
+
+    let model = client(&server).completion_model(QWEN3_MAX);
+    let response = model
+        .completion_request("weather in Hangzhou?")
+        .tool(ToolDefinition {
+            name: "get_weather".into(),
+            description: "Look up the weather".into(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "city": { "type": "string" } },
+            }),
+        })
+        .send()
+        .await
+        .unwrap();
+
+    let AssistantContent::ToolCall(call) = response.choice.first() else {
+        panic!("expected a tool call, got {:?}", response.choice);
+    };
+    assert_eq!(call.id, "call_1");
+    assert_eq!(call.function.name, "get_weather");
+    assert_eq!(call.function.arguments, json!({ "city": "Hangzhou" }));
+
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[0].json();
+    assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+}
+
+#[tokio::test]
+async fn streaming_tool_call_is_assembled() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::sse(
+        fixtures::chat_tool_call_stream(QWEN3_MAX, "call_1", "add", &["{\"a\":", "1,\"b\":2}"]),
+    )));
+
+    let model = client(&server).completion_model(QWEN3_MAX);
+    let mut stream = model.completion_request("1+2").stream().await.unwrap();
+
+    let mut calls = Vec::new();
+    while let Some(item) = stream.next().await {
+        if let StreamedAssistantContent::ToolCall(call) = item.unwrap() {
+            calls.push(call);
+        }
+    }
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].function.name, "add");
+    assert_eq!(calls[0].function.arguments, json!({ "a": 1, "b": 2 }));
+}
+
+#[tokio::test]
+async fn provider_error_is_surfaced() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::error(
+            400,
+            fixtures::openai_error(
+                "Range of input length should be [1, 129024]",
+                "invalid_request_error",
+                "invalid_parameter_error",
+            ),
+        )),
+    );
+
+    let model = client(&server).completion_model(QWEN3_MAX);
+    let err = model.completion_request("hi").send().await.unwrap_err();
+    assert!(err.to_string().contains("Range of input length"), "{err}");
+
+    let provider = err.provider_error().expect("typed provider error");
+    assert_eq!(provider.status, Some(400));
+    assert_eq!(provider.code.as_deref(), Some("invalid_parameter_error"));
+    assert_eq!(provider.request_id.as_deref(), Some(fixtures::REQUEST_ID));
+    assert_eq!(provider.class, ErrorClass::ContextLength);
+}
+
+#[tokio::test]
+async fn thinking_params_and_reasoning_content_are_surfaced() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::json(
+            fixtures::chat_reasoning_completion(QWEN3_MAX, "The user greets me.", "Hello!"),
+        )),
+    );
+    let ledger = UsageLedger::new();
+    let model = Client::builder("test-key")
+        .base_url(&server.dashscope_base_url())
+        .usage_ledger(ledger.clone())
+        .build()
+        .completion_model(QWEN3_MAX)
+        .with_thinking_budget(512);
+
+    let response = model.completion_request("hi").send().await.unwrap();
+    let choice: Vec<_> = response.choice.into_iter().collect();
+    match &choice[..] {
+        [
+            AssistantContent::Reasoning(reasoning),
+            AssistantContent::Text(text),
+        ] => {
+            assert_eq!(reasoning.reasoning.concat(), "The user greets me.");
+            assert_eq!(text.text, "Hello!");
+        }
+        other => panic!("expected reasoning then text, got {other:?}"),
+    }
+    assert_eq!(
+        ledger.snapshot().total().usage.reasoning_tokens,
+        fixtures::REASONING_TOKENS
+    );
+
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[0].json();
+    assert_eq!(body["enable_thinking"], true);
+    assert_eq!(body["thinking_budget"], 512);
+
+    // Explicit params win; reasoning from an earlier turn is not sent back.
+    let history = vec![
+        rig::completion::Message::user("hi"),
+        rig::completion::Message::Assistant {
+            id: None,
+            content: rig::OneOrMany::many(choice).unwrap(),
+        },
+    ];
+    model
+        .with_thinking(false)
+        .completion_request("and now?")
+        .messages(history)
+        .additional_params(json!({ "enable_thinking": true }))
+        .send()
+        .await
+        .unwrap();
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[1].json();
+    assert_eq!(body["enable_thinking"], true);
+    assert!(body.get("thinking_budget").is_none());
+    assert_eq!(body["messages"].as_array().unwrap().len(), 3);
+    assert!(!body["messages"].to_string().contains("greets"));
+}
+
+#[tokio::test]
+async fn streaming_yields_reasoning_before_text() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::sse(
+        fixtures::chat_reasoning_stream(QWEN3_MAX, &["Think", "ing."], &["Hi", "!"]),
+    )));
+    let ledger = UsageLedger::new();
+    let model = Client::builder("test-key")
+        .base_url(&server.dashscope_base_url())
+        .usage_ledger(ledger.clone())
+        .build()
+        .completion_model(QWEN3_MAX)
+        .with_thinking(true);
+
+    let mut stream = model.completion_request("hi").stream().await.unwrap();
+    let mut items = vec![];
+    while let Some(item) = stream.next().await {
+        match item.unwrap() {
+            StreamedAssistantContent::Reasoning(r) => {
+                items.push(format!("r:{}", r.reasoning.concat()))
+            }
+            StreamedAssistantContent::Text(t) => items.push(format!("t:{}", t.text)),
+            _ => {}
+        }
+    }
+    assert_eq!(items, ["r:Think", "r:ing.", "t:Hi", "t:!"]);
+    assert!(stream.response.is_some());
+    assert_eq!(
+        ledger.snapshot().total().usage.reasoning_tokens,
+        fixtures::REASONING_TOKENS
+    );
+    assert_eq!(
+        server.requests_to(paths::DASHSCOPE_CHAT)[0].json()["enable_thinking"],
+        true
+    );
+}
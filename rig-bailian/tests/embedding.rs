@@ -0,0 +1,141 @@
+use rig::embeddings::EmbeddingModel as _;
+use rig::prelude::*;
+use rig_bailian::{Client, ErrorClass, ProviderErrorExt, TEXT_EMBEDDING_V4};
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+
+fn client(server: &MockServer) -> Client {
+    Client::builder("test-key")
+        .base_url(&server.dashscope_base_url())
+        .build()
+}
+
+#[tokio::test]
+async fn embed_texts_keeps_input_order() {
+    let server = MockServer::start().await;
+    let model = client(&server).embedding_model_with_ndims(TEXT_EMBEDDING_V4, 8);
+
+    let docs = vec!["first".to_string(), "second".to_string()];
+    let embeddings = model.embed_texts(docs.clone()).await.unwrap();
+
+    assert_eq!(embeddings.len(), 2);
+    for (embedding, doc) in embeddings.iter().zip(&docs) {
+        assert_eq!(&embedding.document, doc);
+        assert_eq!(embedding.vec, fixtures::embedding(doc, 8));
+    }
+
+    let body = server.requests_to(paths::DASHSCOPE_EMBEDDINGS)[0].json();
+    assert_eq!(body["model"], TEXT_EMBEDDING_V4);
+    assert_eq!(body["input"][1], "second");
+}
+
+#[tokio::test]
+async fn mismatched_response_length_is_an_error() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_EMBEDDINGS).respond(MockResponse::json(
+            fixtures::openai_embeddings(TEXT_EMBEDDING_V4, &[vec![0.0; 8]]),
+        )),
+    );
+
+    let model = client(&server).embedding_model(TEXT_EMBEDDING_V4);
+    let result = model
+        .embed_texts(vec!["a".to_string(), "b".to_string()])
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn items_are_placed_by_index() {
+    let server = MockServer::start().await;
+    let mut body = fixtures::openai_embeddings(
+        TEXT_EMBEDDING_V4,
+        &[fixtures::embedding("a", 8), fixtures::embedding("b", 8)],
+    );
+    body["data"].as_array_mut().unwrap().reverse();
+    server.mock(
+        Mock::post(paths::DASHSCOPE_EMBEDDINGS)
+            .respond(MockResponse::json(body.clone()))
+            .once(),
+    );
+
+    let model = client(&server).embedding_model_with_ndims(TEXT_EMBEDDING_V4, 8);
+    let embeddings = model
+        .embed_texts(vec!["a".to_string(), "b".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(embeddings[0].vec, fixtures::embedding("a", 8));
+    assert_eq!(embeddings[1].vec, fixtures::embedding("b", 8));
+
+    // Same length, but one input is missing and another is there twice.
+    body["data"][0]["index"] = 0.into();
+    server.mock(Mock::post(paths::DASHSCOPE_EMBEDDINGS).respond(MockResponse::json(body)));
+    let err = model
+        .embed_texts(vec!["a".to_string(), "b".to_string()])
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Duplicate embedding index 0"),
+        "{err}"
+    );
+}
+
+fn texts(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("text-{i:02}")).collect()
+}
+
+#[tokio::test]
+async fn large_calls_are_split_at_the_model_limit() {
+    let server = MockServer::start().await;
+    let model = client(&server).embedding_model_with_ndims(TEXT_EMBEDDING_V4, 8);
+    assert_eq!(model.batching().batch_size, 10);
+
+    let docs = texts(25);
+    let embeddings = model.embed_texts(docs.clone()).await.unwrap();
+
+    let mut sizes: Vec<usize> = server
+        .requests_to(paths::DASHSCOPE_EMBEDDINGS)
+        .iter()
+        .map(|r| r.json()["input"].as_array().unwrap().len())
+        .collect();
+    sizes.sort();
+    assert_eq!(sizes, [5, 10, 10]);
+    for (embedding, doc) in embeddings.iter().zip(&docs) {
+        assert_eq!(&embedding.document, doc);
+        assert_eq!(embedding.vec, fixtures::embedding(doc, 8));
+    }
+
+    server.reset();
+    let model = model.with_batch_size(4).with_concurrency(1);
+    model.embed_texts(texts(9)).await.unwrap();
+    assert_eq!(server.requests_to(paths::DASHSCOPE_EMBEDDINGS).len(), 3);
+}
+
+#[tokio::test]
+async fn failed_batches_are_reported_individually() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_EMBEDDINGS)
+            .body_contains("text-12")
+            .respond(MockResponse::error(
+                500,
+                fixtures::openai_error("Internal error", "server_error", "InternalError"),
+            )),
+    );
+    let model = client(&server).embedding_model_with_ndims(TEXT_EMBEDDING_V4, 8);
+
+    let err = model.embed_batched(texts(25)).await.unwrap_err();
+    assert_eq!(err.batches, 3);
+    assert_eq!(err.failures.len(), 1);
+    assert_eq!(err.failures[0].batch, 1);
+    assert_eq!(err.failures[0].range, 10..20);
+    assert!(err.embeddings[..10].iter().all(Option::is_some));
+    assert!(err.embeddings[10..20].iter().all(Option::is_none));
+    assert_eq!(
+        err.embeddings[24].as_ref().unwrap().document,
+        "text-24".to_string()
+    );
+
+    // Through the rig trait the first failure comes back with its type intact.
+    let err = model.embed_texts(texts(25)).await.unwrap_err();
+    assert_eq!(err.error_class(), Some(ErrorClass::Server));
+}
@@ -0,0 +1,50 @@
+use rig::embeddings::EmbeddingModel as _;
+use rig::prelude::*;
+use rig_bailian::{Client, GTE_RERANK_V2, RerankClient, Reranker, TEXT_EMBEDDING_V4};
+use rig_extend_mock::{MockServer, Recorder, paths};
+
+fn cassette_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!(
+        "rig-bailian-backend-{}-{name}.json",
+        std::process::id()
+    ))
+}
+
+/// The client traits are implemented for any `HttpClientExt`, not just `reqwest::Client`.
+#[tokio::test]
+async fn client_traits_work_with_another_http_backend() {
+    let server = MockServer::start().await;
+    let path = cassette_path("traits");
+    let recorder = Recorder::record(reqwest::Client::new(), &path);
+    let client: Client<Recorder> = Client::builder("sk-secret")
+        .base_url(&server.dashscope_base_url())
+        .with_client(recorder.clone())
+        .build();
+
+    client.verify().await.unwrap();
+
+    let embeddings = client
+        .embedding_model_with_ndims(TEXT_EMBEDDING_V4, 8)
+        .embed_texts(vec!["a".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(embeddings.len(), 1);
+
+    let reranker = client.rerank_model(GTE_RERANK_V2, Some(server.dashscope_rerank_url()));
+    let documents = vec!["bananas".to_string(), "apples".to_string()];
+    let results = Reranker::rerank(&reranker, "bananas", &documents, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+
+    let uris: Vec<String> = recorder
+        .cassette()
+        .interactions
+        .iter()
+        .map(|it| it.request.uri.clone())
+        .collect();
+    assert_eq!(uris.len(), 3);
+    assert!(uris[1].ends_with(paths::DASHSCOPE_EMBEDDINGS));
+    assert!(uris[2].ends_with(paths::DASHSCOPE_RERANK));
+    std::fs::remove_file(&path).ok();
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use rig::image_generation::{ImageGenerationError, ImageGenerationModel as _};
+use rig::prelude::*;
+use rig_bailian::{
+    Client, ErrorClass, ImageGenerationModel, Operation, ProviderErrorExt, TaskStatus, UsageLedger,
+    WANX2_1_T2I_TURBO,
+};
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+use serde_json::json;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder("test-key")
+        .base_url(&server.dashscope_base_url())
+        .endpoint("image_generation", &server.dashscope_image_synthesis_url())
+        .build()
+}
+
+fn model(server: &MockServer) -> ImageGenerationModel {
+    client(server)
+        .image_generation_model(WANX2_1_T2I_TURBO)
+        .with_poll_interval(Duration::from_millis(10))
+}
+
+fn task_path(task_id: &str) -> String {
+    format!("{}/{task_id}", paths::DASHSCOPE_TASKS)
+}
+
+#[tokio::test]
+async fn submits_polls_and_downloads_the_image() {
+    let server = MockServer::start().await;
+    let ledger = UsageLedger::new();
+    let model = Client::builder("test-key")
+        .endpoint("image_generation", &server.dashscope_image_synthesis_url())
+        .usage_ledger(ledger.clone())
+        .build()
+        .image_generation_model(WANX2_1_T2I_TURBO)
+        .with_poll_interval(Duration::from_millis(10));
+
+    let response = model
+        .image_generation_request()
+        .prompt("A lighthouse at dusk")
+        .width(1024)
+        .height(768)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.image, fixtures::IMAGE_PNG);
+    assert_eq!(response.response.output.task_id, fixtures::TASK_ID);
+    assert_eq!(response.response.output.task_status, TaskStatus::Succeeded);
+
+    let submit = &server.requests_to(paths::DASHSCOPE_IMAGE_SYNTHESIS)[0];
+    assert_eq!(submit.header("x-dashscope-async"), Some("enable"));
+    assert_eq!(submit.header("authorization"), Some("Bearer test-key"));
+    assert_eq!(
+        submit.json(),
+        json!({
+            "model": WANX2_1_T2I_TURBO,
+            "input": { "prompt": "A lighthouse at dusk" },
+            "parameters": { "size": "1024*768", "n": 1 },
+        })
+    );
+    let poll = &server.requests_to(&task_path(fixtures::TASK_ID))[0];
+    assert_eq!(poll.header("authorization"), Some("Bearer test-key"));
+    // Result URLs are pre-signed; the API key must not leak to them.
+    let download = &server.requests_to(&format!("{}/{}.png", paths::FILES, fixtures::TASK_ID))[0];
+    assert_eq!(download.header("authorization"), None);
+
+    let snapshot = ledger.snapshot();
+    assert_eq!(snapshot.entries.len(), 1);
+    assert_eq!(snapshot.entries[0].operation, Operation::ImageGeneration);
+    assert_eq!(snapshot.entries[0].requests, 1);
+}
+
+#[tokio::test]
+async fn polls_until_the_task_finishes_and_can_return_urls_only() {
+    let server = MockServer::start().await;
+    server
+        .mock(
+            Mock::get(&task_path(fixtures::TASK_ID))
+                .respond(MockResponse::json(fixtures::dashscope_task(
+                    fixtures::TASK_ID,
+                    "PENDING",
+                )))
+                .once(),
+        )
+        .mock(
+            Mock::get(&task_path(fixtures::TASK_ID))
+                .respond(MockResponse::json(fixtures::dashscope_task(
+                    fixtures::TASK_ID,
+                    "RUNNING",
+                )))
+                .once(),
+        )
+        .mock(
+            Mock::get(&task_path(fixtures::TASK_ID)).respond(MockResponse::json(
+                fixtures::dashscope_image_task(
+                    fixtures::TASK_ID,
+                    &["https://example.com/1.png", "https://example.com/2.png"],
+                ),
+            )),
+        );
+
+    let response = model(&server)
+        .with_download(false)
+        .image_generation_request()
+        .prompt("Two cats")
+        .width(512)
+        .height(512)
+        .additional_params(json!({
+            "input": { "negative_prompt": "dogs" },
+            "parameters": { "n": 2, "seed": 42 },
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.image.is_empty());
+    assert_eq!(
+        response.response.urls().collect::<Vec<_>>(),
+        ["https://example.com/1.png", "https://example.com/2.png"]
+    );
+    assert_eq!(server.requests_to(&task_path(fixtures::TASK_ID)).len(), 3);
+
+    let body = server.requests_to(paths::DASHSCOPE_IMAGE_SYNTHESIS)[0].json();
+    assert_eq!(
+        body["input"],
+        json!({ "prompt": "Two cats", "negative_prompt": "dogs" })
+    );
+    assert_eq!(
+        body["parameters"],
+        json!({ "size": "512*512", "n": 2, "seed": 42 })
+    );
+}
+
+#[tokio::test]
+async fn failed_tasks_and_rejected_submits_are_provider_errors() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::get(&task_path(fixtures::TASK_ID)).respond(MockResponse::json(
+            fixtures::dashscope_failed_task(
+                fixtures::TASK_ID,
+                "DataInspectionFailed",
+                "Input data may contain inappropriate content.",
+            ),
+        )),
+    );
+    let err = model(&server)
+        .image_generation_request()
+        .prompt("something")
+        .send()
+        .await
+        .unwrap_err();
+    let provider = err.provider_error().expect("provider error");
+    assert_eq!(provider.code.as_deref(), Some("DataInspectionFailed"));
+    assert_eq!(provider.class, ErrorClass::ContentFilter);
+    assert_eq!(provider.request_id.as_deref(), Some(fixtures::REQUEST_ID));
+
+    server.reset();
+    server.mock(
+        Mock::post(paths::DASHSCOPE_IMAGE_SYNTHESIS).respond(MockResponse::error(
+            400,
+            fixtures::dashscope_error("InvalidParameter", "size is not supported"),
+        )),
+    );
+    let err = model(&server)
+        .image_generation_request()
+        .prompt("something")
+        .send()
+        .await
+        .unwrap_err();
+    let provider = err.provider_error().expect("provider error");
+    assert_eq!(provider.status, Some(400));
+    assert_eq!(provider.code.as_deref(), Some("InvalidParameter"));
+    assert!(server.requests_to(&task_path(fixtures::TASK_ID)).is_empty());
+}
+
+#[tokio::test]
+async fn gives_up_after_the_timeout() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::get(&task_path(fixtures::TASK_ID)).respond(MockResponse::json(
+            fixtures::dashscope_task(fixtures::TASK_ID, "RUNNING"),
+        )),
+    );
+
+    let err = model(&server)
+        .with_timeout(Duration::from_millis(100))
+        .image_generation_request()
+        .prompt("something")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ImageGenerationError::ProviderError(message) if message.contains(fixtures::TASK_ID)),
+        "{err}"
+    );
+    let polls = server.requests_to(&task_path(fixtures::TASK_ID)).len();
+    assert!((2..=11).contains(&polls), "{polls} polls");
+}
@@ -0,0 +1,146 @@
+use rig_bailian::{
+    Client, ErrorClass, GTE_RERANK_V2, Operation, ProviderErrorExt, RerankClient, RerankError,
+    Reranker, UsageLedger,
+};
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+
+fn documents() -> Vec<String> {
+    vec![
+        "Rust is a systems programming language".to_string(),
+        "Bananas are yellow".to_string(),
+        "The Rust compiler checks lifetimes".to_string(),
+    ]
+}
+
+#[tokio::test]
+async fn rerank_orders_documents_and_returns_text() {
+    let server = MockServer::start().await;
+    let client = Client::new("test-key");
+    let model = client.rerank_model(GTE_RERANK_V2, Some(server.dashscope_rerank_url()));
+
+    let results = model
+        .rerank_documents("rust compiler", &documents(), Some(2), true)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].index, 2);
+    assert!(results[0].relevance_score >= results[1].relevance_score);
+    assert_eq!(
+        results[0].text.as_deref(),
+        Some("The Rust compiler checks lifetimes")
+    );
+
+    let body = server.requests_to(paths::DASHSCOPE_RERANK)[0].json();
+    assert_eq!(body["model"], GTE_RERANK_V2);
+    assert_eq!(body["input"]["query"], "rust compiler");
+    assert_eq!(body["parameters"]["top_n"], 2);
+}
+
+#[tokio::test]
+async fn reranker_trait_omits_documents() {
+    let server = MockServer::start().await;
+    let client = Client::new("test-key");
+    let model = client.rerank_model(GTE_RERANK_V2, Some(server.dashscope_rerank_url()));
+
+    let results = Reranker::rerank(&model, "bananas", &documents(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].index, 1);
+    assert!(results.iter().all(|r| r.text.is_none()));
+
+    // The trait method is reachable with plain method-call syntax.
+    let top = model
+        .rerank("bananas", &documents(), Some(1))
+        .await
+        .unwrap();
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].index, 1);
+    assert!(top[0].text.is_none());
+}
+
+#[tokio::test]
+async fn rerank_usage_is_recorded() {
+    let server = MockServer::start().await;
+    let ledger = UsageLedger::new();
+    let client = Client::builder("test-key")
+        .usage_ledger(ledger.clone())
+        .build()
+        .with_usage_tag("search");
+    let model = client.rerank_model(GTE_RERANK_V2, Some(server.dashscope_rerank_url()));
+
+    Reranker::rerank(&model, "bananas", &documents(), None)
+        .await
+        .unwrap();
+
+    let entry = &ledger.snapshot().entries[0];
+    assert_eq!(entry.provider, "bailian");
+    assert_eq!(entry.model, GTE_RERANK_V2);
+    assert_eq!(entry.operation, Operation::Rerank);
+    assert_eq!(entry.tag.as_deref(), Some("search"));
+    assert!(entry.usage.prompt_tokens > 0);
+}
+
+#[tokio::test]
+async fn provider_error_message_is_surfaced() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_RERANK).respond(MockResponse::error(
+            401,
+            fixtures::dashscope_error("InvalidApiKey", "Invalid API-key provided."),
+        )),
+    );
+    let client = Client::new("bad-key");
+    let model = client.rerank_model(GTE_RERANK_V2, Some(server.dashscope_rerank_url()));
+
+    let err = model
+        .rerank_documents("q", &documents(), None, false)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid API-key"), "{err:?}");
+    assert_eq!(err.error_class(), Some(ErrorClass::Auth));
+    assert_eq!(err.request_id(), Some(fixtures::REQUEST_ID));
+}
+
+#[tokio::test]
+async fn empty_input_is_rejected_before_sending() {
+    let server = MockServer::start().await;
+    let client = Client::new("test-key");
+    let model = client.rerank_model(GTE_RERANK_V2, Some(server.dashscope_rerank_url()));
+
+    let err = model
+        .rerank_documents("q", &[], None, false)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RerankError::ValidationError(_)));
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn configured_rerank_model_uses_the_endpoint_override() {
+    let server = MockServer::start().await;
+    let config = rig_bailian::Config::from_toml_str(&format!(
+        r#"
+[clients.dashscope]
+provider = "bailian"
+api_key = "test-key"
+endpoints = {{ rerank = "{}" }}
+
+[models.rerank]
+client = "dashscope"
+kind = "rerank"
+model = "{GTE_RERANK_V2}"
+"#,
+        server.dashscope_rerank_url()
+    ))
+    .unwrap();
+
+    let model: rig_bailian::RerankModel = config.model("rerank").unwrap();
+    let results = Reranker::rerank(&model, "bananas", &documents(), Some(1))
+        .await
+        .unwrap();
+    assert_eq!(results[0].index, 1);
+    assert_eq!(server.requests_to(paths::DASHSCOPE_RERANK).len(), 1);
+}
@@ -0,0 +1,154 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rig::OneOrMany;
+use rig::completion::{CompletionError, CompletionModel as _, Message};
+use rig::message::{ImageDetail, ImageMediaType, UserContent};
+use rig::prelude::*;
+use rig_bailian::{Client, QWEN_VL_MAX, VisionError, vision};
+use rig_extend_mock::{MockServer, paths};
+use serde_json::json;
+
+const PNG: &[u8] = &[
+    0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D,
+];
+const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0, 0x10, b'J', b'F', b'I', b'F'];
+const GIF: &[u8] = b"GIF89a\x01\x00\x01\x00";
+
+fn client(server: &MockServer) -> Client {
+    Client::builder("test-key")
+        .base_url(&server.dashscope_base_url())
+        .build()
+}
+
+fn user(parts: Vec<UserContent>) -> Message {
+    Message::User {
+        content: OneOrMany::many(parts).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn images_become_image_url_parts() {
+    let server = MockServer::start().await;
+    let model = client(&server).completion_model(QWEN_VL_MAX);
+
+    model
+        .completion_request(user(vec![
+            UserContent::text("What changed between these screenshots?"),
+            UserContent::image_url(
+                "https://example.com/before.png",
+                None,
+                Some(ImageDetail::High),
+            ),
+            UserContent::image_raw(PNG, None, None),
+            UserContent::image_base64(STANDARD.encode(JPEG), Some(ImageMediaType::JPEG), None),
+        ]))
+        .send()
+        .await
+        .unwrap();
+
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[0].json();
+    let content = &body["messages"][0]["content"];
+    assert_eq!(
+        content[0],
+        json!({ "type": "text", "text": "What changed between these screenshots?" })
+    );
+    assert_eq!(
+        content[1],
+        json!({
+            "type": "image_url",
+            "image_url": { "url": "https://example.com/before.png", "detail": "high" },
+        })
+    );
+    assert_eq!(
+        content[2]["image_url"]["url"],
+        format!("data:image/png;base64,{}", STANDARD.encode(PNG))
+    );
+    assert!(content[2]["image_url"].get("detail").is_none());
+    assert_eq!(
+        content[3]["image_url"]["url"],
+        format!("data:image/jpeg;base64,{}", STANDARD.encode(JPEG))
+    );
+}
+
+#[tokio::test]
+async fn videos_are_sent_as_frame_lists_or_urls() {
+    let server = MockServer::start().await;
+    let model = client(&server).completion_model(QWEN_VL_MAX);
+
+    let mut frames =
+        vision::video_frames(["https://example.com/1.jpg", "https://example.com/2.jpg"]);
+    if let UserContent::Video(video) = &mut frames {
+        video.additional_params.as_mut().unwrap()["fps"] = json!(2);
+    }
+    model
+        .completion_request(user(vec![
+            frames,
+            vision::video_url("https://example.com/clip.mp4"),
+            UserContent::text("Describe the video."),
+        ]))
+        .send()
+        .await
+        .unwrap();
+
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[0].json();
+    let content = &body["messages"][0]["content"];
+    assert_eq!(
+        content[0],
+        json!({
+            "type": "video",
+            "video": ["https://example.com/1.jpg", "https://example.com/2.jpg"],
+            "fps": 2,
+        })
+    );
+    assert_eq!(
+        content[1],
+        json!({ "type": "video_url", "video_url": { "url": "https://example.com/clip.mp4" } })
+    );
+}
+
+#[tokio::test]
+async fn inline_images_are_checked_before_upload() {
+    let server = MockServer::start().await;
+    let model = client(&server).completion_model(QWEN_VL_MAX);
+    let vision_error = |err: CompletionError| match err {
+        CompletionError::RequestError(err) => *err.downcast::<VisionError>().unwrap(),
+        other => panic!("expected a request error, got {other:?}"),
+    };
+
+    // Qwen-VL does not take GIFs.
+    let err = model
+        .completion_request(user(vec![UserContent::image_raw(GIF, None, None)]))
+        .send()
+        .await
+        .unwrap_err();
+    let err = vision_error(err);
+    assert!(
+        matches!(&err, VisionError::UnsupportedFormat(format, _) if format == "image/gif"),
+        "{err}"
+    );
+
+    let mut large = PNG.to_vec();
+    large.resize(8 * 1024 * 1024, 0);
+    let err = model
+        .completion_request(user(vec![UserContent::image_raw(large, None, None)]))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        vision_error(err),
+        VisionError::TooLarge { limit, .. } if limit == 10 * 1024 * 1024
+    ));
+
+    let err = model
+        .completion_request(user(vec![UserContent::image_base64(
+            "not base64!",
+            Some(ImageMediaType::PNG),
+            None,
+        )]))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(vision_error(err), VisionError::InvalidBase64(_)));
+
+    assert!(server.requests_to(paths::DASHSCOPE_CHAT).is_empty());
+}
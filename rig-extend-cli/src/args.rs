@@ -0,0 +1,286 @@
+//! Category: args.rs (command-line parsing and usage text)
+
+use std::fmt;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: rig-extend [OPTIONS] <COMMAND> [ARGS]
+
+Commands:
+  chat [PROMPT]        Chat with a model, streaming the reply (prompt from stdin if omitted)
+  embed                Embed texts from stdin or --input, one per line (plain text or JSONL)
+  rerank               Rerank the documents in --documents against --query
+  predict [TEXT]...    Classify texts with TEI /predict (texts from stdin if omitted)
+
+Options:
+  -p, --provider <NAME>   bailian, volcengine or tei [default: bailian, env RIG_EXTEND_PROVIDER]
+  -m, --model <MODEL>     Model name [default: per provider and command]
+      --base-url <URL>    API base URL [default: provider env var, then built-in default]
+      --api-key <KEY>     API key [default: BAILIAN_API_KEY / VOLCENGINE_API_KEY]
+  -f, --format <FORMAT>   text (for people) or json (for scripts) [default: text; json for embed]
+  -h, --help              Print this help
+  -V, --version           Print the version
+
+chat:
+  -s, --system <TEXT>     System prompt
+      --temperature <T>   Sampling temperature
+      --max-tokens <N>    Maximum tokens to generate
+      --no-stream         Wait for the whole reply instead of streaming it
+
+embed:
+  -i, --input <FILE>      Read texts from FILE instead of stdin
+      --dims <N>          Output dimensions, if the model supports it
+      --batch-size <N>    Texts per request [default: the model's limit]
+      --concurrency <N>   Requests in flight at once [default: 4]
+
+rerank:
+  -q, --query <TEXT>      Query to rank the documents against (required)
+  -d, --documents <FILE>  Documents, one per line (plain text or JSONL); - for stdin (required)
+  -n, --top-n <N>         Return only the best N documents
+      --rerank-url <URL>  Bailian rerank endpoint [default: DashScope gte-rerank]
+
+JSONL input lines are objects with a \"text\" field and an optional \"id\", which is copied
+to the output.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    Bailian,
+    Volcengine,
+    Tei,
+}
+
+impl Provider {
+    fn parse(value: &str) -> Result<Self, UsageError> {
+        match value.to_ascii_lowercase().as_str() {
+            "bailian" | "dashscope" => Ok(Self::Bailian),
+            "volcengine" | "ark" => Ok(Self::Volcengine),
+            "tei" => Ok(Self::Tei),
+            other => Err(UsageError(format!(
+                "unknown provider `{other}` (expected bailian, volcengine or tei)"
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bailian => "bailian",
+            Self::Volcengine => "volcengine",
+            Self::Tei => "tei",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl Format {
+    fn parse(value: &str) -> Result<Self, UsageError> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" | "jsonl" => Ok(Self::Json),
+            other => Err(UsageError(format!(
+                "unknown format `{other}` (expected text or json)"
+            ))),
+        }
+    }
+}
+
+/// Options shared by every command.
+#[derive(Debug, Clone)]
+pub struct Global {
+    pub provider: Provider,
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub format: Option<Format>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChatArgs {
+    pub prompt: Option<String>,
+    pub system: Option<String>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<u64>,
+    pub no_stream: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EmbedArgs {
+    pub input: Option<PathBuf>,
+    pub dims: Option<usize>,
+    pub batch_size: Option<usize>,
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RerankArgs {
+    pub query: String,
+    /// `None` reads stdin.
+    pub documents: Option<PathBuf>,
+    pub top_n: Option<usize>,
+    pub rerank_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PredictArgs {
+    pub texts: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Chat(ChatArgs),
+    Embed(EmbedArgs),
+    Rerank(RerankArgs),
+    Predict(PredictArgs),
+    Help,
+    Version,
+}
+
+#[derive(Debug, Clone)]
+pub struct Args {
+    pub global: Global,
+    pub command: Command,
+}
+
+/// Invalid command line; reported with a hint to `--help` and exit code 2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// Parse the arguments after the program name. `provider_env` is `RIG_EXTEND_PROVIDER`.
+pub fn parse(
+    args: impl IntoIterator<Item = String>,
+    provider_env: Option<String>,
+) -> Result<Args, UsageError> {
+    let mut global = Global {
+        provider: match provider_env {
+            Some(value) if !value.is_empty() => Provider::parse(&value)?,
+            _ => Provider::Bailian,
+        },
+        model: None,
+        base_url: None,
+        api_key: None,
+        format: None,
+    };
+    let mut command: Option<String> = None;
+    let mut positional = Vec::new();
+    let mut chat = ChatArgs::default();
+    let mut embed = EmbedArgs::default();
+    let mut query = None;
+    let mut documents = None;
+    let mut top_n = None;
+    let mut rerank_url = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positional.extend(args.by_ref());
+            break;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            if command.is_none() {
+                command = Some(arg);
+            } else {
+                positional.push(arg);
+            }
+            continue;
+        }
+
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || -> Result<String, UsageError> {
+            match inline {
+                Some(value) => Ok(value.to_string()),
+                None => args
+                    .next()
+                    .ok_or_else(|| UsageError(format!("`{name}` needs a value"))),
+            }
+        };
+
+        match name.as_str() {
+            "-h" | "--help" => {
+                return Ok(Args {
+                    global,
+                    command: Command::Help,
+                });
+            }
+            "-V" | "--version" => {
+                return Ok(Args {
+                    global,
+                    command: Command::Version,
+                });
+            }
+            "-p" | "--provider" => global.provider = Provider::parse(&value()?)?,
+            "-m" | "--model" => global.model = Some(value()?),
+            "--base-url" => global.base_url = Some(value()?),
+            "--api-key" => global.api_key = Some(value()?),
+            "-f" | "--format" => global.format = Some(Format::parse(&value()?)?),
+            "-s" | "--system" => chat.system = Some(value()?),
+            "--temperature" => chat.temperature = Some(number(&name, &value()?)?),
+            "--max-tokens" => chat.max_tokens = Some(number(&name, &value()?)?),
+            "--no-stream" => chat.no_stream = true,
+            "-i" | "--input" => embed.input = Some(value()?.into()),
+            "--dims" => embed.dims = Some(number(&name, &value()?)?),
+            "--batch-size" => embed.batch_size = Some(number(&name, &value()?)?),
+            "--concurrency" => embed.concurrency = Some(number(&name, &value()?)?),
+            "-q" | "--query" => query = Some(value()?),
+            "-d" | "--documents" => documents = Some(value()?),
+            "-n" | "--top-n" => top_n = Some(number(&name, &value()?)?),
+            "--rerank-url" => rerank_url = Some(value()?),
+            _ => return Err(UsageError(format!("unknown option `{name}`"))),
+        }
+    }
+
+    let command = match command.as_deref() {
+        None => return Err(UsageError("missing command".to_string())),
+        Some("help") => Command::Help,
+        Some("chat") => {
+            chat.prompt = (!positional.is_empty()).then(|| positional.join(" "));
+            return Ok(Args {
+                global,
+                command: Command::Chat(chat),
+            });
+        }
+        Some("embed") => Command::Embed(embed),
+        Some("rerank") => Command::Rerank(RerankArgs {
+            query: query.ok_or_else(|| UsageError("rerank needs --query".to_string()))?,
+            documents: match documents
+                .ok_or_else(|| UsageError("rerank needs --documents".to_string()))?
+            {
+                path if path == "-" => None,
+                path => Some(path.into()),
+            },
+            top_n,
+            rerank_url,
+        }),
+        Some("predict") => {
+            return Ok(Args {
+                global,
+                command: Command::Predict(PredictArgs { texts: positional }),
+            });
+        }
+        Some(other) => return Err(UsageError(format!("unknown command `{other}`"))),
+    };
+    if let Some(extra) = positional.first() {
+        return Err(UsageError(format!("unexpected argument `{extra}`")));
+    }
+    Ok(Args { global, command })
+}
+
+fn number<N: std::str::FromStr>(name: &str, value: &str) -> Result<N, UsageError> {
+    value
+        .parse()
+        .map_err(|_| UsageError(format!("`{name}` expects a number, got `{value}`")))
+}
@@ -0,0 +1,96 @@
+//! Category: chat.rs (`chat`: one prompt, streamed or whole reply)
+
+use std::io::Write;
+
+use anyhow::bail;
+use futures::StreamExt;
+use rig::completion::{AssistantContent, CompletionModel, GetTokenUsage, Usage};
+use rig::streaming::StreamedAssistantContent;
+use serde_json::json;
+
+use super::args::{ChatArgs, Format};
+use super::input;
+
+pub async fn run<M>(model: M, name: &str, args: ChatArgs, format: Format) -> anyhow::Result<()>
+where
+    M: CompletionModel,
+{
+    let prompt = match args.prompt {
+        Some(prompt) => prompt,
+        None => input::read(None)?.trim().to_string(),
+    };
+    if prompt.is_empty() {
+        bail!("empty prompt: pass it as an argument or on stdin");
+    }
+
+    let mut request = model
+        .completion_request(prompt)
+        .temperature_opt(args.temperature)
+        .max_tokens_opt(args.max_tokens);
+    if let Some(system) = args.system {
+        request = request.preamble(system);
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    let (text, usage) = if args.no_stream {
+        let response = request.send().await?;
+        let text: String = response
+            .choice
+            .iter()
+            .filter_map(|content| match content {
+                AssistantContent::Text(text) => Some(text.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        if format == Format::Text {
+            writeln!(stdout, "{text}")?;
+        }
+        (text, Some(response.usage))
+    } else {
+        let mut stream = request.stream().await?;
+        let mut text = String::new();
+        let mut usage = None;
+        while let Some(item) = stream.next().await {
+            match item? {
+                StreamedAssistantContent::Text(delta) => {
+                    if format == Format::Text {
+                        write!(stdout, "{}", delta.text)?;
+                        stdout.flush()?;
+                    }
+                    text.push_str(&delta.text);
+                }
+                StreamedAssistantContent::Final(response) => usage = response.token_usage(),
+                _ => {}
+            }
+        }
+        if format == Format::Text {
+            writeln!(stdout)?;
+        }
+        (text, usage)
+    };
+
+    match format {
+        Format::Text => {
+            if let Some(usage) = usage {
+                eprintln!(
+                    "[{name}] {} input + {} output tokens",
+                    usage.input_tokens, usage.output_tokens
+                );
+            }
+        }
+        Format::Json => {
+            let usage = usage.unwrap_or_else(Usage::new);
+            let output = json!({
+                "model": name,
+                "text": text,
+                "usage": {
+                    "input_tokens": usage.input_tokens,
+                    "output_tokens": usage.output_tokens,
+                    "total_tokens": usage.total_tokens,
+                },
+            });
+            writeln!(stdout, "{output}")?;
+        }
+    }
+    Ok(())
+}
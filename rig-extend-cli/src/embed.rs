@@ -0,0 +1,59 @@
+//! Category: embed.rs (`embed`: texts in, one JSONL vector per text out)
+
+use std::io::Write;
+
+use rig::embeddings::EmbeddingModel;
+use serde_json::json;
+
+use super::args::Format;
+use super::input::Item;
+
+pub async fn run<M>(model: M, items: Vec<Item>, format: Format) -> anyhow::Result<()>
+where
+    M: EmbeddingModel,
+{
+    let texts: Vec<String> = items.iter().map(|item| item.text.clone()).collect();
+    let embeddings = model.embed_texts(texts).await?;
+
+    let mut stdout = std::io::stdout().lock();
+    for (index, (item, embedding)) in items.into_iter().zip(embeddings).enumerate() {
+        match format {
+            Format::Json => {
+                let mut line = json!({
+                    "index": index,
+                    "text": item.text,
+                    "embedding": embedding.vec,
+                });
+                if let Some(id) = item.id {
+                    line["id"] = id;
+                }
+                writeln!(stdout, "{line}")?;
+            }
+            Format::Text => {
+                let head: Vec<String> = embedding
+                    .vec
+                    .iter()
+                    .take(4)
+                    .map(|x| format!("{x:.4}"))
+                    .collect();
+                writeln!(
+                    stdout,
+                    "{index}\t{} dims\t[{}, ...]\t{}",
+                    embedding.vec.len(),
+                    head.join(", "),
+                    preview(&item.text)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// First line of `text`, cut at 60 characters.
+pub fn preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    match line.char_indices().nth(60) {
+        Some((cut, _)) => format!("{}...", &line[..cut]),
+        None => line.to_string(),
+    }
+}
@@ -0,0 +1,62 @@
+//! Category: input.rs (line-oriented text input: plain lines or JSONL objects)
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, bail};
+use serde_json::Value;
+
+/// One input text and the caller's id for it, if the line was a JSONL object.
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub id: Option<Value>,
+    pub text: String,
+}
+
+/// Read `path`, or stdin when `None`.
+pub fn read(path: Option<&Path>) -> anyhow::Result<String> {
+    match path {
+        Some(path) => {
+            std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))
+        }
+        None => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .context("cannot read stdin")?;
+            Ok(text)
+        }
+    }
+}
+
+/// Parse non-empty lines. A line starting with `{` must be a JSON object with a string
+/// `text` field; anything else is taken as the text itself.
+pub fn items(input: &str) -> anyhow::Result<Vec<Item>> {
+    let mut items = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.trim_start().starts_with('{') {
+            items.push(Item {
+                id: None,
+                text: line.to_string(),
+            });
+            continue;
+        }
+        let mut object: Value = serde_json::from_str(line)
+            .with_context(|| format!("line {}: invalid JSON", number + 1))?;
+        let Some(text) = object["text"].as_str().map(str::to_string) else {
+            bail!(
+                "line {}: JSON input needs a string \"text\" field",
+                number + 1
+            );
+        };
+        items.push(Item {
+            id: object.get_mut("id").map(Value::take),
+            text,
+        });
+    }
+    Ok(items)
+}
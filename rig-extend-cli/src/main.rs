@@ -0,0 +1,157 @@
+//! Category: main.rs (entry point and command dispatch)
+//! `rig-extend` command-line tool over rig-bailian, rig-volcengine and rig-tei:
+//! - args.rs: Command-line parsing and usage text
+//! - provider.rs: Client construction from flags and environment, default models
+//! - input.rs: Line-oriented input (plain text or JSONL with `text` / `id`)
+//! - chat.rs, embed.rs, rerank.rs, predict.rs: One module per command
+
+mod args;
+mod chat;
+mod embed;
+mod input;
+mod predict;
+mod provider;
+mod rerank;
+
+use std::io::Write;
+use std::process::ExitCode;
+
+use rig::client::{CompletionClient, EmbeddingsClient};
+use rig_bailian::{Bailian, RerankClient};
+use rig_volcengine::Volcengine;
+
+use args::{Args, Command, Format, Provider, UsageError};
+use provider::Task;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match args::parse(
+        std::env::args().skip(1),
+        std::env::var("RIG_EXTEND_PROVIDER").ok(),
+    ) {
+        Ok(args) => args,
+        Err(UsageError(message)) => {
+            eprintln!("error: {message}\n\nRun `rig-extend --help` for usage.");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        // Output piped into `head` and the like.
+        Err(err)
+            if err
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe) =>
+        {
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> anyhow::Result<()> {
+    let global = args.global;
+    match args.command {
+        Command::Help => writeln!(std::io::stdout(), "{}", args::USAGE)?,
+        Command::Version => writeln!(
+            std::io::stdout(),
+            "rig-extend {}",
+            env!("CARGO_PKG_VERSION")
+        )?,
+        Command::Chat(chat) => {
+            let format = global.format.unwrap_or(Format::Text);
+            let name = provider::model(&global, Task::Chat)?;
+            match global.provider {
+                Provider::Bailian => {
+                    let model = provider::client::<Bailian>(&global)?.completion_model(&name);
+                    chat::run(model, &name, chat, format).await?;
+                }
+                Provider::Volcengine => {
+                    let model = provider::client::<Volcengine>(&global)?.completion_model(&name);
+                    chat::run(model, &name, chat, format).await?;
+                }
+                Provider::Tei => return Err(provider::unsupported(global.provider, Task::Chat)),
+            }
+        }
+        Command::Embed(embed) => {
+            let format = global.format.unwrap_or(Format::Json);
+            let name = provider::model(&global, Task::Embed)?;
+            let items = input::items(&input::read(embed.input.as_deref())?)?;
+            let dims = embed.dims.unwrap_or(0);
+            match global.provider {
+                Provider::Bailian => {
+                    let mut model = provider::client::<Bailian>(&global)?
+                        .embedding_model_with_ndims(&name, dims);
+                    if let Some(size) = embed.batch_size {
+                        model = model.with_batch_size(size);
+                    }
+                    if let Some(concurrency) = embed.concurrency {
+                        model = model.with_concurrency(concurrency);
+                    }
+                    embed::run(model, items, format).await?;
+                }
+                Provider::Volcengine => {
+                    let mut model = provider::client::<Volcengine>(&global)?
+                        .embedding_model_with_ndims(&name, dims);
+                    if let Some(size) = embed.batch_size {
+                        model = model.with_batch_size(size);
+                    }
+                    if let Some(concurrency) = embed.concurrency {
+                        model = model.with_concurrency(concurrency);
+                    }
+                    embed::run(model, items, format).await?;
+                }
+                Provider::Tei => {
+                    let mut model = provider::tei(&global).embedding_model_with_ndims(&name, dims);
+                    if let Some(size) = embed.batch_size {
+                        model = model.with_batch_size(size);
+                    }
+                    if let Some(concurrency) = embed.concurrency {
+                        model = model.with_concurrency(concurrency);
+                    }
+                    embed::run(model, items, format).await?;
+                }
+            }
+        }
+        Command::Rerank(rerank) => {
+            let format = global.format.unwrap_or(Format::Text);
+            let name = provider::model(&global, Task::Rerank)?;
+            let documents = input::items(&input::read(rerank.documents.as_deref())?)?;
+            match global.provider {
+                Provider::Bailian => {
+                    let model = provider::client::<Bailian>(&global)?
+                        .rerank_model(&name, rerank.rerank_url.clone());
+                    rerank::run(&model, &rerank.query, documents, rerank.top_n, format).await?;
+                }
+                Provider::Tei => {
+                    let client = provider::tei(&global);
+                    rerank::run(&client, &rerank.query, documents, rerank.top_n, format).await?;
+                }
+                Provider::Volcengine => {
+                    return Err(provider::unsupported(global.provider, Task::Rerank));
+                }
+            }
+        }
+        Command::Predict(predict) => {
+            let format = global.format.unwrap_or(Format::Text);
+            if global.provider != Provider::Tei {
+                return Err(provider::unsupported(global.provider, Task::Predict));
+            }
+            let items = if predict.texts.is_empty() {
+                input::items(&input::read(None)?)?
+            } else {
+                predict
+                    .texts
+                    .into_iter()
+                    .map(|text| input::Item { id: None, text })
+                    .collect()
+            };
+            predict::run(&provider::tei(&global), items, format).await?;
+        }
+    }
+    Ok(())
+}
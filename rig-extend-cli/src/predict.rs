@@ -0,0 +1,43 @@
+//! Category: predict.rs (`predict`: TEI sequence classification)
+
+use std::io::Write;
+
+use serde_json::json;
+
+use super::args::Format;
+use super::embed::preview;
+use super::input::Item;
+
+pub async fn run(client: &rig_tei::Client, items: Vec<Item>, format: Format) -> anyhow::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    for (index, item) in items.into_iter().enumerate() {
+        let response = client.predict([item.text.clone()]).await?;
+        match format {
+            Format::Json => {
+                let mut line = json!({
+                    "index": index,
+                    "text": item.text,
+                    "labels": response.items,
+                });
+                if let Some(id) = item.id {
+                    line["id"] = id;
+                }
+                writeln!(stdout, "{line}")?;
+            }
+            Format::Text => {
+                let labels: Vec<String> = response
+                    .items
+                    .iter()
+                    .map(|label| format!("{} {:.4}", label.label, label.score))
+                    .collect();
+                writeln!(
+                    stdout,
+                    "{index}\t{}\t{}",
+                    labels.join(", "),
+                    preview(&item.text)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Category: provider.rs (client construction from flags and environment, default models)
+
+use anyhow::Context;
+use rig_extend_core::{Profile, RetryPolicy};
+
+use super::args::{Global, Provider};
+
+/// Commands a provider can serve, for default models and error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    Chat,
+    Embed,
+    Rerank,
+    Predict,
+}
+
+impl Task {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Chat => "chat",
+            Self::Embed => "embed",
+            Self::Rerank => "rerank",
+            Self::Predict => "predict",
+        }
+    }
+}
+
+/// `--model`, or the provider's default model for `task`.
+pub fn model(global: &Global, task: Task) -> anyhow::Result<String> {
+    if let Some(model) = &global.model {
+        return Ok(model.clone());
+    }
+    let model = match (global.provider, task) {
+        (Provider::Bailian, Task::Chat) => rig_bailian::QWEN3_MAX,
+        (Provider::Bailian, Task::Embed) => rig_bailian::TEXT_EMBEDDING_V4,
+        (Provider::Bailian, Task::Rerank) => rig_bailian::GTE_RERANK_V2,
+        (Provider::Volcengine, Task::Chat) => rig_volcengine::DOUBAO_SEED,
+        (Provider::Volcengine, Task::Embed) => rig_volcengine::TEXT_DOUBAO_EMBEDDING,
+        // TEI serves a single model chosen when the server starts.
+        (Provider::Tei, Task::Embed | Task::Rerank | Task::Predict) => "tei",
+        (provider, task) => return Err(unsupported(provider, task)),
+    };
+    Ok(model.to_string())
+}
+
+pub fn unsupported(provider: Provider, task: Task) -> anyhow::Error {
+    anyhow::anyhow!(
+        "provider `{}` does not support `{}`",
+        provider.name(),
+        task.name()
+    )
+}
+
+/// OpenAI-compatible client for profile `P`, retrying transient failures.
+pub fn client<P: Profile>(global: &Global) -> anyhow::Result<rig_extend_core::Client<P>> {
+    let api_key = global
+        .api_key
+        .clone()
+        .or_else(|| std::env::var(P::API_KEY_ENV).ok())
+        .filter(|key| !key.is_empty())
+        .with_context(|| format!("no API key: pass --api-key or set {}", P::API_KEY_ENV))?;
+    let base_url = global
+        .base_url
+        .clone()
+        .or_else(|| std::env::var(P::BASE_URL_ENV).ok())
+        .unwrap_or_else(|| P::BASE_URL.to_string());
+    Ok(rig_extend_core::Client::<P>::builder(&api_key)
+        .base_url(&base_url)
+        .retry_policy(RetryPolicy::default())
+        .build())
+}
+
+pub fn tei(global: &Global) -> rig_tei::Client {
+    let base_url = global
+        .base_url
+        .clone()
+        .or_else(|| std::env::var(rig_tei::TEI_BASE_URL_ENV).ok())
+        .unwrap_or_else(|| rig_tei::TEI_DEFAULT_BASE_URL.to_string());
+    rig_tei::Client::builder()
+        .base_url(&base_url)
+        .retry_policy(RetryPolicy::default())
+        .build()
+}
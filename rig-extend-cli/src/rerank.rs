@@ -0,0 +1,54 @@
+//! Category: rerank.rs (`rerank`: a query and a document file, best match first)
+
+use std::io::Write;
+
+use rig_extend_core::Reranker;
+use serde_json::json;
+
+use super::args::Format;
+use super::embed::preview;
+use super::input::Item;
+
+pub async fn run<R>(
+    reranker: &R,
+    query: &str,
+    documents: Vec<Item>,
+    top_n: Option<usize>,
+    format: Format,
+) -> anyhow::Result<()>
+where
+    R: Reranker,
+{
+    let texts: Vec<String> = documents.iter().map(|doc| doc.text.clone()).collect();
+    let results = reranker.rerank(query, &texts, top_n).await?;
+
+    let mut stdout = std::io::stdout().lock();
+    for (rank, result) in results.iter().enumerate() {
+        let Some(document) = documents.get(result.index) else {
+            anyhow::bail!("provider returned unknown document index {}", result.index);
+        };
+        match format {
+            Format::Json => {
+                let mut line = json!({
+                    "rank": rank + 1,
+                    "index": result.index,
+                    "score": result.relevance_score,
+                    "text": document.text,
+                });
+                if let Some(id) = &document.id {
+                    line["id"] = id.clone();
+                }
+                writeln!(stdout, "{line}")?;
+            }
+            Format::Text => writeln!(
+                stdout,
+                "{:>3}. {:.4}  #{:<4} {}",
+                rank + 1,
+                result.relevance_score,
+                result.index,
+                preview(&document.text)
+            )?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use std::process::Stdio;
+
+use rig_extend_mock::{MockServer, defaults, fixtures, paths};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+struct Output {
+    code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+/// Run the binary with `args` and `stdin`, without any provider variables from the host.
+async fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rig-extend"))
+        .args(args)
+        .env_remove("RIG_EXTEND_PROVIDER")
+        .env_remove("BAILIAN_API_KEY")
+        .env_remove("BAILIAN_BASE_URL")
+        .env_remove("VOLCENGINE_API_KEY")
+        .env_remove("VOLCENGINE_BASE_URL")
+        .env_remove("TEI_BASE_URL")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    input.write_all(stdin.as_bytes()).await.unwrap();
+    drop(input);
+    let output = child.wait_with_output().await.unwrap();
+    Output {
+        code: output.status.code().unwrap_or(-1),
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+    }
+}
+
+fn json_lines(stdout: &str) -> Vec<Value> {
+    stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn chat_streams_with_system_prompt() {
+    let server = MockServer::start().await;
+    let base_url = server.dashscope_base_url();
+    let out = run(
+        &[
+            "chat",
+            "--base-url",
+            &base_url,
+            "--api-key",
+            "test-key",
+            "-s",
+            "Be brief.",
+            "hello",
+            "there",
+        ],
+        "",
+    )
+    .await;
+
+    assert_eq!(out.code, 0, "{}", out.stderr);
+    assert_eq!(out.stdout.trim_end(), defaults::DEFAULT_REPLY);
+    assert!(
+        out.stderr.contains("10 input + 5 output tokens"),
+        "{}",
+        out.stderr
+    );
+
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[0].json();
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["model"], rig_bailian::QWEN3_MAX);
+    assert_eq!(body["messages"][0]["content"][0]["text"], "Be brief.");
+    assert_eq!(body["messages"][1]["content"][0]["text"], "hello there");
+}
+
+#[tokio::test]
+async fn chat_json_output_reads_prompt_from_stdin() {
+    let server = MockServer::start().await;
+    let base_url = server.ark_base_url();
+    let out = run(
+        &[
+            "--provider",
+            "volcengine",
+            "--model=doubao-test",
+            "--base-url",
+            &base_url,
+            "--api-key",
+            "test-key",
+            "--format",
+            "json",
+            "chat",
+            "--no-stream",
+        ],
+        "what is rust?\n",
+    )
+    .await;
+
+    assert_eq!(out.code, 0, "{}", out.stderr);
+    let reply = &json_lines(&out.stdout)[0];
+    assert_eq!(reply["model"], "doubao-test");
+    assert_eq!(reply["text"], defaults::DEFAULT_REPLY);
+    assert_eq!(reply["usage"]["input_tokens"], 10);
+    assert_eq!(
+        server.requests_to(paths::ARK_CHAT)[0].json()["stream"],
+        Value::Null
+    );
+}
+
+#[tokio::test]
+async fn embed_writes_jsonl_in_input_order() {
+    let server = MockServer::start().await;
+    let base_url = server.dashscope_base_url();
+    let mut input = String::new();
+    for i in 0..12 {
+        input.push_str(&format!(
+            "{{\"id\": \"doc-{i}\", \"text\": \"text {i}\"}}\n"
+        ));
+    }
+    input.push_str("\nplain line\n");
+
+    let out = run(
+        &[
+            "embed",
+            "--base-url",
+            &base_url,
+            "--api-key",
+            "test-key",
+            "--dims",
+            "8",
+        ],
+        &input,
+    )
+    .await;
+
+    assert_eq!(out.code, 0, "{}", out.stderr);
+    let lines = json_lines(&out.stdout);
+    assert_eq!(lines.len(), 13);
+    assert_eq!(lines[3]["id"], "doc-3");
+    assert_eq!(lines[3]["index"], 3);
+    assert_eq!(
+        lines[3]["embedding"],
+        serde_json::to_value(fixtures::embedding("text 3", 8)).unwrap()
+    );
+    assert_eq!(lines[12]["text"], "plain line");
+    assert!(lines[12].get("id").is_none());
+    // text-embedding-v4 takes 10 inputs per call.
+    assert_eq!(server.requests_to(paths::DASHSCOPE_EMBEDDINGS).len(), 2);
+}
+
+#[tokio::test]
+async fn rerank_reads_documents_file() {
+    let server = MockServer::start().await;
+    let base_url = server.tei_base_url();
+    let documents =
+        std::env::temp_dir().join(format!("rig-extend-docs-{}.txt", std::process::id()));
+    std::fs::write(&documents, "cats purr\ndogs bark loudly\nbirds sing\n").unwrap();
+
+    let out = run(
+        &[
+            "-p",
+            "tei",
+            "--base-url",
+            &base_url,
+            "rerank",
+            "-q",
+            "dogs bark",
+            "-d",
+            documents.to_str().unwrap(),
+            "-n",
+            "1",
+        ],
+        "",
+    )
+    .await;
+    std::fs::remove_file(&documents).unwrap();
+
+    assert_eq!(out.code, 0, "{}", out.stderr);
+    assert_eq!(out.stdout.lines().count(), 1);
+    assert!(out.stdout.contains("#1"), "{}", out.stdout);
+    assert!(out.stdout.contains("dogs bark loudly"), "{}", out.stdout);
+}
+
+#[tokio::test]
+async fn bailian_rerank_json_keeps_ids() {
+    let server = MockServer::start().await;
+    let rerank_url = server.dashscope_rerank_url();
+    let out = run(
+        &[
+            "rerank",
+            "--api-key",
+            "test-key",
+            "--rerank-url",
+            &rerank_url,
+            "--query",
+            "bananas",
+            "--documents",
+            "-",
+            "-f",
+            "json",
+        ],
+        "{\"id\": 7, \"text\": \"Rust is fast\"}\n{\"id\": 8, \"text\": \"Bananas are yellow\"}\n",
+    )
+    .await;
+
+    assert_eq!(out.code, 0, "{}", out.stderr);
+    let lines = json_lines(&out.stdout);
+    assert_eq!(lines[0]["rank"], 1);
+    assert_eq!(lines[0]["id"], 8);
+    assert_eq!(lines[0]["text"], "Bananas are yellow");
+}
+
+#[tokio::test]
+async fn predict_prints_labels() {
+    let server = MockServer::start().await;
+    let base_url = server.tei_base_url();
+    let out = run(
+        &[
+            "predict",
+            "-p",
+            "tei",
+            "--base-url",
+            &base_url,
+            "-f",
+            "json",
+            "I love this",
+        ],
+        "",
+    )
+    .await;
+
+    assert_eq!(out.code, 0, "{}", out.stderr);
+    let line = &json_lines(&out.stdout)[0];
+    assert_eq!(line["text"], "I love this");
+    assert!(line["labels"][0]["label"].is_string());
+}
+
+#[tokio::test]
+async fn reports_usage_and_runtime_errors() {
+    let out = run(&["--provider", "openai", "chat", "hi"], "").await;
+    assert_eq!(out.code, 2);
+    assert!(
+        out.stderr.contains("unknown provider `openai`"),
+        "{}",
+        out.stderr
+    );
+
+    let out = run(&["rerank", "--query", "q"], "").await;
+    assert_eq!(out.code, 2);
+    assert!(out.stderr.contains("--documents"), "{}", out.stderr);
+
+    let out = run(&["chat", "hi"], "").await;
+    assert_eq!(out.code, 1);
+    assert!(out.stderr.contains("BAILIAN_API_KEY"), "{}", out.stderr);
+
+    let out = run(
+        &["-p", "volcengine", "rerank", "-q", "q", "-d", "-"],
+        "doc\n",
+    )
+    .await;
+    assert_eq!(out.code, 1);
+    assert!(
+        out.stderr.contains("does not support `rerank`"),
+        "{}",
+        out.stderr
+    );
+
+    let out = run(&["--help"], "").await;
+    assert_eq!(out.code, 0);
+    assert!(out.stdout.starts_with("Usage: rig-extend"));
+}
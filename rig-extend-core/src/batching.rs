@@ -0,0 +1,160 @@
+//! Category: batching.rs (split embedding inputs into per-model batches, run them concurrently)
+
+use std::ops::Range;
+
+use futures::StreamExt;
+use rig::embeddings::{Embedding, EmbeddingError};
+
+/// Batches run at once when a model does not say otherwise.
+pub const DEFAULT_EMBEDDING_CONCURRENCY: usize = 4;
+
+/// How one `embed_texts` call is split into requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddingBatching {
+    /// Inputs per request; the provider's per-call limit for the model.
+    pub batch_size: usize,
+    /// Requests in flight at once for one call. Client rate limits still apply on top.
+    pub concurrency: usize,
+}
+
+impl EmbeddingBatching {
+    pub fn new(batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            concurrency: DEFAULT_EMBEDDING_CONCURRENCY,
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Input ranges of each batch for `len` texts.
+    pub fn ranges(&self, len: usize) -> Vec<Range<usize>> {
+        (0..len)
+            .step_by(self.batch_size)
+            .map(|start| start..(start + self.batch_size).min(len))
+            .collect()
+    }
+}
+
+/// One batch that failed.
+#[derive(Debug)]
+pub struct BatchFailure {
+    /// Position of the batch, starting at 0.
+    pub batch: usize,
+    /// Input positions the batch covered.
+    pub range: Range<usize>,
+    pub error: EmbeddingError,
+}
+
+/// Some batches of a call failed. Embeddings of the successful batches are kept, in input
+/// order, with `None` for texts whose batch failed.
+#[derive(thiserror::Error, Debug)]
+#[error("{} of {batches} embedding batches failed; first: {}", failures.len(), failures[0].error)]
+pub struct EmbeddingBatchError {
+    pub batches: usize,
+    pub failures: Vec<BatchFailure>,
+    pub embeddings: Vec<Option<Embedding>>,
+}
+
+impl EmbeddingBatchError {
+    /// The error of the first failed batch, as returned by `EmbeddingModel::embed_texts`.
+    pub fn into_first_error(mut self) -> EmbeddingError {
+        self.failures.swap_remove(0).error
+    }
+
+    /// Input ranges of the failed batches, for a retry.
+    pub fn failed_ranges(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.failures.iter().map(|failure| failure.range.clone())
+    }
+}
+
+/// Embed `texts` in batches with `embed`, running up to `batching.concurrency` at once, and
+/// reassemble the results in input order.
+pub async fn embed_in_batches<F, Fut>(
+    texts: Vec<String>,
+    batching: EmbeddingBatching,
+    embed: F,
+) -> Result<Vec<Embedding>, EmbeddingBatchError>
+where
+    F: Fn(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<Embedding>, EmbeddingError>>,
+{
+    let len = texts.len();
+    let ranges = batching.ranges(len);
+    if ranges.len() <= 1 {
+        return embed(texts).await.map_err(|error| EmbeddingBatchError {
+            batches: 1,
+            failures: vec![BatchFailure {
+                batch: 0,
+                range: 0..len,
+                error,
+            }],
+            embeddings: (0..len).map(|_| None).collect(),
+        });
+    }
+
+    let batches = ranges.len();
+    let embed = &embed;
+    let mut results =
+        futures::stream::iter(ranges.into_iter().enumerate().map(|(batch, range)| {
+            let inputs = texts[range.clone()].to_vec();
+            async move {
+                let result = embed(inputs).await.and_then(|embeddings| {
+                    if embeddings.len() == range.len() {
+                        Ok(embeddings)
+                    } else {
+                        Err(EmbeddingError::ResponseError(
+                            "Response data length does not match input length".into(),
+                        ))
+                    }
+                });
+                (batch, range, result)
+            }
+        }))
+        .buffer_unordered(batching.concurrency);
+
+    let mut embeddings: Vec<Option<Embedding>> = (0..len).map(|_| None).collect();
+    let mut failures = Vec::new();
+    while let Some((batch, range, result)) = results.next().await {
+        match result {
+            Ok(batch_embeddings) => {
+                for (slot, embedding) in embeddings[range].iter_mut().zip(batch_embeddings) {
+                    *slot = Some(embedding);
+                }
+            }
+            Err(error) => {
+                tracing::warn!(
+                    target: "rig::embeddings",
+                    batch,
+                    start = range.start,
+                    end = range.end,
+                    "embedding batch failed: {error}"
+                );
+                failures.push(BatchFailure {
+                    batch,
+                    range,
+                    error,
+                });
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(embeddings.into_iter().flatten().collect())
+    } else {
+        failures.sort_by_key(|failure| failure.batch);
+        Err(EmbeddingBatchError {
+            batches,
+            failures,
+            embeddings,
+        })
+    }
+}
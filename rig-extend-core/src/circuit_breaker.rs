@@ -0,0 +1,424 @@
+//! Category: circuit_breaker.rs (per-endpoint circuit breaker with half-open probing)
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use rig::client::{VerifyClient, VerifyError};
+use rig::http_client::{self, Response};
+use rig::wasm_compat::WasmBoxedFuture;
+
+use super::error::{ErrorClass, ProviderError, ProviderErrorExt};
+use super::rt::Instant;
+
+/// [`ProviderError::code`] of requests rejected because their endpoint's circuit is open.
+pub const CIRCUIT_OPEN: &str = "CircuitOpen";
+
+/// When a client stops sending requests to a failing endpoint.
+///
+/// A circuit opens after `consecutive_failures` failures in a row, or when the share of
+/// failures among the last `window` requests reaches `error_rate`. While open, requests fail
+/// immediately with a `server` [`ProviderError`] coded [`CIRCUIT_OPEN`], which the router
+/// fails over on and the retry policy does not retry. After `open_for` the circuit is
+/// half-open: one probe (the client's `verify` when it has one, otherwise the next request)
+/// decides whether it closes or opens again.
+///
+/// Failures are transport errors (including timeouts) and `failure_statuses`; rate limits,
+/// auth and invalid requests say nothing about the endpoint's health.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreakerPolicy {
+    pub consecutive_failures: Option<u32>,
+    /// Failure share in `0.0..=1.0` and the number of recent requests it is measured over.
+    pub error_rate: Option<(f64, usize)>,
+    pub open_for: Duration,
+    pub failure_statuses: Vec<u16>,
+}
+
+impl Default for CircuitBreakerPolicy {
+    /// Open after 5 consecutive failures for 30s; 408 and 5xx gateway statuses count.
+    fn default() -> Self {
+        Self {
+            consecutive_failures: Some(5),
+            error_rate: None,
+            open_for: Duration::from_secs(30),
+            failure_statuses: vec![408, 500, 502, 503, 504],
+        }
+    }
+}
+
+impl CircuitBreakerPolicy {
+    pub fn consecutive_failures(mut self, failures: u32) -> Self {
+        self.consecutive_failures = Some(failures.max(1));
+        self
+    }
+
+    /// Open when at least `rate` of the last `window` requests failed.
+    pub fn error_rate(mut self, rate: f64, window: usize) -> Self {
+        self.error_rate = Some((rate.clamp(0.0, 1.0), window.max(1)));
+        self
+    }
+
+    pub fn open_for(mut self, duration: Duration) -> Self {
+        self.open_for = duration;
+        self
+    }
+
+    pub fn failure_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.failure_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    pub fn is_failure_status(&self, status: StatusCode) -> bool {
+        self.failure_statuses.contains(&status.as_u16())
+    }
+
+    /// Whether a failed request counts against the endpoint.
+    pub fn is_failure(&self, error: &http_client::Error) -> bool {
+        match error {
+            http_client::Error::InvalidStatusCode(status)
+            | http_client::Error::InvalidStatusCodeWithMessage(status, _) => {
+                self.is_failure_status(*status)
+            }
+            http_client::Error::Instance(err) => match err.downcast_ref::<ProviderError>() {
+                Some(ProviderError {
+                    status: Some(status),
+                    ..
+                }) => StatusCode::from_u16(*status).is_ok_and(|s| self.is_failure_status(s)),
+                Some(_) => false,
+                None => true,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half-open",
+        })
+    }
+}
+
+/// Health check run when a circuit turns half-open; `true` means the provider answers.
+pub type Probe = Arc<dyn Fn() -> WasmBoxedFuture<'static, bool> + Send + Sync>;
+
+/// Probe calling `client.verify()`; any answer other than a server or transport failure
+/// counts as up. Give it a client over [`Transport::unguarded`](crate::Transport::unguarded)
+/// so the probe is one request that neither retries nor goes through the breaker.
+pub fn verify_probe<C>(client: C) -> Probe
+where
+    C: VerifyClient + Clone + Send + Sync + 'static,
+{
+    Arc::new(move || {
+        let client = client.clone();
+        Box::pin(async move {
+            match client.verify().await {
+                Ok(()) | Err(VerifyError::InvalidAuthentication) => true,
+                Err(VerifyError::HttpError(err)) => err
+                    .provider_error()
+                    .is_some_and(|err| err.status.is_some_and(|status| status < 500)),
+                Err(_) => false,
+            }
+        })
+    })
+}
+
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    consecutive_failures: u32,
+    recent: VecDeque<bool>,
+    opened_at: Instant,
+    probing: bool,
+}
+
+impl Circuit {
+    fn new() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            recent: VecDeque::new(),
+            opened_at: Instant::now(),
+            probing: false,
+        }
+    }
+}
+
+/// Circuits of one client, keyed by endpoint URL (without query string).
+///
+/// Shared by every clone of the client; state changes are logged on the `rig::circuit`
+/// tracing target with `endpoint`, `from` and `to` fields.
+pub struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    circuits: Mutex<HashMap<String, Circuit>>,
+    probe: OnceLock<Probe>,
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("policy", &self.policy)
+            .field("probe", &self.probe.get().is_some())
+            .finish()
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: CircuitBreakerPolicy) -> Self {
+        Self {
+            policy,
+            circuits: Mutex::new(HashMap::new()),
+            probe: OnceLock::new(),
+        }
+    }
+
+    pub fn policy(&self) -> &CircuitBreakerPolicy {
+        &self.policy
+    }
+
+    /// Use `probe` instead of a live request to test half-open circuits. Only the first
+    /// probe set is kept.
+    pub fn set_probe(&self, probe: Probe) {
+        let _ = self.probe.set(probe);
+    }
+
+    /// State of the circuit for `endpoint` (a full URL); unseen endpoints are closed.
+    pub fn state(&self, endpoint: &str) -> CircuitState {
+        let endpoint = endpoint.split('?').next().unwrap_or_default();
+        let circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(circuit) = circuits.get(endpoint) else {
+            return CircuitState::Closed;
+        };
+        match circuit.state {
+            CircuitState::Open if circuit.opened_at.elapsed() >= self.policy.open_for => {
+                CircuitState::HalfOpen
+            }
+            state => state,
+        }
+    }
+
+    /// Every endpoint seen so far with its state.
+    pub fn states(&self) -> Vec<(String, CircuitState)> {
+        let endpoints: Vec<String> = {
+            let circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+            circuits.keys().cloned().collect()
+        };
+        let mut states: Vec<_> = endpoints
+            .into_iter()
+            .map(|endpoint| {
+                let state = self.state(&endpoint);
+                (endpoint, state)
+            })
+            .collect();
+        states.sort_by(|a, b| a.0.cmp(&b.0));
+        states
+    }
+
+    /// Close every circuit, e.g. after switching the network back on.
+    pub fn reset(&self) {
+        self.circuits
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Let a request to `endpoint` through, or reject it while the circuit is open.
+    pub(crate) async fn admit(
+        self: &Arc<Self>,
+        endpoint: &str,
+    ) -> Result<CircuitPermit, ProviderError> {
+        let trial = {
+            let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+            let circuit = circuits
+                .entry(endpoint.to_string())
+                .or_insert_with(Circuit::new);
+            if circuit.state == CircuitState::Open
+                && circuit.opened_at.elapsed() >= self.policy.open_for
+            {
+                transition(endpoint, circuit, CircuitState::HalfOpen);
+            }
+            match circuit.state {
+                CircuitState::Closed => false,
+                CircuitState::HalfOpen if !circuit.probing => {
+                    circuit.probing = true;
+                    true
+                }
+                _ => return Err(self.open_error(endpoint, circuit)),
+            }
+        };
+
+        let permit = CircuitPermit {
+            breaker: self.clone(),
+            endpoint: endpoint.to_string(),
+            trial,
+            finished: false,
+        };
+        match self.probe.get() {
+            Some(probe) if trial => {
+                if probe().await {
+                    permit.finish(false);
+                    Ok(CircuitPermit {
+                        breaker: self.clone(),
+                        endpoint: endpoint.to_string(),
+                        trial: false,
+                        finished: false,
+                    })
+                } else {
+                    permit.finish(true);
+                    let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+                    let circuit = circuits
+                        .entry(endpoint.to_string())
+                        .or_insert_with(Circuit::new);
+                    Err(self.open_error(endpoint, circuit))
+                }
+            }
+            _ => Ok(permit),
+        }
+    }
+
+    fn open_error(&self, endpoint: &str, circuit: &Circuit) -> ProviderError {
+        let retry_in = self
+            .policy
+            .open_for
+            .saturating_sub(circuit.opened_at.elapsed());
+        ProviderError {
+            status: None,
+            code: Some(CIRCUIT_OPEN.to_string()),
+            error_type: None,
+            message: format!(
+                "circuit open for {endpoint} after repeated failures; next probe in {}s",
+                retry_in.as_secs()
+            ),
+            request_id: None,
+            class: ErrorClass::Server,
+        }
+    }
+
+    fn record(&self, endpoint: &str, failed: bool, trial: bool) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let circuit = circuits
+            .entry(endpoint.to_string())
+            .or_insert_with(Circuit::new);
+        if trial {
+            circuit.probing = false;
+        }
+
+        if let Some((_, window)) = self.policy.error_rate {
+            circuit.recent.push_back(failed);
+            while circuit.recent.len() > window {
+                circuit.recent.pop_front();
+            }
+        }
+        if !failed {
+            circuit.consecutive_failures = 0;
+            if circuit.state == CircuitState::HalfOpen {
+                transition(endpoint, circuit, CircuitState::Closed);
+            }
+            return;
+        }
+
+        circuit.consecutive_failures += 1;
+        let too_many = self
+            .policy
+            .consecutive_failures
+            .is_some_and(|n| circuit.consecutive_failures >= n);
+        let too_often = self.policy.error_rate.is_some_and(|(rate, window)| {
+            let failures = circuit.recent.iter().filter(|f| **f).count();
+            circuit.recent.len() >= window && failures as f64 >= rate * window as f64
+        });
+        match circuit.state {
+            CircuitState::HalfOpen => transition(endpoint, circuit, CircuitState::Open),
+            CircuitState::Closed if too_many || too_often => {
+                transition(endpoint, circuit, CircuitState::Open)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn transition(endpoint: &str, circuit: &mut Circuit, to: CircuitState) {
+    let from = circuit.state;
+    circuit.state = to;
+    match to {
+        CircuitState::Open => {
+            circuit.opened_at = Instant::now();
+            tracing::warn!(
+                target: "rig::circuit",
+                endpoint,
+                from = %from,
+                to = %to,
+                consecutive_failures = circuit.consecutive_failures,
+                "circuit opened"
+            );
+        }
+        CircuitState::HalfOpen => {
+            tracing::info!(target: "rig::circuit", endpoint, from = %from, to = %to, "circuit half-open");
+        }
+        CircuitState::Closed => {
+            circuit.consecutive_failures = 0;
+            circuit.recent.clear();
+            tracing::info!(target: "rig::circuit", endpoint, from = %from, to = %to, "circuit closed");
+        }
+    }
+}
+
+/// Admission of one request; report its outcome with [`finish`](Self::finish). A trial that
+/// is dropped unfinished (e.g. cancelled) lets the next request probe instead.
+pub(crate) struct CircuitPermit {
+    breaker: Arc<CircuitBreaker>,
+    endpoint: String,
+    trial: bool,
+    finished: bool,
+}
+
+impl CircuitPermit {
+    pub(crate) fn finish(mut self, failed: bool) {
+        self.finished = true;
+        self.breaker.record(&self.endpoint, failed, self.trial);
+    }
+
+    pub(crate) fn finish_with<B>(self, result: &http_client::Result<Response<B>>) {
+        let failed = match result {
+            Ok(response) => self.breaker.policy.is_failure_status(response.status()),
+            Err(err) => self.breaker.policy.is_failure(err),
+        };
+        self.finish(failed);
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if self.trial && !self.finished {
+            let mut circuits = self
+                .breaker
+                .circuits
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if let Some(circuit) = circuits.get_mut(&self.endpoint) {
+                circuit.probing = false;
+            }
+        }
+    }
+}
+
+/// Circuit key of a request: scheme, authority and path.
+pub(crate) fn endpoint_key(uri: &http_client::Uri) -> String {
+    format!(
+        "{}://{}{}",
+        uri.scheme_str().unwrap_or("http"),
+        uri.authority().map(|a| a.as_str()).unwrap_or_default(),
+        uri.path()
+    )
+}
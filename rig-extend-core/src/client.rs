@@ -0,0 +1,460 @@
+//! Category: client.rs (Client and Builder; implements Provider/Verify/Completion/Embedding)
+
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use rig::client::{CompletionClient, EmbeddingsClient, ProviderClient, VerifyClient, VerifyError};
+use rig::http_client::{self, HttpClientExt};
+
+use super::circuit_breaker::{CircuitBreaker, CircuitBreakerPolicy, verify_probe};
+use super::completion::CompletionModel;
+use super::config::ClientConfig;
+#[cfg(feature = "reqwest")]
+use super::config::{ConfigError, FromClientConfig};
+use super::embedding::EmbeddingModel;
+use super::error::{ErrorClass, ProviderErrorExt};
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+use super::http::{HttpOptions, HttpOptionsExt};
+use super::profile::Profile;
+use super::rate_limit::RateLimits;
+use super::retry::RetryPolicy;
+use super::transport::Transport;
+use super::usage::{Operation, TokenUsage, UsageLedger};
+
+/// Provider client: Client<P, T>
+#[derive(Clone)]
+pub struct Client<
+    P,
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
+    pub(crate) base_url: String,
+    pub(crate) api_key: String,
+    pub(crate) http_client: Transport<T>,
+    endpoints: BTreeMap<String, String>,
+    usage: Option<UsageLedger>,
+    usage_tag: Option<String>,
+    profile: PhantomData<P>,
+}
+
+impl<P, T> std::fmt::Debug for Client<P, T>
+where
+    P: Profile,
+    T: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("provider", &P::NAME)
+            .field("base_url", &self.base_url)
+            .field("http_client", &self.http_client)
+            .field("usage_tag", &self.usage_tag)
+            .field("api_key", &"<REDACTED>")
+            .finish()
+    }
+}
+
+/// Client builder: ClientBuilder<'a, P, T>
+#[derive(Clone)]
+pub struct ClientBuilder<
+    'a,
+    P,
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
+    api_key: &'a str,
+    base_url: &'a str,
+    http_client: T,
+    #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+    http_options: Option<HttpOptions>,
+    retry_policy: RetryPolicy,
+    rate_limits: RateLimits,
+    circuit_breaker: Option<CircuitBreakerPolicy>,
+    endpoints: BTreeMap<String, String>,
+    usage: Option<UsageLedger>,
+    profile: PhantomData<P>,
+}
+
+impl<'a, P, T> ClientBuilder<'a, P, T>
+where
+    P: Profile,
+    T: Default,
+{
+    pub fn new(api_key: &'a str) -> Self {
+        Self {
+            api_key,
+            base_url: P::BASE_URL,
+            http_client: Default::default(),
+            #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+            http_options: None,
+            retry_policy: RetryPolicy::none(),
+            rate_limits: RateLimits::default(),
+            circuit_breaker: None,
+            endpoints: BTreeMap::new(),
+            usage: None,
+            profile: PhantomData,
+        }
+    }
+}
+
+impl<'a, P, T> ClientBuilder<'a, P, T> {
+    pub fn base_url(mut self, base_url: &'a str) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Use a prebuilt HTTP client; this drops any HTTP options set so far.
+    pub fn with_client<U>(self, http_client: U) -> ClientBuilder<'a, P, U> {
+        ClientBuilder {
+            api_key: self.api_key,
+            base_url: self.base_url,
+            http_client,
+            #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+            http_options: None,
+            retry_policy: self.retry_policy,
+            rate_limits: self.rate_limits,
+            circuit_breaker: self.circuit_breaker,
+            endpoints: self.endpoints,
+            usage: self.usage,
+            profile: PhantomData,
+        }
+    }
+
+    /// Retry policy applied to every request made through the client (default: no retries).
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Requests per minute across every model built from the client.
+    pub fn requests_per_minute(mut self, rpm: u32) -> Self {
+        self.rate_limits = self.rate_limits.requests_per_minute(rpm);
+        self
+    }
+
+    /// Estimated tokens per minute across every model built from the client.
+    pub fn tokens_per_minute(mut self, tpm: u32) -> Self {
+        self.rate_limits = self.rate_limits.tokens_per_minute(tpm);
+        self
+    }
+
+    /// Maximum concurrent requests across every model built from the client.
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        self.rate_limits = self.rate_limits.max_in_flight(max);
+        self
+    }
+
+    /// Replace all client-side rate limits at once (default: unlimited).
+    pub fn rate_limits(mut self, rate_limits: RateLimits) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Fail fast on endpoints that keep failing (default: off). Half-open circuits are
+    /// probed with `verify`.
+    pub fn circuit_breaker(mut self, policy: CircuitBreakerPolicy) -> Self {
+        self.circuit_breaker = Some(policy);
+        self
+    }
+
+    /// Full URL for a provider endpoint outside `base_url`, e.g. Bailian's `rerank`.
+    pub fn endpoint(mut self, name: &str, url: &str) -> Self {
+        self.endpoints.insert(name.to_string(), url.to_string());
+        self
+    }
+
+    /// Record token usage of every completion, streaming, embedding, rerank and
+    /// image generation call.
+    pub fn usage_ledger(mut self, ledger: UsageLedger) -> Self {
+        self.usage = Some(ledger);
+        self
+    }
+
+    /// Build the client.
+    ///
+    /// # Panics
+    ///
+    /// If HTTP options were set and the `reqwest::Client` cannot be built (e.g. the TLS
+    /// backend fails to initialise); see [`try_build`](Self::try_build).
+    pub fn build(self) -> Client<P, T>
+    where
+        P: Profile + 'static,
+        T: HttpClientExt + Clone + Send + Sync + 'static,
+    {
+        self.try_build()
+            .unwrap_or_else(|err| panic!("failed to build HTTP client: {err}"))
+    }
+
+    /// Build the client, reporting HTTP options the `reqwest::Client` rejects (e.g. an
+    /// invalid certificate) instead of panicking.
+    #[cfg_attr(
+        any(not(feature = "reqwest"), target_family = "wasm"),
+        allow(unused_mut)
+    )]
+    pub fn try_build(mut self) -> http_client::Result<Client<P, T>>
+    where
+        P: Profile + 'static,
+        T: HttpClientExt + Clone + Send + Sync + 'static,
+    {
+        #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+        if let Some(options) = &self.http_options
+            && let Some(client) = (&mut self.http_client as &mut dyn Any).downcast_mut()
+        {
+            *client = options
+                .build()
+                .map_err(|err| http_client::Error::Instance(err.into()))?;
+        }
+        let mut client = Client {
+            base_url: self.base_url.to_string(),
+            api_key: self.api_key.to_string(),
+            http_client: Transport::new(self.http_client, self.retry_policy)
+                .with_rate_limits(self.rate_limits),
+            endpoints: self.endpoints,
+            usage: self.usage,
+            usage_tag: None,
+            profile: PhantomData,
+        };
+        if let Some(policy) = self.circuit_breaker {
+            let mut probe = client.clone();
+            probe.http_client = client.http_client.unguarded();
+            client.http_client = client
+                .http_client
+                .with_circuit_breaker(policy)
+                .with_probe(verify_probe(probe));
+        }
+        Ok(client)
+    }
+}
+
+/// HTTP options for the default `reqwest::Client`; see [`HttpOptionsExt`].
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+impl<P> HttpOptionsExt for ClientBuilder<'_, P, reqwest::Client> {
+    fn map_http_options(mut self, f: impl FnOnce(HttpOptions) -> HttpOptions) -> Self {
+        self.http_options = Some(f(self.http_options.take().unwrap_or_default()));
+        self
+    }
+}
+
+/// Constructors for the default `reqwest::Client`, so `Client::builder(..)` and
+/// `Client::from_env()` need no type annotation. For another backend call `with_client` on
+/// the builder.
+#[cfg(feature = "reqwest")]
+impl<P> Client<P, reqwest::Client>
+where
+    P: Profile + 'static,
+{
+    pub fn builder(api_key: &str) -> ClientBuilder<'_, P> {
+        ClientBuilder::new(api_key)
+    }
+
+    pub fn new(api_key: &str) -> Self {
+        Self::builder(api_key).build()
+    }
+
+    /// Panics when the API key variable is unset; see [`Client::try_from_env`].
+    pub fn from_env() -> Self {
+        <Self as ProviderClient>::from_env()
+    }
+
+    /// Like `from_env`, but reports a missing API key instead of panicking.
+    pub fn try_from_env() -> Result<Self, ConfigError> {
+        Self::from_client_config(P::NAME, &ClientConfig::new(P::NAME))
+    }
+}
+
+impl<P, T> Client<P, T> {
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    /// Endpoint URL set with [`ClientBuilder::endpoint`].
+    pub fn endpoint(&self, name: &str) -> Option<&str> {
+        self.endpoints.get(name).map(String::as_str)
+    }
+
+    /// HTTP client used for requests, wrapped with the configured retry policy and rate limits.
+    pub fn http_client(&self) -> &Transport<T> {
+        &self.http_client
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        self.http_client.policy()
+    }
+
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.http_client.circuit_breaker()
+    }
+
+    pub fn rate_limits(&self) -> Option<&RateLimits> {
+        self.http_client
+            .rate_limiter()
+            .map(|limiter| limiter.limits())
+    }
+
+    pub fn usage_ledger(&self) -> Option<&UsageLedger> {
+        self.usage.as_ref()
+    }
+
+    /// A copy of the client whose usage is recorded under `tag` (e.g. a tenant or feature).
+    /// It shares the ledger, retries and rate limits with the original.
+    pub fn with_usage_tag(&self, tag: impl Into<String>) -> Self
+    where
+        Self: Clone,
+    {
+        Self {
+            usage_tag: Some(tag.into()),
+            ..self.clone()
+        }
+    }
+
+    pub fn usage_tag(&self) -> Option<&str> {
+        self.usage_tag.as_deref()
+    }
+}
+
+impl<P, T> Client<P, T>
+where
+    P: Profile,
+{
+    /// Add one request's usage to the client's ledger, if any.
+    pub fn record_usage(&self, model: &str, operation: Operation, usage: TokenUsage) {
+        if let Some(ledger) = &self.usage {
+            ledger.record(P::NAME, model, operation, self.usage_tag.as_deref(), usage);
+        }
+    }
+}
+
+impl<P, T> Client<P, T>
+where
+    T: HttpClientExt,
+{
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    fn req(
+        &self,
+        method: http_client::Method,
+        url: String,
+    ) -> http_client::Result<http_client::Builder> {
+        http_client::with_bearer_auth(
+            http_client::Builder::new().method(method).uri(url),
+            &self.api_key,
+        )
+    }
+
+    pub fn get(&self, path: &str) -> http_client::Result<http_client::Builder> {
+        self.req(http_client::Method::GET, self.url(path))
+    }
+
+    pub fn post(&self, path: &str) -> http_client::Result<http_client::Builder> {
+        self.req(http_client::Method::POST, self.url(path))
+    }
+
+    /// Authenticated POST to a full URL outside `base_url` (e.g. DashScope native services).
+    pub fn post_full(&self, url: &str) -> http_client::Result<http_client::Builder> {
+        self.req(http_client::Method::POST, url.to_string())
+    }
+
+    /// Authenticated GET to a full URL outside `base_url`, e.g. a DashScope task status.
+    pub fn get_full(&self, url: &str) -> http_client::Result<http_client::Builder> {
+        self.req(http_client::Method::GET, url.to_string())
+    }
+}
+
+impl<P, T> ProviderClient for Client<P, T>
+where
+    P: Profile + 'static,
+    T: HttpClientExt + Clone + Default + Send + Sync + 'static,
+{
+    type Input = String;
+
+    /// Panics when the API key variable is unset.
+    fn from_env() -> Self {
+        let config = ClientConfig::new(P::NAME);
+        let api_key = config
+            .resolve_api_key(P::NAME, P::API_KEY_ENV)
+            .unwrap_or_else(|err| panic!("{err}"));
+        let base_url = config.resolve_base_url(P::BASE_URL_ENV, P::BASE_URL);
+        ClientBuilder::new(&api_key)
+            .base_url(base_url.trim_end_matches('/'))
+            .build()
+    }
+
+    fn from_val(input: String) -> Self {
+        ClientBuilder::new(&input).build()
+    }
+}
+
+impl<P, T> CompletionClient for Client<P, T>
+where
+    P: Profile,
+    T: HttpClientExt + Clone + Default + Send + 'static,
+{
+    type CompletionModel = CompletionModel<P, T>;
+
+    fn completion_model(&self, model: impl Into<String>) -> Self::CompletionModel {
+        CompletionModel::new(self.clone(), model)
+    }
+}
+
+impl<P, T> EmbeddingsClient for Client<P, T>
+where
+    P: Profile,
+    T: HttpClientExt + Clone + std::fmt::Debug + Send + 'static,
+{
+    type EmbeddingModel = EmbeddingModel<P, T>;
+
+    fn embedding_model(&self, model: impl Into<String>) -> Self::EmbeddingModel {
+        EmbeddingModel::new(self.clone(), model, 0)
+    }
+
+    fn embedding_model_with_ndims(
+        &self,
+        model: impl Into<String>,
+        ndims: usize,
+    ) -> Self::EmbeddingModel {
+        EmbeddingModel::new(self.clone(), model, ndims)
+    }
+}
+
+impl<P, T> VerifyClient for Client<P, T>
+where
+    P: Profile,
+    T: HttpClientExt + Clone + Send + Sync + 'static,
+{
+    async fn verify(&self) -> Result<(), VerifyError> {
+        let req = self
+            .get(P::VERIFY_PATH)?
+            .body(rig::http_client::NoBody)
+            .map_err(rig::http_client::Error::from)?;
+
+        let response = match HttpClientExt::send(&self.http_client, req).await {
+            Ok(response) => response,
+            Err(err) => {
+                return Err(match err.error_class() {
+                    Some(ErrorClass::Auth) => VerifyError::InvalidAuthentication,
+                    Some(ErrorClass::Server) => VerifyError::ProviderError(err.to_string()),
+                    _ => VerifyError::HttpError(err),
+                });
+            }
+        };
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(()),
+            reqwest::StatusCode::UNAUTHORIZED => Err(VerifyError::InvalidAuthentication),
+            reqwest::StatusCode::INTERNAL_SERVER_ERROR
+            | reqwest::StatusCode::SERVICE_UNAVAILABLE
+            | reqwest::StatusCode::BAD_GATEWAY => {
+                let text = rig::http_client::text(response).await?;
+                Err(VerifyError::ProviderError(text))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use rig::OneOrMany;
+use rig::completion::{self, CompletionError, CompletionRequest};
+use rig::http_client;
+use rig::message;
+use rig::providers::openai;
+use rig::providers::openai::completion::Usage;
+use rig::streaming::StreamingCompletionResponse;
+
+use serde_json::{Map, Value, json};
+use tracing::{Instrument, info_span};
+
+use super::client::Client;
+use super::error::{ErrorClass, ProviderError};
+use super::json_utils::merge;
+use super::profile::Profile;
+use super::structured::ResponseFormat;
+use super::types::{ApiResponse, ToolChoice};
+use super::usage::{Operation, TokenUsage};
+use super::vision;
+
+/// Chat completion model: CompletionModel<P, T>
+#[derive(Clone)]
+pub struct CompletionModel<
+    P,
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
+    pub(crate) client: Client<P, T>,
+    pub model: String,
+    default_params: Map<String, Value>,
+    response_format: Option<ResponseFormat>,
+    thinking: Option<Thinking>,
+}
+
+/// Reasoning ("thinking") mode for models that can think before answering. Each profile maps
+/// it to its provider's request fields with [`Profile::thinking_params`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Thinking {
+    Disabled,
+    /// Think first, optionally capped at `budget` reasoning tokens.
+    Enabled {
+        budget: Option<u64>,
+    },
+    /// Let the model decide per request whether to think (Doubao). Providers without an
+    /// automatic mode use the model's default.
+    Auto,
+}
+
+impl<P, T> CompletionModel<P, T> {
+    pub fn new(client: Client<P, T>, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+            default_params: Map::new(),
+            response_format: None,
+            thinking: None,
+        }
+    }
+
+    /// Request fields (e.g. `temperature`, `max_tokens`) used when a request leaves them unset.
+    pub fn with_default_params(mut self, params: Map<String, Value>) -> Self {
+        self.default_params = params;
+        self
+    }
+
+    pub fn default_params(&self) -> &Map<String, Value> {
+        &self.default_params
+    }
+
+    /// `response_format` sent with every request (streaming included) that does not set one
+    /// in `additional_params`.
+    pub fn with_response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+
+    pub fn response_format(&self) -> Option<&ResponseFormat> {
+        self.response_format.as_ref()
+    }
+
+    /// Turn thinking on or off; unset, the model's own default applies.
+    pub fn with_thinking(mut self, enabled: bool) -> Self {
+        self.thinking = Some(if enabled {
+            Thinking::Enabled { budget: None }
+        } else {
+            Thinking::Disabled
+        });
+        self
+    }
+
+    /// Any thinking mode, including [`Thinking::Auto`].
+    pub fn with_thinking_mode(mut self, thinking: Thinking) -> Self {
+        self.thinking = Some(thinking);
+        self
+    }
+
+    /// Turn thinking on with at most `budget` reasoning tokens.
+    pub fn with_thinking_budget(mut self, budget: u64) -> Self {
+        self.thinking = Some(Thinking::Enabled {
+            budget: Some(budget),
+        });
+        self
+    }
+
+    pub fn thinking(&self) -> Option<Thinking> {
+        self.thinking
+    }
+
+    pub(crate) fn set_response_format(&mut self, format: Option<ResponseFormat>) {
+        self.response_format = format;
+    }
+
+    pub(crate) fn create_completion_request(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> Result<Value, CompletionError>
+    where
+        P: Profile,
+    {
+        // Build messages (include context documents if any)
+        let mut partial_history = vec![];
+        if let Some(docs) = completion_request.normalized_documents() {
+            partial_history.push(docs);
+        }
+        // Reasoning from earlier turns is not sent back (and rig cannot convert it).
+        partial_history.extend(
+            completion_request
+                .chat_history
+                .into_iter()
+                .filter_map(without_reasoning),
+        );
+
+        // Preamble (system) goes first
+        let mut full_history: Vec<Value> = completion_request
+            .preamble
+            .map_or_else(Vec::new, |preamble| {
+                vec![json!(openai::Message::system(&preamble))]
+            });
+
+        // Convert user/assistant messages; image and video parts are converted here
+        let limits = P::image_limits();
+        for message in partial_history {
+            if let message::Message::User { content } = &message
+                && let Some(user) = vision::user_message(content, &limits)?
+            {
+                full_history.push(user);
+                continue;
+            }
+            let converted: Vec<openai::Message> = message.try_into()?;
+            for message in converted {
+                full_history.push(serde_json::to_value(message)?);
+            }
+        }
+
+        let tool_choice = completion_request
+            .tool_choice
+            .map(ToolChoice::try_from)
+            .transpose()?;
+
+        // OpenAI-compatible payload
+        let request = if completion_request.tools.is_empty() {
+            json!({
+                "model": self.model,
+                "messages": full_history,
+                "temperature": completion_request.temperature,
+                "max_tokens": completion_request.max_tokens,
+            })
+        } else {
+            json!({
+                "model": self.model,
+                "messages": full_history,
+                "temperature": completion_request.temperature,
+                "max_tokens": completion_request.max_tokens,
+                "tools": completion_request.tools.into_iter().map(openai::ToolDefinition::from).collect::<Vec<_>>(),
+                "tool_choice": tool_choice,
+            })
+        };
+
+        let mut request = if let Some(params) = completion_request.additional_params {
+            merge(request, params)
+        } else {
+            request
+        };
+        if let Some(format) = &self.response_format
+            && request.get("response_format").is_none_or(Value::is_null)
+        {
+            request["response_format"] = json!(format);
+        }
+        let thinking = self.thinking.map(P::thinking_params);
+        let thinking = thinking
+            .as_ref()
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .filter(|(_, value)| !value.is_null());
+        for (key, value) in thinking.chain(&self.default_params) {
+            if request.get(key).is_none_or(Value::is_null) {
+                request[key] = value.clone();
+            }
+        }
+        Ok(request)
+    }
+}
+
+/// Drops reasoning from assistant messages, and messages left with nothing else.
+fn without_reasoning(message: message::Message) -> Option<message::Message> {
+    match message {
+        message::Message::Assistant { id, content } => {
+            let content = content
+                .into_iter()
+                .filter(|content| !matches!(content, message::AssistantContent::Reasoning(_)));
+            OneOrMany::many(content)
+                .ok()
+                .map(|content| message::Message::Assistant { id, content })
+        }
+        message => Some(message),
+    }
+}
+
+impl TryFrom<message::ToolChoice> for ToolChoice {
+    type Error = CompletionError;
+
+    fn try_from(value: message::ToolChoice) -> Result<Self, Self::Error> {
+        let res = match value {
+            message::ToolChoice::None => Self::None,
+            message::ToolChoice::Auto => Self::Auto,
+            message::ToolChoice::Required => Self::Required,
+            choice => {
+                return Err(CompletionError::ProviderError(format!(
+                    "Unsupported tool choice type: {choice:?}"
+                )));
+            }
+        };
+
+        Ok(res)
+    }
+}
+
+impl<P, T> completion::CompletionModel for CompletionModel<P, T>
+where
+    P: Profile,
+    T: http_client::HttpClientExt + Clone + Default + Send + 'static,
+{
+    type Response = openai::CompletionResponse;
+    type StreamingResponse = openai::StreamingCompletionResponse;
+    type Client = Client<P, T>;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self::new(client.clone(), model)
+    }
+
+    async fn completion(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> Result<completion::CompletionResponse<openai::CompletionResponse>, CompletionError> {
+        let preamble = completion_request.preamble.clone();
+        let request = self.create_completion_request(completion_request)?;
+
+        let span = if tracing::Span::current().is_disabled() {
+            info_span!(
+                target: "rig::completions",
+                "chat",
+                gen_ai.operation.name = "chat",
+                gen_ai.provider.name = P::NAME,
+                gen_ai.request.model = self.model,
+                gen_ai.system_instructions = preamble,
+                gen_ai.response.id = tracing::field::Empty,
+                gen_ai.response.model = tracing::field::Empty,
+                gen_ai.usage.output_tokens = tracing::field::Empty,
+                gen_ai.usage.input_tokens = tracing::field::Empty,
+                gen_ai.input.messages = serde_json::to_string(&request.get("messages").unwrap_or(&json!([]))).unwrap(),
+                gen_ai.output.messages = tracing::field::Empty,
+            )
+        } else {
+            tracing::Span::current()
+        };
+
+        async move {
+            let body = serde_json::to_vec(&request)?;
+            let req = self
+                .client
+                .post("/chat/completions")?
+                .header("Content-Type", "application/json")
+                .body(body)
+                .map_err(|e| CompletionError::HttpError(e.into()))?;
+
+            let response = http_client::HttpClientExt::send(&self.client.http_client, req)
+                .await
+                .map_err(CompletionError::HttpError)?;
+
+            if response.status().is_success() {
+                let t = http_client::text(response).await?;
+                tracing::debug!(target: "rig::completions", "{} completion response: {t}", P::DISPLAY_NAME);
+
+                match serde_json::from_str::<ApiResponse<openai::CompletionResponse>>(&t)? {
+                    ApiResponse::Ok(response) => {
+                        // rig's response type has no field for Qwen's `reasoning_content`.
+                        let body: Value = serde_json::from_str(&t)?;
+                        let reasoning = body["choices"][0]["message"]["reasoning_content"]
+                            .as_str()
+                            .filter(|reasoning| !reasoning.is_empty())
+                            .map(message::Reasoning::new);
+                        if self.client.usage_ledger().is_some() {
+                            self.client.record_usage(
+                                &self.model,
+                                Operation::Chat,
+                                TokenUsage::from_openai(&body["usage"]),
+                            );
+                        }
+                        let span = tracing::Span::current();
+                        span.record("gen_ai.response.id", response.id.clone());
+                        span.record("gen_ai.response.model_name", response.model.clone());
+                        span.record(
+                            "gen_ai.output.messages",
+                            serde_json::to_string(&response.choices).unwrap(),
+                        );
+                        if let Some(Usage {
+                            prompt_tokens,
+                            total_tokens,
+                            ..
+                        }) = response.usage
+                        {
+                            span.record("gen_ai.usage.input_tokens", prompt_tokens);
+                            span.record(
+                                "gen_ai.usage.output_tokens",
+                                total_tokens.saturating_sub(prompt_tokens),
+                            );
+                        }
+                        // A refusal with no content cannot be converted; report it as such.
+                        let filtered = response
+                            .choices
+                            .iter()
+                            .any(|choice| choice.finish_reason == "content_filter");
+                        let id = response.id.clone();
+                        let response: Result<completion::CompletionResponse<_>, _> =
+                            response.try_into();
+                        let response = response.map(|mut response| {
+                            if let Some(reasoning) = reasoning {
+                                response
+                                    .choice
+                                    .insert(0, message::AssistantContent::Reasoning(reasoning));
+                            }
+                            response
+                        });
+                        response.map_err(|err| {
+                            if !filtered {
+                                return err;
+                            }
+                            CompletionError::HttpError(
+                                ProviderError {
+                                    request_id: Some(id),
+                                    code: Some("content_filter".to_string()),
+                                    class: ErrorClass::ContentFilter,
+                                    ..ProviderError::new(None, "response refused by content filter")
+                                }
+                                .into(),
+                            )
+                        })
+                    }
+                    ApiResponse::Err(_) => Err(CompletionError::HttpError(
+                        ProviderError::from_body(None, &t).into(),
+                    )),
+                }
+            } else {
+                let status = response.status();
+                let headers = response.headers().clone();
+                let t = http_client::text(response).await?;
+                Err(CompletionError::HttpError(
+                    ProviderError::from_response(status, &headers, &t).into(),
+                ))
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
+        super::streaming::stream_completion(self, request).await
+    }
+}
@@ -0,0 +1,769 @@
+//! Category: config.rs (declarative clients and models loaded from TOML or YAML)
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::header::{HeaderName, HeaderValue};
+#[cfg(feature = "reqwest")]
+use rig::client::EmbeddingsClient;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use super::circuit_breaker::CircuitBreakerPolicy;
+#[cfg(feature = "reqwest")]
+use super::client::Client;
+#[cfg(feature = "reqwest")]
+use super::completion::CompletionModel;
+#[cfg(feature = "reqwest")]
+use super::embedding::EmbeddingModel;
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+use super::http::HttpOptions;
+#[cfg(feature = "reqwest")]
+use super::profile::Profile;
+use super::rate_limit::RateLimits;
+use super::retry::RetryPolicy;
+
+/// Named clients and models.
+///
+/// ```toml
+/// [clients.dashscope]
+/// provider = "bailian"
+/// api_key_env = "DASHSCOPE_KEY"
+/// timeout = "60s"
+/// proxy = "http://proxy.internal:3128"
+/// retry = { max_attempts = 3 }
+///
+/// [models.qwen]
+/// client = "dashscope"
+/// kind = "completion"
+/// model = "qwen3-max"
+/// temperature = 0.3
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub clients: BTreeMap<String, ClientConfig>,
+    #[serde(default)]
+    pub models: BTreeMap<String, ModelConfig>,
+}
+
+/// One provider client.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    /// Provider id, e.g. `bailian`, `volcengine` or `tei` (a profile's `NAME`).
+    pub provider: String,
+    /// API base URL [default: the provider's base URL variable, then its built-in URL].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// API key written into the file. Prefer `api_key_env`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Variable holding the API key [default: the provider's, e.g. `BAILIAN_API_KEY`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// Full URLs replacing single endpoints, e.g. `rerank`, or TEI `embed` and `predict`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub endpoints: BTreeMap<String, String>,
+    /// Limit on a whole request, e.g. `"60s"`.
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Duration>,
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub connect_timeout: Option<Duration>,
+    /// Longest pause between two chunks of a response body, e.g. `"30s"` for slow streams.
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stream_idle_timeout: Option<Duration>,
+    /// Proxy for all requests, e.g. `http://proxy:3128` [default: `HTTP(S)_PROXY`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Headers sent with every request.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// PEM file with an extra CA to trust, e.g. for an on-prem TEI behind a private CA.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+    /// Skip certificate verification. Only for local testing.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub danger_accept_invalid_certs: bool,
+    /// Retries [default: none]. Unset fields take [`RetryPolicy::default`] values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limits: Option<RateLimitConfig>,
+    /// Circuit breaker [default: off]. Unset fields take [`CircuitBreakerPolicy::default`]
+    /// values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_attempts: Option<u32>,
+    #[serde(default, with = "humantime_serde")]
+    pub initial_backoff: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    pub max_backoff: Option<Duration>,
+    pub multiplier: Option<f64>,
+    pub jitter: Option<bool>,
+    pub respect_retry_after: Option<bool>,
+    pub retry_statuses: Option<Vec<u16>>,
+    pub retry_transport_errors: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+    pub max_in_flight: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    pub consecutive_failures: Option<u32>,
+    /// Failure share that opens the circuit; requires `window`.
+    pub error_rate: Option<f64>,
+    /// Number of recent requests `error_rate` is measured over.
+    pub window: Option<usize>,
+    #[serde(default, with = "humantime_serde")]
+    pub open_for: Option<Duration>,
+    pub failure_statuses: Option<Vec<u16>>,
+}
+
+/// What a configured model is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelKind {
+    #[serde(alias = "chat")]
+    Completion,
+    Embedding,
+    Rerank,
+}
+
+impl ModelKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Completion => "completion",
+            Self::Embedding => "embedding",
+            Self::Rerank => "rerank",
+        }
+    }
+}
+
+impl fmt::Display for ModelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One model on a configured client, with its default request parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelConfig {
+    /// Name of an entry in `clients`.
+    pub client: String,
+    pub kind: ModelKind,
+    /// Model name sent to the provider.
+    pub model: String,
+    /// Completion: default sampling temperature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Completion: default output limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    /// Completion: extra request fields, e.g. `enable_search = true`.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub params: Map<String, Value>,
+    /// Embedding: output dimensions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<usize>,
+    /// Embedding: inputs per request [default: the model's limit].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
+    /// Embedding: requests in flight for one call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+}
+
+impl ModelConfig {
+    /// A model on `client` without defaults.
+    pub fn new(client: impl Into<String>, kind: ModelKind, model: impl Into<String>) -> Self {
+        Self {
+            client: client.into(),
+            kind,
+            model: model.into(),
+            temperature: None,
+            max_tokens: None,
+            params: Map::new(),
+            dimensions: None,
+            batch_size: None,
+            concurrency: None,
+        }
+    }
+
+    /// Fields filled into every completion request that does not set them.
+    pub fn default_params(&self) -> Map<String, Value> {
+        let mut params = self.params.clone();
+        if let Some(temperature) = self.temperature {
+            params.insert("temperature".to_string(), json!(temperature));
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            params.insert("max_tokens".to_string(), json!(max_tokens));
+        }
+        params
+    }
+}
+
+/// Loading or building from a [`Config`] failed.
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{origin}: unknown config format (expected .toml, .yaml or .yml)")]
+    UnknownFormat { origin: String },
+    #[error("{origin}: {message}")]
+    Parse { origin: String, message: String },
+    #[error("{origin}: invalid config:{}", issues.iter().map(|i| format!("\n  - {i}")).collect::<String>())]
+    Invalid { origin: String, issues: Vec<String> },
+    #[error("no client named `{0}` in config")]
+    UnknownClient(String),
+    #[error("no model named `{0}` in config")]
+    UnknownModel(String),
+    #[error("client `{client}` is a `{found}` client, not `{expected}`")]
+    ProviderMismatch {
+        client: String,
+        expected: String,
+        found: String,
+    },
+    #[error("model `{model}` is a {found} model, not {expected}")]
+    KindMismatch {
+        model: String,
+        expected: ModelKind,
+        found: ModelKind,
+    },
+    #[error("unknown provider `{provider}` (registered: {known})")]
+    UnknownProvider { provider: String, known: String },
+    #[error("provider `{provider}` has no {kind} models")]
+    Unsupported { provider: String, kind: ModelKind },
+    #[error("no API key for `{client}`: {env} is not set")]
+    MissingApiKey { client: String, env: String },
+    #[cfg(feature = "reqwest")]
+    #[error("`{client}`: {source}")]
+    HttpClient {
+        client: String,
+        source: reqwest::Error,
+    },
+}
+
+impl Config {
+    /// Read a `.toml`, `.yaml` or `.yml` file and validate it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let origin = path.display().to_string();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::parse_toml(&text, origin),
+            Some("yaml" | "yml") => Self::parse_yaml(&text, origin),
+            _ => Err(ConfigError::UnknownFormat { origin }),
+        }
+    }
+
+    pub fn from_toml_str(text: &str) -> Result<Self, ConfigError> {
+        Self::parse_toml(text, "config".to_string())
+    }
+
+    pub fn from_yaml_str(text: &str) -> Result<Self, ConfigError> {
+        Self::parse_yaml(text, "config".to_string())
+    }
+
+    fn parse_toml(text: &str, origin: String) -> Result<Self, ConfigError> {
+        match toml::from_str::<Self>(text) {
+            Ok(config) => config.validated(origin),
+            Err(err) => Err(ConfigError::Parse {
+                origin,
+                message: err.to_string().trim_end().to_string(),
+            }),
+        }
+    }
+
+    fn parse_yaml(text: &str, origin: String) -> Result<Self, ConfigError> {
+        match serde_yaml::from_str::<Self>(text) {
+            Ok(config) => config.validated(origin),
+            Err(err) => Err(ConfigError::Parse {
+                origin,
+                message: err.to_string(),
+            }),
+        }
+    }
+
+    fn validated(self, origin: String) -> Result<Self, ConfigError> {
+        let issues = self.issues();
+        if issues.is_empty() {
+            Ok(self)
+        } else {
+            Err(ConfigError::Invalid { origin, issues })
+        }
+    }
+
+    /// Check references and values; every problem is reported, not only the first.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.clone().validated("config".to_string()).map(|_| ())
+    }
+
+    fn issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        for (name, client) in &self.clients {
+            let at = format!("clients.{name}");
+            if client.provider.trim().is_empty() {
+                issues.push(format!("{at}.provider: must not be empty"));
+            }
+            if client.api_key.is_some() && client.api_key_env.is_some() {
+                issues.push(format!("{at}: set `api_key` or `api_key_env`, not both"));
+            }
+            let urls = client
+                .base_url
+                .iter()
+                .map(|url| ("base_url".to_string(), url))
+                .chain(
+                    client
+                        .endpoints
+                        .iter()
+                        .map(|(endpoint, url)| (format!("endpoints.{endpoint}"), url)),
+                );
+            for (field, url) in urls {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    issues.push(format!("{at}.{field}: `{url}` is not an http(s) URL"));
+                }
+            }
+            #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+            for (field, set) in [
+                ("ca_cert", client.ca_cert.is_some()),
+                (
+                    "danger_accept_invalid_certs",
+                    client.danger_accept_invalid_certs,
+                ),
+            ] {
+                if set {
+                    issues.push(format!(
+                        "{at}.{field}: needs the `native-tls` or `rustls` feature"
+                    ));
+                }
+            }
+            #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+            if let Some(proxy) = &client.proxy
+                && reqwest::Proxy::all(proxy).is_err()
+            {
+                issues.push(format!("{at}.proxy: `{proxy}` is not a proxy URL"));
+            }
+            for (header, value) in &client.headers {
+                if HeaderName::try_from(header).is_err() {
+                    issues.push(format!("{at}.headers: `{header}` is not a header name"));
+                } else if HeaderValue::try_from(value).is_err() {
+                    issues.push(format!("{at}.headers.{header}: not a valid header value"));
+                }
+            }
+            if let Some(user_agent) = &client.user_agent
+                && HeaderValue::try_from(user_agent).is_err()
+            {
+                issues.push(format!("{at}.user_agent: not a valid header value"));
+            }
+            if let Some(retry) = &client.retry {
+                if retry.max_attempts == Some(0) {
+                    issues.push(format!("{at}.retry.max_attempts: must be at least 1"));
+                }
+                if retry.multiplier.is_some_and(|m| m < 1.0) {
+                    issues.push(format!("{at}.retry.multiplier: must be at least 1.0"));
+                }
+            }
+            if let Some(breaker) = &client.circuit_breaker {
+                if breaker.consecutive_failures == Some(0) {
+                    issues.push(format!(
+                        "{at}.circuit_breaker.consecutive_failures: must be at least 1"
+                    ));
+                }
+                match (breaker.error_rate, breaker.window) {
+                    (Some(rate), _) if !(0.0..=1.0).contains(&rate) => issues.push(format!(
+                        "{at}.circuit_breaker.error_rate: must be between 0.0 and 1.0"
+                    )),
+                    (Some(_), None) | (None, Some(_)) => issues.push(format!(
+                        "{at}.circuit_breaker: set `error_rate` and `window` together"
+                    )),
+                    (Some(_), Some(0)) => {
+                        issues.push(format!("{at}.circuit_breaker.window: must be at least 1"))
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(limits) = &client.rate_limits
+                && (limits.requests_per_minute == Some(0)
+                    || limits.tokens_per_minute == Some(0)
+                    || limits.max_in_flight == Some(0))
+            {
+                issues.push(format!("{at}.rate_limits: limits must be greater than 0"));
+            }
+        }
+
+        for (name, model) in &self.models {
+            let at = format!("models.{name}");
+            if !self.clients.contains_key(&model.client) {
+                issues.push(format!(
+                    "{at}.client: no client named `{}` in `clients`",
+                    model.client
+                ));
+            }
+            if model.model.trim().is_empty() {
+                issues.push(format!("{at}.model: must not be empty"));
+            }
+            let completion = [
+                ("temperature", model.temperature.is_some()),
+                ("max_tokens", model.max_tokens.is_some()),
+                ("params", !model.params.is_empty()),
+            ];
+            let embedding = [
+                ("dimensions", model.dimensions.is_some()),
+                ("batch_size", model.batch_size.is_some()),
+                ("concurrency", model.concurrency.is_some()),
+            ];
+            let misplaced = match model.kind {
+                ModelKind::Completion => embedding.iter().collect::<Vec<_>>(),
+                ModelKind::Embedding => completion.iter().collect(),
+                ModelKind::Rerank => completion.iter().chain(&embedding).collect(),
+            };
+            for (field, set) in misplaced {
+                if *set {
+                    issues.push(format!("{at}.{field}: not used by {} models", model.kind));
+                }
+            }
+            if model.batch_size == Some(0) || model.concurrency == Some(0) {
+                issues.push(format!(
+                    "{at}: `batch_size` and `concurrency` must be greater than 0"
+                ));
+            }
+        }
+        issues
+    }
+
+    pub fn client_config(&self, name: &str) -> Result<&ClientConfig, ConfigError> {
+        self.clients
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownClient(name.to_string()))
+    }
+
+    pub fn model_config(&self, name: &str) -> Result<&ModelConfig, ConfigError> {
+        self.models
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownModel(name.to_string()))
+    }
+
+    /// Build the client named `name`, e.g. `config.client::<rig_bailian::Client>("dashscope")`.
+    pub fn client<C: FromClientConfig>(&self, name: &str) -> Result<C, ConfigError> {
+        let config = self.client_config(name)?;
+        if config.provider != C::PROVIDER {
+            return Err(ConfigError::ProviderMismatch {
+                client: name.to_string(),
+                expected: C::PROVIDER.to_string(),
+                found: config.provider.clone(),
+            });
+        }
+        C::from_client_config(name, config)
+    }
+
+    /// Build the model named `name` on a new instance of its client, with its default
+    /// parameters applied.
+    ///
+    /// Each call builds its own client, with its own rate limits and circuit breaker. For
+    /// several models on one client, build the client once and use [`model_on`](Self::model_on).
+    pub fn model<M: FromModelConfig>(&self, name: &str) -> Result<M, ConfigError> {
+        let config = self.model_config_of::<M>(name)?;
+        let client = self.client::<M::Client>(&config.client)?;
+        M::from_model_config(&client, config)
+    }
+
+    /// Build the model named `name` on `client`, which should be the one built from the
+    /// model's `client` entry. Models built on the same client share its rate limits,
+    /// circuit breaker and usage ledger.
+    pub fn model_on<M: FromModelConfig>(
+        &self,
+        client: &M::Client,
+        name: &str,
+    ) -> Result<M, ConfigError> {
+        let config = self.model_config_of::<M>(name)?;
+        let client_config = self.client_config(&config.client)?;
+        let provider = <M::Client as FromClientConfig>::PROVIDER;
+        if client_config.provider != provider {
+            return Err(ConfigError::ProviderMismatch {
+                client: config.client.clone(),
+                expected: provider.to_string(),
+                found: client_config.provider.clone(),
+            });
+        }
+        M::from_model_config(client, config)
+    }
+
+    /// The config of model `name`, checked to be of `M`'s kind.
+    fn model_config_of<M: FromModelConfig>(&self, name: &str) -> Result<&ModelConfig, ConfigError> {
+        let config = self.model_config(name)?;
+        if config.kind != M::KIND {
+            return Err(ConfigError::KindMismatch {
+                model: name.to_string(),
+                expected: M::KIND,
+                found: config.kind,
+            });
+        }
+        Ok(config)
+    }
+}
+
+impl ClientConfig {
+    /// A config with only `provider` set, resolved like `from_env`.
+    pub fn new(provider: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            ..Self::default()
+        }
+    }
+
+    /// The inline key, else the value of `api_key_env` (or `default_env` when unset).
+    pub fn resolve_api_key(&self, name: &str, default_env: &str) -> Result<String, ConfigError> {
+        if let Some(key) = &self.api_key {
+            return Ok(key.clone());
+        }
+        let env = self.api_key_env.as_deref().unwrap_or(default_env);
+        match std::env::var(env) {
+            Ok(key) if !key.is_empty() => Ok(key),
+            _ => Err(ConfigError::MissingApiKey {
+                client: name.to_string(),
+                env: env.to_string(),
+            }),
+        }
+    }
+
+    /// `base_url`, else the value of `env`, else `default`.
+    pub fn resolve_base_url(&self, env: &str, default: &str) -> String {
+        self.base_url
+            .clone()
+            .or_else(|| std::env::var(env).ok().filter(|url| !url.is_empty()))
+            .unwrap_or_else(|| default.to_string())
+    }
+
+    pub fn endpoint(&self, name: &str) -> Option<&str> {
+        self.endpoints.get(name).map(String::as_str)
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        let Some(retry) = &self.retry else {
+            return RetryPolicy::none();
+        };
+        let defaults = RetryPolicy::default();
+        RetryPolicy {
+            max_attempts: retry.max_attempts.unwrap_or(defaults.max_attempts).max(1),
+            initial_backoff: retry.initial_backoff.unwrap_or(defaults.initial_backoff),
+            max_backoff: retry.max_backoff.unwrap_or(defaults.max_backoff),
+            multiplier: retry.multiplier.unwrap_or(defaults.multiplier),
+            jitter: retry.jitter.unwrap_or(defaults.jitter),
+            respect_retry_after: retry
+                .respect_retry_after
+                .unwrap_or(defaults.respect_retry_after),
+            retry_statuses: retry
+                .retry_statuses
+                .clone()
+                .unwrap_or(defaults.retry_statuses),
+            retry_transport_errors: retry
+                .retry_transport_errors
+                .unwrap_or(defaults.retry_transport_errors),
+        }
+    }
+
+    pub fn circuit_breaker(&self) -> Option<CircuitBreakerPolicy> {
+        let config = self.circuit_breaker.as_ref()?;
+        let defaults = CircuitBreakerPolicy::default();
+        Some(CircuitBreakerPolicy {
+            consecutive_failures: config
+                .consecutive_failures
+                .or(defaults.consecutive_failures),
+            error_rate: config.error_rate.zip(config.window),
+            open_for: config.open_for.unwrap_or(defaults.open_for),
+            failure_statuses: config
+                .failure_statuses
+                .clone()
+                .unwrap_or(defaults.failure_statuses),
+        })
+    }
+
+    pub fn rate_limits(&self) -> RateLimits {
+        let mut limits = RateLimits::default();
+        if let Some(config) = &self.rate_limits {
+            if let Some(rpm) = config.requests_per_minute {
+                limits = limits.requests_per_minute(rpm);
+            }
+            if let Some(tpm) = config.tokens_per_minute {
+                limits = limits.tokens_per_minute(tpm);
+            }
+            if let Some(max) = config.max_in_flight {
+                limits = limits.max_in_flight(max);
+            }
+        }
+        limits
+    }
+
+    /// Timeouts, proxy, headers and TLS settings; reads `ca_cert` if set.
+    #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+    pub fn http_options(&self, name: &str) -> Result<HttpOptions, ConfigError> {
+        let http_error = |source| ConfigError::HttpClient {
+            client: name.to_string(),
+            source,
+        };
+        let invalid = |issue: String| ConfigError::Invalid {
+            origin: format!("clients.{name}"),
+            issues: vec![issue],
+        };
+
+        let mut options = HttpOptions {
+            connect_timeout: self.connect_timeout,
+            timeout: self.timeout,
+            stream_idle_timeout: self.stream_idle_timeout,
+            ..HttpOptions::default()
+        };
+        if let Some(proxy) = &self.proxy {
+            options = options.proxy(reqwest::Proxy::all(proxy).map_err(http_error)?);
+        }
+        for (header, value) in &self.headers {
+            let header = HeaderName::try_from(header)
+                .map_err(|_| invalid(format!("headers: `{header}` is not a header name")))?;
+            let value = HeaderValue::try_from(value)
+                .map_err(|_| invalid(format!("headers.{header}: not a valid header value")))?;
+            options = options.header(header, value);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            let user_agent = HeaderValue::try_from(user_agent)
+                .map_err(|_| invalid("user_agent: not a valid header value".to_string()))?;
+            options = options.user_agent(user_agent);
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        if self.danger_accept_invalid_certs {
+            options = options.danger_accept_invalid_certs(true);
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        if let Some(path) = &self.ca_cert {
+            let pem = std::fs::read(path).map_err(|source| ConfigError::Read {
+                path: path.clone(),
+                source,
+            })?;
+            options = options
+                .add_root_certificate(reqwest::Certificate::from_pem(&pem).map_err(http_error)?);
+        }
+        Ok(options)
+    }
+
+    /// A `reqwest::Client` built from [`http_options`](Self::http_options).
+    #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+    pub fn http_client(&self, name: &str) -> Result<reqwest::Client, ConfigError> {
+        self.http_options(name)?
+            .build()
+            .map_err(|source| ConfigError::HttpClient {
+                client: name.to_string(),
+                source,
+            })
+    }
+
+    /// On wasm reqwest runs on the Fetch API, which has none of the native HTTP options.
+    #[cfg(all(feature = "reqwest", target_family = "wasm"))]
+    pub fn http_client(&self, _name: &str) -> Result<reqwest::Client, ConfigError> {
+        Ok(reqwest::Client::new())
+    }
+}
+
+/// A client that can be built from a [`ClientConfig`].
+pub trait FromClientConfig: Sized {
+    /// Provider id matched against [`ClientConfig::provider`].
+    const PROVIDER: &'static str;
+
+    /// Build the client; `name` is used in error messages.
+    fn from_client_config(name: &str, config: &ClientConfig) -> Result<Self, ConfigError>;
+}
+
+/// A model that can be built from a [`ModelConfig`] on its client.
+pub trait FromModelConfig: Sized {
+    type Client: FromClientConfig + 'static;
+    const KIND: ModelKind;
+
+    fn from_model_config(client: &Self::Client, config: &ModelConfig) -> Result<Self, ConfigError>;
+}
+
+#[cfg(feature = "reqwest")]
+impl<P: Profile> FromClientConfig for Client<P, reqwest::Client> {
+    const PROVIDER: &'static str = P::NAME;
+
+    fn from_client_config(name: &str, config: &ClientConfig) -> Result<Self, ConfigError> {
+        let api_key = config.resolve_api_key(name, P::API_KEY_ENV)?;
+        let base_url = config.resolve_base_url(P::BASE_URL_ENV, P::BASE_URL);
+        let mut builder = Client::<P>::builder(&api_key)
+            .base_url(base_url.trim_end_matches('/'))
+            .with_client(config.http_client(name)?)
+            .retry_policy(config.retry_policy())
+            .rate_limits(config.rate_limits());
+        if let Some(policy) = config.circuit_breaker() {
+            builder = builder.circuit_breaker(policy);
+        }
+        for (endpoint, url) in &config.endpoints {
+            builder = builder.endpoint(endpoint, url);
+        }
+        Ok(builder.build())
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl<P: Profile> FromModelConfig for CompletionModel<P, reqwest::Client> {
+    type Client = Client<P, reqwest::Client>;
+    const KIND: ModelKind = ModelKind::Completion;
+
+    fn from_model_config(client: &Self::Client, config: &ModelConfig) -> Result<Self, ConfigError> {
+        Ok(CompletionModel::new(client.clone(), &config.model)
+            .with_default_params(config.default_params()))
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl<P: Profile> FromModelConfig for EmbeddingModel<P, reqwest::Client> {
+    type Client = Client<P, reqwest::Client>;
+    const KIND: ModelKind = ModelKind::Embedding;
+
+    fn from_model_config(client: &Self::Client, config: &ModelConfig) -> Result<Self, ConfigError> {
+        let mut model = match config.dimensions {
+            Some(dims) => client.embedding_model_with_ndims(&config.model, dims),
+            None => client.embedding_model(&config.model),
+        };
+        if let Some(batch_size) = config.batch_size {
+            model = model.with_batch_size(batch_size);
+        }
+        if let Some(concurrency) = config.concurrency {
+            model = model.with_concurrency(concurrency);
+        }
+        Ok(model)
+    }
+}
@@ -0,0 +1,211 @@
+use rig::embeddings::{self, EmbeddingError};
+use rig::http_client::{self, HttpClientExt};
+use rig::providers::openai::completion::Usage;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::batching::{EmbeddingBatchError, EmbeddingBatching, embed_in_batches};
+use super::client::Client;
+use super::error::ProviderError;
+use super::profile::Profile;
+use super::types::ApiResponse;
+use super::usage::{Operation, TokenUsage};
+
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingData {
+    pub object: String,
+    pub embedding: Vec<f64>,
+    pub index: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingResponse {
+    pub object: String,
+    pub data: Vec<EmbeddingData>,
+    pub model: String,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+#[derive(Clone)]
+pub struct EmbeddingModel<
+    P,
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
+    pub(crate) client: Client<P, T>,
+    pub model: String,
+    ndims: usize,
+    batching: EmbeddingBatching,
+}
+
+impl<P, T> EmbeddingModel<P, T>
+where
+    P: Profile,
+{
+    /// Batch size defaults to the profile's limit for `model`.
+    pub fn new(client: Client<P, T>, model: impl Into<String>, ndims: usize) -> Self {
+        let model = model.into();
+        Self {
+            client,
+            batching: EmbeddingBatching::new(P::embedding_batch_size(&model)),
+            model,
+            ndims,
+        }
+    }
+}
+
+impl<P, T> EmbeddingModel<P, T> {
+    /// Inputs per request, overriding the profile's limit for the model.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batching = self.batching.batch_size(batch_size);
+        self
+    }
+
+    /// Requests in flight at once for one `embed_texts` call.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.batching = self.batching.concurrency(concurrency);
+        self
+    }
+
+    pub fn batching(&self) -> EmbeddingBatching {
+        self.batching
+    }
+}
+
+impl<P, T> EmbeddingModel<P, T>
+where
+    P: Profile,
+    T: HttpClientExt + Clone + std::fmt::Debug + Send + 'static,
+{
+    /// Embed `documents` in batches, keeping the embeddings of batches that succeeded when
+    /// others fail.
+    pub async fn embed_batched(
+        &self,
+        documents: impl IntoIterator<Item = String>,
+    ) -> Result<Vec<embeddings::Embedding>, EmbeddingBatchError> {
+        let documents = documents.into_iter().collect();
+        embed_in_batches(documents, self.batching, |batch| self.embed_batch(batch)).await
+    }
+
+    async fn embed_batch(
+        &self,
+        documents: Vec<String>,
+    ) -> Result<Vec<embeddings::Embedding>, EmbeddingError> {
+        let mut body = json!({
+            "model": self.model,
+            "input": documents,
+        });
+
+        if self.ndims > 0 {
+            body["dimensions"] = json!(self.ndims);
+        }
+
+        let body = serde_json::to_vec(&body)?;
+
+        let req = self
+            .client
+            .post("/embeddings")?
+            .header("Content-Type", "application/json")
+            .body(body)
+            .map_err(|e| EmbeddingError::HttpError(e.into()))?;
+
+        let response = HttpClientExt::send(&self.client.http_client, req).await?;
+
+        if response.status().is_success() {
+            let text = http_client::text(response).await?;
+            let parsed: ApiResponse<EmbeddingResponse> = serde_json::from_str(&text)?;
+
+            match parsed {
+                ApiResponse::Ok(response) => {
+                    if let Some(ref usage) = response.usage {
+                        tracing::info!(target: "rig", "{} embedding token usage: {}", P::DISPLAY_NAME, usage);
+                    }
+                    if self.client.usage_ledger().is_some() {
+                        let body: serde_json::Value = serde_json::from_str(&text)?;
+                        self.client.record_usage(
+                            &self.model,
+                            Operation::Embedding,
+                            TokenUsage::from_openai(&body["usage"]),
+                        );
+                    }
+
+                    if response.data.len() != documents.len() {
+                        return Err(EmbeddingError::ResponseError(
+                            "Response data length does not match input length".into(),
+                        ));
+                    }
+
+                    // Items may come back in any order; `index` says which input each is for.
+                    let mut vectors: Vec<Option<Vec<f64>>> = vec![None; documents.len()];
+                    for item in response.data {
+                        match vectors.get_mut(item.index) {
+                            Some(slot @ None) => *slot = Some(item.embedding),
+                            Some(Some(_)) => {
+                                return Err(EmbeddingError::ResponseError(format!(
+                                    "Duplicate embedding index {}",
+                                    item.index
+                                )));
+                            }
+                            None => {
+                                return Err(EmbeddingError::ResponseError(format!(
+                                    "Embedding index {} out of range for {} inputs",
+                                    item.index,
+                                    documents.len()
+                                )));
+                            }
+                        }
+                    }
+
+                    Ok(vectors
+                        .into_iter()
+                        .zip(documents)
+                        .map(|(vec, document)| embeddings::Embedding {
+                            document,
+                            vec: vec.unwrap_or_default(),
+                        })
+                        .collect())
+                }
+                ApiResponse::Err(_) => Err(EmbeddingError::HttpError(
+                    ProviderError::from_body(None, &text).into(),
+                )),
+            }
+        } else {
+            let status = response.status();
+            let headers = response.headers().clone();
+            let text = http_client::text(response).await?;
+            Err(EmbeddingError::HttpError(
+                ProviderError::from_response(status, &headers, &text).into(),
+            ))
+        }
+    }
+}
+
+impl<P, T> embeddings::EmbeddingModel for EmbeddingModel<P, T>
+where
+    P: Profile,
+    T: HttpClientExt + Clone + std::fmt::Debug + Send + 'static,
+{
+    const MAX_DOCUMENTS: usize = 1024;
+
+    type Client = Client<P, T>;
+
+    fn make(client: &Self::Client, model: impl Into<String>, ndims: Option<usize>) -> Self {
+        let model = model.into();
+        let dims = ndims.unwrap_or(0);
+        Self::new(client.clone(), model, dims)
+    }
+
+    fn ndims(&self) -> usize {
+        self.ndims
+    }
+
+    async fn embed_texts(
+        &self,
+        documents: impl IntoIterator<Item = String>,
+    ) -> Result<Vec<embeddings::Embedding>, EmbeddingError> {
+        self.embed_batched(documents)
+            .await
+            .map_err(EmbeddingBatchError::into_first_error)
+    }
+}
@@ -0,0 +1,354 @@
+//! Category: embedding_cache.rs (SQLite-backed cache in front of any EmbeddingModel)
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rig::embeddings::{self, Embedding, EmbeddingError};
+use rig::wasm_compat::WasmCompatSend;
+use sha2::{Digest, Sha256};
+use tokio_rusqlite::{Connection, params};
+
+/// Keys looked up per `SELECT`, well under SQLite's bound-parameter limit.
+const LOOKUP_CHUNK: usize = 500;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS embeddings (
+    model TEXT NOT NULL,
+    dims INTEGER NOT NULL,
+    hash TEXT NOT NULL,
+    vector BLOB NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    PRIMARY KEY (model, dims, hash)
+)";
+
+#[derive(thiserror::Error, Debug)]
+pub enum CacheError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] tokio_rusqlite::rusqlite::Error),
+    #[error("sqlite connection error: {0}")]
+    Connection(#[from] tokio_rusqlite::Error),
+}
+
+/// Hit/miss counters, shared by every clone of a [`CachedEmbeddingModel`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    pub fn requests(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// Fraction of texts served from the cache, `0.0` before any lookup.
+    pub fn hit_rate(&self) -> f64 {
+        match self.requests() {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// SQLite store of embedding vectors keyed by model, dimensions and the SHA-256 of the text.
+///
+/// Vectors are stored as little-endian `f64` blobs, so cached values are bit-identical to
+/// what the provider returned. Clones share one connection.
+#[derive(Clone)]
+pub struct EmbeddingCache {
+    conn: Connection,
+}
+
+impl std::fmt::Debug for EmbeddingCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddingCache").finish_non_exhaustive()
+    }
+}
+
+impl EmbeddingCache {
+    /// Open (or create) the cache database at `path`.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, CacheError> {
+        Self::init(Connection::open(path).await?).await
+    }
+
+    pub async fn open_in_memory() -> Result<Self, CacheError> {
+        Self::init(Connection::open_in_memory().await?).await
+    }
+
+    async fn init(conn: Connection) -> Result<Self, CacheError> {
+        conn.call(|conn| {
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+            conn.execute(SCHEMA, [])?;
+            Ok::<_, tokio_rusqlite::rusqlite::Error>(())
+        })
+        .await?;
+        Ok(Self { conn })
+    }
+
+    /// Cache key for `text`.
+    pub fn hash(text: &str) -> String {
+        Sha256::digest(text.as_bytes())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    /// Cached vectors for the given hashes; absent hashes are misses.
+    pub async fn get_many(
+        &self,
+        model: &str,
+        dims: usize,
+        hashes: &[String],
+    ) -> Result<HashMap<String, Vec<f64>>, CacheError> {
+        let model = model.to_string();
+        let hashes = hashes.to_vec();
+        let found = self
+            .conn
+            .call(move |conn| {
+                let mut found = HashMap::new();
+                for chunk in hashes.chunks(LOOKUP_CHUNK) {
+                    let placeholders = vec!["?"; chunk.len()].join(", ");
+                    let mut stmt = conn.prepare(&format!(
+                        "SELECT hash, vector FROM embeddings \
+                         WHERE model = ? AND dims = ? AND hash IN ({placeholders})"
+                    ))?;
+                    let mut params: Vec<&dyn tokio_rusqlite::ToSql> = vec![&model, &dims];
+                    params.extend(chunk.iter().map(|h| h as &dyn tokio_rusqlite::ToSql));
+                    let rows = stmt.query_map(params.as_slice(), |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+                    })?;
+                    for row in rows {
+                        let (hash, blob) = row?;
+                        found.insert(hash, decode(&blob));
+                    }
+                }
+                Ok::<_, tokio_rusqlite::rusqlite::Error>(found)
+            })
+            .await?;
+        Ok(found)
+    }
+
+    /// Store `(hash, vector)` pairs, replacing existing entries.
+    pub async fn put_many(
+        &self,
+        model: &str,
+        dims: usize,
+        entries: Vec<(String, Vec<f64>)>,
+    ) -> Result<(), CacheError> {
+        let model = model.to_string();
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                {
+                    let mut stmt = tx.prepare(
+                        "INSERT OR REPLACE INTO embeddings (model, dims, hash, vector) \
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )?;
+                    for (hash, vector) in &entries {
+                        stmt.execute(params![model, dims, hash, encode(vector)])?;
+                    }
+                }
+                tx.commit()
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Number of cached vectors across all models.
+    pub async fn len(&self) -> Result<usize, CacheError> {
+        let count = self
+            .conn
+            .call(|conn| {
+                conn.query_row("SELECT COUNT(*) FROM embeddings", [], |row| {
+                    row.get::<_, i64>(0)
+                })
+            })
+            .await?;
+        Ok(count as usize)
+    }
+
+    pub async fn is_empty(&self) -> Result<bool, CacheError> {
+        Ok(self.len().await? == 0)
+    }
+
+    /// Drop cached vectors for `model`, or for every model when `None`.
+    pub async fn clear(&self, model: Option<&str>) -> Result<usize, CacheError> {
+        let model = model.map(str::to_string);
+        let removed = self
+            .conn
+            .call(move |conn| match model {
+                Some(model) => conn.execute("DELETE FROM embeddings WHERE model = ?1", [model]),
+                None => conn.execute("DELETE FROM embeddings", []),
+            })
+            .await?;
+        Ok(removed)
+    }
+}
+
+fn encode(vector: &[f64]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn decode(blob: &[u8]) -> Vec<f64> {
+    blob.chunks_exact(8)
+        .map(|b| f64::from_le_bytes(b.try_into().expect("chunks of 8 bytes")))
+        .collect()
+}
+
+/// Embedding model wrapper that serves repeated texts from an [`EmbeddingCache`].
+///
+/// Only texts missing from the cache are sent to the wrapped model (duplicates within a call
+/// are sent once); results come back in input order. Cache failures are logged and treated
+/// as misses, so a broken cache never fails an embedding call.
+#[derive(Clone, Debug)]
+pub struct CachedEmbeddingModel<M> {
+    inner: M,
+    cache: EmbeddingCache,
+    model: String,
+    counters: Arc<Counters>,
+}
+
+impl<M> CachedEmbeddingModel<M>
+where
+    M: embeddings::EmbeddingModel,
+{
+    /// `model` is the cache namespace, normally the provider model name.
+    pub fn new(inner: M, cache: EmbeddingCache, model: impl Into<String>) -> Self {
+        Self {
+            inner,
+            cache,
+            model: model.into(),
+            counters: Arc::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    pub fn cache(&self) -> &EmbeddingCache {
+        &self.cache
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_stats(&self) {
+        self.counters.hits.store(0, Ordering::Relaxed);
+        self.counters.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<M> embeddings::EmbeddingModel for CachedEmbeddingModel<M>
+where
+    M: embeddings::EmbeddingModel,
+{
+    const MAX_DOCUMENTS: usize = M::MAX_DOCUMENTS;
+
+    /// The wrapped model's client plus the cache to put in front of it.
+    type Client = (M::Client, EmbeddingCache);
+
+    fn make(client: &Self::Client, model: impl Into<String>, dims: Option<usize>) -> Self {
+        let model = model.into();
+        let inner = M::make(&client.0, model.clone(), dims);
+        Self::new(inner, client.1.clone(), model)
+    }
+
+    fn ndims(&self) -> usize {
+        self.inner.ndims()
+    }
+
+    async fn embed_texts(
+        &self,
+        texts: impl IntoIterator<Item = String> + WasmCompatSend,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        let texts: Vec<String> = texts.into_iter().collect();
+        let hashes: Vec<String> = texts.iter().map(|t| EmbeddingCache::hash(t)).collect();
+        let dims = self.inner.ndims();
+
+        let mut found = self
+            .cache
+            .get_many(&self.model, dims, &hashes)
+            .await
+            .unwrap_or_else(|err| {
+                tracing::warn!(target: "rig::embedding_cache", "cache lookup failed: {err}");
+                HashMap::new()
+            });
+
+        // Unique misses, in first-seen order.
+        let mut misses = 0u64;
+        let mut queued = HashSet::new();
+        let mut pending: Vec<(String, String)> = Vec::new();
+        for (text, hash) in texts.iter().zip(&hashes) {
+            if found.contains_key(hash) {
+                continue;
+            }
+            misses += 1;
+            if queued.insert(hash) {
+                pending.push((hash.clone(), text.clone()));
+            }
+        }
+        self.counters
+            .hits
+            .fetch_add(texts.len() as u64 - misses, Ordering::Relaxed);
+        self.counters.misses.fetch_add(misses, Ordering::Relaxed);
+        tracing::debug!(
+            target: "rig::embedding_cache",
+            model = self.model,
+            hits = texts.len() as u64 - misses,
+            misses,
+            "embedding cache lookup"
+        );
+
+        if !pending.is_empty() {
+            let embedded = self
+                .inner
+                .embed_texts(
+                    pending
+                        .iter()
+                        .map(|(_, text)| text.clone())
+                        .collect::<Vec<_>>(),
+                )
+                .await?;
+            if embedded.len() != pending.len() {
+                return Err(EmbeddingError::ResponseError(
+                    "Response data length does not match input length".into(),
+                ));
+            }
+
+            let entries: Vec<(String, Vec<f64>)> = pending
+                .into_iter()
+                .zip(embedded)
+                .map(|((hash, _), embedding)| (hash, embedding.vec))
+                .collect();
+            if let Err(err) = self
+                .cache
+                .put_many(&self.model, dims, entries.clone())
+                .await
+            {
+                tracing::warn!(target: "rig::embedding_cache", "cache write failed: {err}");
+            }
+            found.extend(entries);
+        }
+
+        Ok(texts
+            .into_iter()
+            .zip(hashes)
+            .map(|(document, hash)| Embedding {
+                vec: found.get(&hash).cloned().unwrap_or_default(),
+                document,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,378 @@
+//! Category: error.rs (structured provider errors: status, code, request id, classification)
+
+use std::fmt;
+
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use rig::client::VerifyError;
+use rig::completion::{CompletionError, PromptError};
+use rig::embeddings::EmbeddingError;
+use rig::http_client;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::rerank::RerankError;
+
+/// Response headers that carry the provider's request id.
+pub const REQUEST_ID_HEADERS: &[&str] = &["x-request-id", "x-dashscope-request-id", "x-tt-logid"];
+
+/// Coarse category of a provider failure, for branching without string matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// Missing, invalid or unauthorised API key.
+    Auth,
+    /// Too many requests; usually succeeds after waiting.
+    RateLimit,
+    /// Account balance, free tier or quota exhausted.
+    Quota,
+    /// Input or output rejected by the provider's content moderation.
+    ContentFilter,
+    /// Prompt (plus `max_tokens`) exceeds the model's context window.
+    ContextLength,
+    /// Malformed request, unknown model or unsupported parameter.
+    InvalidParam,
+    /// Provider-side failure (5xx, overload, internal error).
+    Server,
+    Other,
+}
+
+impl ErrorClass {
+    /// Classify from the HTTP status and the provider's `code`, `type` and message.
+    ///
+    /// Provider codes win over the status: DashScope reports arrears as `400 Arrearage` and
+    /// content moderation as `400 DataInspectionFailed`.
+    pub fn classify(
+        status: Option<u16>,
+        code: Option<&str>,
+        error_type: Option<&str>,
+        message: &str,
+    ) -> Self {
+        let tags = format!(
+            "{} {}",
+            code.unwrap_or_default(),
+            error_type.unwrap_or_default()
+        )
+        .to_ascii_lowercase();
+        let message = message.to_ascii_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| tags.contains(n));
+        let says = |needles: &[&str]| needles.iter().any(|n| message.contains(n));
+
+        if has(&[
+            "datainspection",
+            "data_inspection",
+            "sensitivecontent",
+            "content_filter",
+            "riskdetection",
+        ]) || says(&["inappropriate content"])
+        {
+            Self::ContentFilter
+        } else if has(&["context_length", "contextlength", "too_long"])
+            || says(&[
+                "maximum context length",
+                "range of input length",
+                "exceeds the model's context",
+                "exceed max message tokens",
+            ])
+        {
+            Self::ContextLength
+        } else if has(&["throttl"]) {
+            // DashScope's `Throttling.RateQuota` is a rate limit, not an exhausted quota.
+            Self::RateLimit
+        } else if has(&["arrearage", "quota", "overdue", "insufficient", "freetier"]) {
+            Self::Quota
+        } else if has(&[
+            "invalid_api_key",
+            "invalidapikey",
+            "authentication",
+            "unauthorized",
+            "accessdenied",
+            "access_denied",
+            "permission",
+        ]) {
+            Self::Auth
+        } else if has(&["ratelimit", "rate_limit", "too_many"]) {
+            Self::RateLimit
+        } else if has(&["internal", "serviceunavailable", "server_error", "overload"]) {
+            Self::Server
+        } else if has(&[
+            "invalidparameter",
+            "invalid_parameter",
+            "invalid_request",
+            "invalidendpoint",
+            "not_found",
+            "notfound",
+            "validation",
+        ]) {
+            Self::InvalidParam
+        } else {
+            match status {
+                Some(401 | 403) => Self::Auth,
+                Some(429) => Self::RateLimit,
+                Some(400 | 404 | 413 | 422) => Self::InvalidParam,
+                Some(500..) => Self::Server,
+                _ => Self::Other,
+            }
+        }
+    }
+
+    /// Whether repeating the same request later can succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::RateLimit | Self::Server)
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Auth => "auth",
+            Self::RateLimit => "rate-limit",
+            Self::Quota => "quota",
+            Self::ContentFilter => "content-filter",
+            Self::ContextLength => "context-length",
+            Self::InvalidParam => "invalid-param",
+            Self::Server => "server",
+            Self::Other => "other",
+        })
+    }
+}
+
+/// Error reported by a provider API, parsed from the response status, headers and body.
+///
+/// Understands the OpenAI-compatible shape used by DashScope compatible-mode and Ark
+/// (`{"error": {"message", "type", "code"}, "request_id"}`), DashScope's native shape
+/// (`{"code", "message", "request_id"}`) and TEI's (`{"error", "error_type"}`); any other
+/// body is kept verbatim as the message.
+///
+/// Failed HTTP calls surface as `HttpError(http_client::Error::Instance(ProviderError))`
+/// inside rig's error enums; use [`ProviderErrorExt::provider_error`] to get at it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderError {
+    /// HTTP status, absent when the error came back in a 200 body.
+    pub status: Option<u16>,
+    /// Provider error code, e.g. `invalid_api_key`, `Arrearage`, `InvalidParameter`.
+    pub code: Option<String>,
+    /// Provider error type, e.g. `invalid_request_error`, `Unauthorized`, `Validation`.
+    pub error_type: Option<String>,
+    pub message: String,
+    /// Id to quote in support tickets.
+    pub request_id: Option<String>,
+    pub class: ErrorClass,
+}
+
+#[derive(Deserialize)]
+struct Body {
+    error: Option<ErrorField>,
+    code: Option<Value>,
+    message: Option<String>,
+    error_type: Option<String>,
+    request_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ErrorField {
+    Object {
+        message: Option<String>,
+        #[serde(rename = "type")]
+        error_type: Option<String>,
+        code: Option<Value>,
+        request_id: Option<String>,
+    },
+    Message(String),
+}
+
+/// Codes arrive as strings from most providers, as numbers from a few.
+fn code_string(code: Option<Value>) -> Option<String> {
+    match code? {
+        Value::String(s) if !s.is_empty() => Some(s),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Ark embeds the id in the message: "... Request id: 0217...".
+fn request_id_in_message(message: &str) -> Option<String> {
+    let (_, rest) = message.split_once("Request id: ")?;
+    let id: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    (!id.is_empty()).then_some(id)
+}
+
+impl ProviderError {
+    pub fn new(status: Option<u16>, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            class: ErrorClass::classify(status, None, None, &message),
+            status,
+            code: None,
+            error_type: None,
+            request_id: None,
+            message,
+        }
+    }
+
+    /// Parse an error response body; `status` is `None` for errors embedded in a 200 body.
+    pub fn from_body(status: Option<u16>, body: &str) -> Self {
+        let Ok(parsed) = serde_json::from_str::<Body>(body) else {
+            return Self::new(status, body.trim());
+        };
+
+        let (message, code, error_type, nested_id) = match parsed.error {
+            Some(ErrorField::Object {
+                message,
+                error_type,
+                code,
+                request_id,
+            }) => (message, code_string(code), error_type, request_id),
+            Some(ErrorField::Message(message)) => (Some(message), None, parsed.error_type, None),
+            None => (
+                parsed.message,
+                code_string(parsed.code),
+                parsed.error_type,
+                None,
+            ),
+        };
+        let message = message.unwrap_or_else(|| body.trim().to_string());
+        let request_id = parsed
+            .request_id
+            .or(nested_id)
+            .or_else(|| request_id_in_message(&message));
+
+        Self {
+            class: ErrorClass::classify(status, code.as_deref(), error_type.as_deref(), &message),
+            status,
+            code,
+            error_type,
+            message,
+            request_id,
+        }
+    }
+
+    /// Parse a failed response, taking the request id from the headers when the body lacks it.
+    pub fn from_response(status: StatusCode, headers: &HeaderMap, body: &str) -> Self {
+        let mut err = Self::from_body(Some(status.as_u16()), body);
+        if err.request_id.is_none() {
+            err.request_id = REQUEST_ID_HEADERS
+                .iter()
+                .find_map(|name| headers.get(*name)?.to_str().ok())
+                .map(str::to_string);
+        }
+        err
+    }
+
+    /// Turn an HTTP status error from the underlying client into a [`ProviderError`];
+    /// other errors are returned unchanged.
+    pub fn lift(error: http_client::Error) -> http_client::Error {
+        match error {
+            http_client::Error::InvalidStatusCodeWithMessage(status, text) => {
+                Self::from_body(Some(status.as_u16()), &text).into()
+            }
+            http_client::Error::InvalidStatusCode(status) => Self::new(
+                Some(status.as_u16()),
+                status.canonical_reason().unwrap_or_default(),
+            )
+            .into(),
+            other => other,
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.class.is_transient()
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} error", self.class)?;
+        if let Some(status) = self.status {
+            write!(f, " (status {status})")?;
+        }
+        if let Some(code) = &self.code {
+            write!(f, " [{code}]")?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(request_id) = &self.request_id {
+            write!(f, " (request_id: {request_id})")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<ProviderError> for http_client::Error {
+    fn from(err: ProviderError) -> Self {
+        http_client::Error::Instance(Box::new(err))
+    }
+}
+
+/// Access to the [`ProviderError`] inside rig's and rig-extend's error types.
+pub trait ProviderErrorExt {
+    fn provider_error(&self) -> Option<&ProviderError>;
+
+    fn error_class(&self) -> Option<ErrorClass> {
+        self.provider_error().map(|e| e.class)
+    }
+
+    fn request_id(&self) -> Option<&str> {
+        self.provider_error()?.request_id.as_deref()
+    }
+}
+
+impl ProviderErrorExt for http_client::Error {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            http_client::Error::Instance(err) => err.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl ProviderErrorExt for CompletionError {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            CompletionError::HttpError(err) => err.provider_error(),
+            CompletionError::RequestError(err) => err.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl ProviderErrorExt for PromptError {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            PromptError::CompletionError(err) => err.provider_error(),
+            _ => None,
+        }
+    }
+}
+
+impl ProviderErrorExt for EmbeddingError {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            EmbeddingError::HttpError(err) => err.provider_error(),
+            EmbeddingError::DocumentError(err) => err.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl ProviderErrorExt for RerankError {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            RerankError::HttpError(err) => err.provider_error(),
+            _ => None,
+        }
+    }
+}
+
+impl ProviderErrorExt for VerifyError {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            VerifyError::HttpError(err) => err.provider_error(),
+            _ => None,
+        }
+    }
+}
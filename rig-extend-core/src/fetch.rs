@@ -0,0 +1,200 @@
+//! Category: fetch.rs (HttpClientExt over the Fetch API, for Cloudflare Workers)
+
+use std::fmt;
+
+use bytes::Bytes;
+use rig::http_client::sse::BoxedStream;
+use rig::http_client::{
+    self, Error, HttpClientExt, LazyBody, Request, Response, StreamingResponse,
+};
+use rig::wasm_compat::WasmCompatSend;
+use send_wrapper::SendWrapper;
+use wasm_bindgen::{JsCast, JsValue, prelude::wasm_bindgen};
+use wasm_bindgen_futures::JsFuture;
+
+#[wasm_bindgen]
+extern "C" {
+    /// The global `fetch`, present in Workers, service workers and browsers alike.
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_with_request(request: &web_sys::Request) -> js_sys::Promise;
+}
+
+/// HTTP client backed by the runtime's `fetch`, for `wasm32-unknown-unknown` targets such
+/// as Cloudflare Workers, where there are no sockets for reqwest's native backend.
+///
+/// Use it as the client's HTTP backend; completions, streaming and embeddings then run
+/// inside the Worker:
+///
+/// ```ignore
+/// let client = rig_bailian::Client::builder(&api_key)
+///     .with_client(FetchClient::new())
+///     .build();
+/// let qwen = client.completion_model(rig_bailian::QWEN3_MAX);
+/// ```
+///
+/// Error statuses come back as responses with their headers, so the client's
+/// [`ProviderError`](crate::ProviderError) keeps the request id and retries honour
+/// `Retry-After`, as with reqwest. JS objects are not `Send`; this is fine on wasm, which
+/// is single-threaded. Calling it on a native target panics.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FetchClient;
+
+impl FetchClient {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// A JS exception or rejected promise, rendered as text.
+#[derive(Debug, Clone)]
+pub struct FetchError(pub String);
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fetch failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+fn js_error(value: JsValue) -> Error {
+    let message = value
+        .dyn_ref::<js_sys::Error>()
+        .map(|err| String::from(err.message()))
+        .or_else(|| value.as_string())
+        .unwrap_or_else(|| format!("{value:?}"));
+    Error::Instance(Box::new(FetchError(message)))
+}
+
+async fn fetch(req: Request<Bytes>) -> http_client::Result<web_sys::Response> {
+    let (parts, body) = req.into_parts();
+    let headers = web_sys::Headers::new().map_err(js_error)?;
+    for (name, value) in &parts.headers {
+        let value = value
+            .to_str()
+            .map_err(|e| Error::Instance(Box::new(FetchError(e.to_string()))))?;
+        headers.append(name.as_str(), value).map_err(js_error)?;
+    }
+
+    let init = web_sys::RequestInit::new();
+    init.set_method(parts.method.as_str());
+    init.set_headers(&headers);
+    if !body.is_empty() {
+        init.set_body(&js_sys::Uint8Array::from(body.as_ref()));
+    }
+    let request =
+        web_sys::Request::new_with_str_and_init(&parts.uri.to_string(), &init).map_err(js_error)?;
+
+    JsFuture::from(fetch_with_request(&request))
+        .await
+        .map_err(js_error)?
+        .dyn_into::<web_sys::Response>()
+        .map_err(js_error)
+}
+
+async fn bytes(response: &web_sys::Response) -> http_client::Result<Bytes> {
+    let buffer = JsFuture::from(response.array_buffer().map_err(js_error)?)
+        .await
+        .map_err(js_error)?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec().into())
+}
+
+/// Status and headers, error statuses included; the body is read separately.
+fn head(response: &web_sys::Response) -> http_client::Result<Response<()>> {
+    let status =
+        reqwest::StatusCode::from_u16(response.status()).map_err(|e| Error::Protocol(e.into()))?;
+    let mut builder = Response::builder().status(status);
+    if let Ok(Some(entries)) = js_sys::try_iter(response.headers().as_ref()) {
+        for entry in entries.flatten() {
+            let entry = js_sys::Array::from(&entry);
+            if let (Some(name), Some(value)) = (entry.get(0).as_string(), entry.get(1).as_string())
+            {
+                builder = builder.header(name, value);
+            }
+        }
+    }
+    builder.body(()).map_err(Error::Protocol)
+}
+
+/// Chunks of a `ReadableStream` body.
+fn body_stream(
+    response: &web_sys::Response,
+) -> impl futures::Stream<Item = http_client::Result<Bytes>> + use<> {
+    let reader = response.body().map(|body| {
+        body.get_reader()
+            .unchecked_into::<web_sys::ReadableStreamDefaultReader>()
+    });
+    futures::stream::unfold(reader, |reader| async move {
+        let reader = reader?;
+        let chunk = match JsFuture::from(reader.read()).await {
+            Ok(chunk) => chunk,
+            Err(err) => return Some((Err(js_error(err)), None)),
+        };
+        let done = js_sys::Reflect::get(&chunk, &JsValue::from_str("done"))
+            .ok()
+            .and_then(|done| done.as_bool())
+            .unwrap_or(true);
+        if done {
+            return None;
+        }
+        let value = js_sys::Reflect::get(&chunk, &JsValue::from_str("value"))
+            .map_err(js_error)
+            .map(|value| Bytes::from(js_sys::Uint8Array::new(&value).to_vec()));
+        Some((value, Some(reader)))
+    })
+}
+
+impl HttpClientExt for FetchClient {
+    fn send<B, U>(
+        &self,
+        req: Request<B>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        B: Into<Bytes>,
+        B: WasmCompatSend,
+        U: From<Bytes>,
+        U: WasmCompatSend + 'static,
+    {
+        let (parts, body) = req.into_parts();
+        let req = Request::from_parts(parts, body.into());
+        SendWrapper::new(async move {
+            let response = fetch(req).await?;
+            let head = head(&response)?;
+            let body: LazyBody<U> = Box::pin(SendWrapper::new(async move {
+                Ok(U::from(bytes(&response).await?))
+            }));
+            Ok(head.map(|()| body))
+        })
+    }
+
+    fn send_multipart<U>(
+        &self,
+        _req: Request<reqwest::multipart::Form>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        U: From<Bytes>,
+        U: WasmCompatSend + 'static,
+    {
+        // reqwest's `Form` cannot be read back into a body; no provider here needs it.
+        std::future::ready(Err(Error::Instance(Box::new(FetchError(
+            "multipart requests are not supported".to_string(),
+        )))))
+    }
+
+    fn send_streaming<B>(
+        &self,
+        req: Request<B>,
+    ) -> impl Future<Output = http_client::Result<StreamingResponse>> + WasmCompatSend
+    where
+        B: Into<Bytes>,
+    {
+        let (parts, body) = req.into_parts();
+        let req = Request::from_parts(parts, body.into());
+        SendWrapper::new(async move {
+            let response = fetch(req).await?;
+            let head = head(&response)?;
+            let stream: BoxedStream = Box::pin(SendWrapper::new(body_stream(&response)));
+            Ok(head.map(|()| stream))
+        })
+    }
+}
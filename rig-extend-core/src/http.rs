@@ -0,0 +1,228 @@
+//! Category: http.rs (reqwest client options: timeouts, proxy, default headers, TLS)
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// Settings for the `reqwest::Client` a provider client builds for itself.
+///
+/// Anything left unset keeps reqwest's default (no timeouts, system proxy, built-in roots),
+/// so setting one option does not cost the others. The client builders expose these as
+/// `connect_timeout`, `proxy`, `header`, … through [`HttpOptionsExt`] when they use the
+/// default `reqwest::Client`.
+#[derive(Clone, Default)]
+pub struct HttpOptions {
+    pub connect_timeout: Option<Duration>,
+    /// Whole-request deadline, including reading the body. Avoid it for long streams and set
+    /// `stream_idle_timeout` instead.
+    pub timeout: Option<Duration>,
+    /// Longest wait for the next chunk of a response body, streamed or not.
+    pub stream_idle_timeout: Option<Duration>,
+    pub proxies: Vec<reqwest::Proxy>,
+    /// Ignore proxy settings, including the system proxy.
+    pub no_proxy: bool,
+    /// Sent with every request; per-request headers (such as `Authorization`) win.
+    pub headers: HeaderMap,
+    pub user_agent: Option<HeaderValue>,
+    /// Extra trust anchors, e.g. the CA of an on-prem TEI deployment.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub root_certificates: Vec<reqwest::Certificate>,
+    /// Trust only `root_certificates`, not the built-in roots.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub only_root_certificates: bool,
+    /// Skip certificate verification. Only for local testing.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl std::fmt::Debug for HttpOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut f = f.debug_struct("HttpOptions");
+        f.field("connect_timeout", &self.connect_timeout)
+            .field("timeout", &self.timeout)
+            .field("stream_idle_timeout", &self.stream_idle_timeout)
+            .field("proxies", &self.proxies)
+            .field("no_proxy", &self.no_proxy)
+            .field("headers", &self.headers)
+            .field("user_agent", &self.user_agent);
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        f.field("root_certificates", &self.root_certificates.len())
+            .field("only_root_certificates", &self.only_root_certificates)
+            .field(
+                "danger_accept_invalid_certs",
+                &self.danger_accept_invalid_certs,
+            );
+        f.finish()
+    }
+}
+
+impl HttpOptions {
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn stream_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: HeaderValue) -> Self {
+        self.user_agent = Some(user_agent);
+        self
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn only_root_certificates(mut self) -> Self {
+        self.only_root_certificates = true;
+        self
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.danger_accept_invalid_certs = accept;
+        self
+    }
+
+    /// Start from `reqwest::Client::builder()` and apply every option that is set.
+    pub fn client_builder(&self) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.stream_idle_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        }
+        for proxy in &self.proxies {
+            builder = builder.proxy(proxy.clone());
+        }
+        if !self.headers.is_empty() {
+            builder = builder.default_headers(self.headers.clone());
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent.clone());
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        {
+            for certificate in &self.root_certificates {
+                builder = builder.add_root_certificate(certificate.clone());
+            }
+            if self.only_root_certificates {
+                builder = builder.tls_built_in_root_certs(false);
+            }
+            if self.danger_accept_invalid_certs {
+                builder = builder.danger_accept_invalid_certs(true);
+            }
+        }
+        builder
+    }
+
+    pub fn build(&self) -> reqwest::Result<reqwest::Client> {
+        self.client_builder().build()
+    }
+}
+
+/// [`HttpOptions`] setters for client builders over the default `reqwest::Client`. Unset
+/// options keep reqwest's defaults.
+pub trait HttpOptionsExt: Sized {
+    /// Apply `f` to the builder's HTTP options (the defaults if none were set yet).
+    fn map_http_options(self, f: impl FnOnce(HttpOptions) -> HttpOptions) -> Self;
+
+    /// Replace all HTTP options at once.
+    fn http_options(self, options: HttpOptions) -> Self {
+        self.map_http_options(|_| options)
+    }
+
+    /// Time allowed to establish a connection.
+    fn connect_timeout(self, timeout: Duration) -> Self {
+        self.map_http_options(|o| o.connect_timeout(timeout))
+    }
+
+    /// Deadline for a whole request, body included; prefer `stream_idle_timeout` for streams.
+    fn timeout(self, timeout: Duration) -> Self {
+        self.map_http_options(|o| o.timeout(timeout))
+    }
+
+    /// Longest pause allowed between two chunks of a response body.
+    fn stream_idle_timeout(self, timeout: Duration) -> Self {
+        self.map_http_options(|o| o.stream_idle_timeout(timeout))
+    }
+
+    /// Route requests through `proxy`, e.g. `reqwest::Proxy::all("http://proxy:3128")`.
+    fn proxy(self, proxy: reqwest::Proxy) -> Self {
+        self.map_http_options(|o| o.proxy(proxy))
+    }
+
+    /// Ignore proxy settings, including `HTTP(S)_PROXY`.
+    fn no_proxy(self) -> Self {
+        self.map_http_options(HttpOptions::no_proxy)
+    }
+
+    /// Header sent with every request, e.g. a gateway tenant id.
+    fn header(self, name: HeaderName, value: HeaderValue) -> Self {
+        self.map_http_options(|o| o.header(name, value))
+    }
+
+    fn headers(self, headers: HeaderMap) -> Self {
+        self.map_http_options(|o| o.headers(headers))
+    }
+
+    fn user_agent(self, user_agent: HeaderValue) -> Self {
+        self.map_http_options(|o| o.user_agent(user_agent))
+    }
+
+    /// Trust an extra CA, e.g. `reqwest::Certificate::from_pem(&pem)?`.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    fn add_root_certificate(self, certificate: reqwest::Certificate) -> Self {
+        self.map_http_options(|o| o.add_root_certificate(certificate))
+    }
+
+    /// Trust only the certificates added with `add_root_certificate`.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    fn only_root_certificates(self) -> Self {
+        self.map_http_options(HttpOptions::only_root_certificates)
+    }
+
+    /// Skip certificate verification. Only for local testing.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    fn danger_accept_invalid_certs(self, accept: bool) -> Self {
+        self.map_http_options(|o| o.danger_accept_invalid_certs(accept))
+    }
+}
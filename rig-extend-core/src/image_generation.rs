@@ -0,0 +1,64 @@
+//! Category: image_generation.rs (ImageGenerationClient for profiles with an image API, feature `image`)
+
+use rig::client::image_generation::ImageGenerationClient;
+use rig::http_client::{self, HttpClientExt};
+use rig::image_generation::{ImageGenerationError, ImageGenerationModel};
+
+use super::client::Client;
+use super::error::{ProviderError, ProviderErrorExt};
+use super::profile::Profile;
+
+/// A profile whose provider has an image generation API. Implementing it gives
+/// `Client<Self, T>` rig's [`ImageGenerationClient`], so `client.image_generation_model(..)`
+/// works as for the built-in rig providers.
+pub trait ImageGenerationProfile<T>: Profile {
+    type ImageGenerationModel: ImageGenerationModel<Client = Client<Self, T>>;
+}
+
+impl<P, T> ImageGenerationClient for Client<P, T>
+where
+    P: ImageGenerationProfile<T>,
+{
+    type ImageGenerationModel = P::ImageGenerationModel;
+
+    fn image_generation_model(&self, model: impl Into<String>) -> Self::ImageGenerationModel {
+        P::ImageGenerationModel::make(self, model)
+    }
+}
+
+impl ProviderErrorExt for ImageGenerationError {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            ImageGenerationError::HttpError(err) => err.provider_error(),
+            ImageGenerationError::RequestError(err) => err.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Fetch a generated image from the URL the provider returned. The API key is not sent:
+/// result URLs are pre-signed object-storage links that reject extra credentials.
+pub async fn download<P, T>(
+    client: &Client<P, T>,
+    url: &str,
+) -> Result<Vec<u8>, ImageGenerationError>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    let req = http_client::Builder::new()
+        .method(http_client::Method::GET)
+        .uri(url)
+        .body(http_client::NoBody)
+        .map_err(http_client::Error::from)?;
+    let response = HttpClientExt::send::<_, Vec<u8>>(client.http_client(), req).await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let headers = response.headers().clone();
+        let text = String::from_utf8_lossy(&response.into_body().await?).into_owned();
+        return Err(ImageGenerationError::HttpError(
+            ProviderError::from_response(status, &headers, &text).into(),
+        ));
+    }
+    Ok(response.into_body().await?)
+}
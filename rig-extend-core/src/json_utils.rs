@@ -0,0 +1,19 @@
+use serde_json::Value;
+
+/// Local deep-merge helper to avoid private rig::json_utils.
+/// - Merge objects recursively, right overrides left; otherwise returns right.
+pub fn merge(left: Value, right: Value) -> Value {
+    match (left, right) {
+        (Value::Object(mut a), Value::Object(b)) => {
+            for (k, v) in b {
+                let merged = match a.remove(&k) {
+                    Some(existing) => merge(existing, v),
+                    None => v,
+                };
+                a.insert(k, merged);
+            }
+            Value::Object(a)
+        }
+        (_, r) => r,
+    }
+}
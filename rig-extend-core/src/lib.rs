@@ -0,0 +1,96 @@
+//! Category: lib.rs (top-level module and re-exports)
+//! Shared OpenAI-compatible core used by rig-bailian and rig-volcengine:
+//! - profile.rs: Provider profile (name, base URL, env var names, request quirks)
+//! - client.rs: Generic Client and Builder; implements Provider/Verify/Completion/Embedding
+//! - completion.rs: Chat completion model (OpenAI-compatible), Thinking (reasoning mode)
+//! - embedding.rs: Text embeddings (OpenAI-compatible)
+//! - config.rs: Config (named clients and models from TOML/YAML), FromClientConfig/FromModelConfig
+//! - batching.rs: EmbeddingBatching (per-model batch size, concurrency) and per-batch errors
+//! - embedding_cache.rs: CachedEmbeddingModel and EmbeddingCache (SQLite, feature `sqlite`)
+//! - error.rs: ProviderError (status, code, request id) and ErrorClass, ProviderErrorExt accessors
+//! - retry.rs: RetryPolicy (backoff, jitter, Retry-After)
+//! - rate_limit.rs: RateLimits and RateLimiter (RPM/TPM buckets, in-flight cap)
+//! - transport.rs: Transport (HTTP client wrapper applying retries, rate limits and circuit breaking)
+//! - circuit_breaker.rs: CircuitBreakerPolicy and CircuitBreaker (per-endpoint circuits, half-open probes)
+//! - http.rs: HttpOptions (timeouts, proxy, default headers, user-agent, TLS for reqwest, feature `reqwest`)
+//! - routing.rs: RoutingModel (fallback and weighted routing over several completion models)
+//! - usage.rs: UsageLedger (token usage per model/operation/tag), PriceTable and UsageSnapshot
+//! - registry.rs: ProviderRegistry (provider ids to factories) and boxed Dyn* models
+//! - rerank.rs: Provider-agnostic Reranker trait, result and error types
+//! - streaming.rs: Streaming (OpenAI-compatible)
+//! - structured.rs: ResponseFormat (json_object / json_schema) and StructuredExtractor (typed output)
+//! - vision.rs: Image and video parts in user messages (data URIs, Qwen-VL frame lists), ImageLimits
+//! - image_generation.rs: ImageGenerationProfile (rig ImageGenerationClient on Client) and image download, feature `image`
+//! - vector_store.rs: RerankedIndex (over-fetch from any VectorStoreIndex, then rerank)
+//! - rt.rs: Timers and clocks (tokio natively, JS event loop on wasm32)
+//! - fetch.rs: FetchClient (HttpClientExt over the Fetch API for Cloudflare Workers, feature `workers`)
+//! - json_utils.rs: Local JSON helpers (deep merge)
+//! - types/mod.rs: Shared wire types (error response, tool choice mapping)
+
+pub mod batching;
+pub mod circuit_breaker;
+pub mod client;
+pub mod completion;
+pub mod config;
+pub mod embedding;
+#[cfg(feature = "sqlite")]
+pub mod embedding_cache;
+pub mod error;
+#[cfg(feature = "workers")]
+pub mod fetch;
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+pub mod http;
+#[cfg(feature = "image")]
+pub mod image_generation;
+pub mod json_utils;
+pub mod profile;
+pub mod rate_limit;
+pub mod registry;
+pub mod rerank;
+pub mod retry;
+pub mod routing;
+pub mod rt;
+pub mod streaming;
+pub mod structured;
+pub mod transport;
+pub mod types;
+pub mod usage;
+pub mod vector_store;
+pub mod vision;
+
+pub use batching::{BatchFailure, EmbeddingBatchError, EmbeddingBatching};
+pub use circuit_breaker::{CIRCUIT_OPEN, CircuitBreaker, CircuitBreakerPolicy, CircuitState};
+pub use client::{Client, ClientBuilder};
+pub use completion::{CompletionModel, Thinking};
+pub use config::{
+    ClientConfig, Config, ConfigError, FromClientConfig, FromModelConfig, ModelConfig, ModelKind,
+};
+pub use embedding::EmbeddingModel;
+#[cfg(feature = "sqlite")]
+pub use embedding_cache::{CacheError, CacheStats, CachedEmbeddingModel, EmbeddingCache};
+pub use error::{ErrorClass, ProviderError, ProviderErrorExt};
+#[cfg(feature = "workers")]
+pub use fetch::{FetchClient, FetchError};
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+pub use http::{HttpOptions, HttpOptionsExt};
+#[cfg(feature = "image")]
+pub use image_generation::ImageGenerationProfile;
+pub use profile::Profile;
+pub use rate_limit::{RateLimiter, RateLimits};
+pub use registry::{
+    DynClient, DynCompletionModel, DynEmbeddingModel, DynRerankModel, ProviderFactory,
+    ProviderRegistry,
+};
+pub use rerank::{RerankError, RerankResult, Reranker, RerankerDyn};
+pub use retry::RetryPolicy;
+pub use routing::{FailoverPolicy, RoutingModel, Strategy};
+pub use structured::{
+    JsonSchemaFormat, ResponseFormat, StructuredExtractor, StructuredOutputError,
+};
+pub use transport::Transport;
+pub use usage::{
+    ModelPrice, Operation, PriceTable, TokenUsage, UsageEntry, UsageLedger, UsageSnapshot,
+    UsageTotals,
+};
+pub use vector_store::RerankedIndex;
+pub use vision::{ImageLimits, VisionError};
@@ -0,0 +1,52 @@
+//! Category: profile.rs (provider profile: identity, defaults and request quirks)
+
+use serde_json::{Value, json};
+
+use super::completion::Thinking;
+use super::vision::ImageLimits;
+
+/// Embedding batch size for profiles that do not know their provider's limit.
+pub const DEFAULT_EMBEDDING_BATCH_SIZE: usize = 256;
+
+/// Describes one OpenAI-compatible provider.
+///
+/// A profile is a zero-sized marker type; the generic [`Client`](crate::Client),
+/// [`CompletionModel`](crate::CompletionModel) and [`EmbeddingModel`](crate::EmbeddingModel)
+/// read everything provider-specific from it.
+pub trait Profile: Clone + Default + std::fmt::Debug + Send + Sync + 'static {
+    /// Provider id recorded as `gen_ai.provider.name` in tracing spans, e.g. `"bailian"`.
+    const NAME: &'static str;
+    /// Human-readable name used in log lines, e.g. `"Bailian"`.
+    const DISPLAY_NAME: &'static str;
+    /// Default API base URL.
+    const BASE_URL: &'static str;
+    /// Environment variable holding the API key (required by `from_env`).
+    const API_KEY_ENV: &'static str;
+    /// Environment variable overriding the base URL (optional).
+    const BASE_URL_ENV: &'static str;
+    /// Path probed by `VerifyClient::verify`.
+    const VERIFY_PATH: &'static str = "/models";
+
+    /// Fields merged into every streaming chat request.
+    fn streaming_params() -> Value {
+        json!({"stream": true, "stream_options": {"include_usage": true}})
+    }
+
+    /// Request fields selecting the thinking mode set with `with_thinking`; `null` fields
+    /// are left out. Profiles without a thinking switch send nothing.
+    fn thinking_params(thinking: Thinking) -> Value {
+        let _ = thinking;
+        json!({})
+    }
+
+    /// Size and formats accepted for inline images in user messages.
+    fn image_limits() -> ImageLimits {
+        ImageLimits::default()
+    }
+
+    /// Most inputs `model` accepts in one `/embeddings` request; larger calls are split.
+    fn embedding_batch_size(model: &str) -> usize {
+        let _ = model;
+        DEFAULT_EMBEDDING_BATCH_SIZE
+    }
+}
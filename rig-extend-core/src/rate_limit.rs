@@ -0,0 +1,205 @@
+//! Category: rate_limit.rs (client-side RPM/TPM limits and in-flight cap)
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::rt::{Instant, sleep};
+use serde_json::Value;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+
+/// Request-body fields counted as output tokens when estimating a request's cost.
+const MAX_TOKEN_FIELDS: &[&str] = &["max_tokens", "max_completion_tokens"];
+
+/// Client-side limits that keep a client under provider quotas.
+///
+/// Requests wait for capacity instead of being sent and rejected with 429. Every limit is
+/// optional; an unset limit is not enforced.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimits {
+    /// Requests started per minute (each retry attempt counts).
+    pub requests_per_minute: Option<u32>,
+    /// Estimated tokens per minute; see [`estimate_tokens`].
+    pub tokens_per_minute: Option<u32>,
+    /// Requests in flight at once. A streaming request holds its slot until the stream ends.
+    pub max_in_flight: Option<usize>,
+}
+
+impl RateLimits {
+    pub fn requests_per_minute(mut self, rpm: u32) -> Self {
+        self.requests_per_minute = Some(rpm.max(1));
+        self
+    }
+
+    pub fn tokens_per_minute(mut self, tpm: u32) -> Self {
+        self.tokens_per_minute = Some(tpm.max(1));
+        self
+    }
+
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max.max(1));
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.requests_per_minute.is_some()
+            || self.tokens_per_minute.is_some()
+            || self.max_in_flight.is_some()
+    }
+}
+
+/// Token bucket refilled continuously at `capacity` per minute.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    per_sec: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = f64::from(limit);
+        Self {
+            capacity,
+            available: capacity,
+            per_sec: capacity / 60.0,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Time until `amount` is available. Amounts above capacity are clamped so that a
+    /// single oversized request waits for a full bucket rather than forever.
+    fn wait_for(&mut self, amount: f64, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.per_sec).min(self.capacity);
+        self.updated = now;
+
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.per_sec)
+        }
+    }
+
+    fn take(&mut self, amount: f64) {
+        self.available -= amount.min(self.capacity);
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+}
+
+/// Shared limiter enforcing [`RateLimits`].
+///
+/// One limiter is created per client and shared (via `Arc`) by every clone of the client and
+/// every model built from it. Waiters are served in FIFO order.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: RateLimits,
+    buckets: Mutex<Buckets>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+/// Capacity held by one request; the in-flight slot is released on drop.
+#[derive(Debug)]
+pub struct RatePermit {
+    _in_flight: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            buckets: Mutex::new(Buckets {
+                requests: limits.requests_per_minute.map(Bucket::per_minute),
+                tokens: limits.tokens_per_minute.map(Bucket::per_minute),
+            }),
+            in_flight: limits.max_in_flight.map(|n| Arc::new(Semaphore::new(n))),
+            limits,
+        }
+    }
+
+    pub fn limits(&self) -> &RateLimits {
+        &self.limits
+    }
+
+    /// Free in-flight slots, if a cap is configured.
+    pub fn available_in_flight(&self) -> Option<usize> {
+        self.in_flight.as_ref().map(|s| s.available_permits())
+    }
+
+    /// Estimated token cost of a request body, or 0 when no token limit is configured.
+    pub fn cost(&self, body: &[u8]) -> u32 {
+        if self.limits.tokens_per_minute.is_some() {
+            estimate_tokens(body)
+        } else {
+            0
+        }
+    }
+
+    /// Wait until one request costing `tokens` may start, then reserve its capacity.
+    pub async fn acquire(&self, tokens: u32) -> RatePermit {
+        {
+            // Holding the (fair) lock while sleeping keeps waiters in arrival order.
+            let mut buckets = self.buckets.lock().await;
+            loop {
+                let now = Instant::now();
+                let wait = [
+                    buckets.requests.as_mut().map(|b| b.wait_for(1.0, now)),
+                    buckets
+                        .tokens
+                        .as_mut()
+                        .map(|b| b.wait_for(f64::from(tokens), now)),
+                ]
+                .into_iter()
+                .flatten()
+                .max()
+                .unwrap_or_default();
+
+                if wait.is_zero() {
+                    break;
+                }
+                tracing::debug!(
+                    target: "rig::rate_limit",
+                    wait_ms = wait.as_millis() as u64,
+                    tokens,
+                    "waiting for rate limit capacity"
+                );
+                sleep(wait).await;
+            }
+            if let Some(bucket) = buckets.requests.as_mut() {
+                bucket.take(1.0);
+            }
+            if let Some(bucket) = buckets.tokens.as_mut() {
+                bucket.take(f64::from(tokens));
+            }
+        }
+
+        let in_flight = match &self.in_flight {
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        RatePermit {
+            _in_flight: in_flight,
+        }
+    }
+}
+
+/// Rough token estimate for a JSON request body: about four bytes per token for the body,
+/// plus any `max_tokens` / `max_completion_tokens` budget, since providers count output
+/// tokens against TPM quotas as well.
+pub fn estimate_tokens(body: &[u8]) -> u32 {
+    let input = (body.len() as u64).div_ceil(4);
+    let output = serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|value| {
+            MAX_TOKEN_FIELDS
+                .iter()
+                .find_map(|field| value.get(*field).and_then(Value::as_u64))
+        })
+        .unwrap_or(0);
+    u32::try_from(input + output).unwrap_or(u32::MAX)
+}
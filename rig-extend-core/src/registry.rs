@@ -0,0 +1,544 @@
+//! Category: registry.rs (provider ids mapped to factories that build boxed models at runtime)
+
+use std::any::{Any, TypeId};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures::StreamExt;
+use rig::client::FinalCompletionResponse;
+use rig::completion::{
+    self, CompletionError, CompletionRequest, CompletionResponse, GetTokenUsage,
+};
+use rig::embeddings::{self, Embedding, EmbeddingError};
+use rig::streaming::StreamingCompletionResponse;
+use rig::wasm_compat::{WasmBoxedFuture, WasmCompatSend, WasmCompatSync};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::config::{
+    ClientConfig, Config, ConfigError, FromClientConfig, FromModelConfig, ModelConfig, ModelKind,
+};
+use super::rerank::{RerankError, RerankResult, Reranker, RerankerDyn};
+use super::streaming::raw_choice;
+
+type AnyClient = Arc<dyn Any + Send + Sync>;
+type ClientFn = dyn Fn(&str, &ClientConfig) -> Result<AnyClient, ConfigError> + Send + Sync;
+type ModelFn<M> = dyn Fn(&AnyClient, &ModelConfig) -> Result<M, ConfigError> + Send + Sync;
+/// Clients built from a [`Config`], with the entry each was built from.
+type ClientCache = BTreeMap<String, (ClientConfig, DynClient)>;
+
+/// Builds the clients and models of one provider, type-erased.
+///
+/// ```ignore
+/// ProviderFactory::new::<Client>()
+///     .completion::<CompletionModel>()
+///     .embedding::<EmbeddingModel>()
+/// ```
+///
+/// Every model type must be built on the factory's client type; a mismatch panics when the
+/// factory is assembled.
+#[derive(Clone)]
+pub struct ProviderFactory {
+    id: &'static str,
+    client_type: TypeId,
+    client: Arc<ClientFn>,
+    completion: Option<Arc<ModelFn<DynCompletionModel>>>,
+    embedding: Option<Arc<ModelFn<DynEmbeddingModel>>>,
+    rerank: Option<Arc<ModelFn<DynRerankModel>>>,
+}
+
+impl fmt::Debug for ProviderFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderFactory")
+            .field("id", &self.id)
+            .field("kinds", &self.kinds())
+            .finish()
+    }
+}
+
+impl ProviderFactory {
+    /// A factory for clients of type `C`, registered under `C::PROVIDER`.
+    pub fn new<C>() -> Self
+    where
+        C: FromClientConfig + Send + Sync + 'static,
+    {
+        Self {
+            id: C::PROVIDER,
+            client_type: TypeId::of::<C>(),
+            client: Arc::new(|name, config| {
+                C::from_client_config(name, config).map(|client| Arc::new(client) as AnyClient)
+            }),
+            completion: None,
+            embedding: None,
+            rerank: None,
+        }
+    }
+
+    pub fn completion<M>(mut self) -> Self
+    where
+        M: FromModelConfig + completion::CompletionModel + 'static,
+        M::Response: Serialize,
+    {
+        self.completion = Some(self.model_fn(|model: M| DynCompletionModel::new(model)));
+        self
+    }
+
+    pub fn embedding<M>(mut self) -> Self
+    where
+        M: FromModelConfig + embeddings::EmbeddingModel + 'static,
+    {
+        self.embedding = Some(self.model_fn(|model: M| DynEmbeddingModel::new(model)));
+        self
+    }
+
+    pub fn rerank<M>(mut self) -> Self
+    where
+        M: FromModelConfig + Reranker + 'static,
+    {
+        self.rerank = Some(self.model_fn(|model: M| DynRerankModel::new(model)));
+        self
+    }
+
+    fn model_fn<M, D>(&self, erase: fn(M) -> D) -> Arc<ModelFn<D>>
+    where
+        M: FromModelConfig + 'static,
+        D: 'static,
+    {
+        assert!(
+            TypeId::of::<<M as FromModelConfig>::Client>() == self.client_type,
+            "{} is not built on the `{}` client type",
+            std::any::type_name::<M>(),
+            self.id
+        );
+        Arc::new(move |client, config| {
+            let client = client
+                .downcast_ref::<<M as FromModelConfig>::Client>()
+                .expect("client type checked when the factory was built");
+            M::from_model_config(client, config).map(erase)
+        })
+    }
+
+    /// Provider id, e.g. `"bailian"`.
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    /// Model kinds this provider can build.
+    pub fn kinds(&self) -> Vec<ModelKind> {
+        [
+            (ModelKind::Completion, self.completion.is_some()),
+            (ModelKind::Embedding, self.embedding.is_some()),
+            (ModelKind::Rerank, self.rerank.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(kind, supported)| supported.then_some(kind))
+        .collect()
+    }
+}
+
+/// Provider ids mapped to [`ProviderFactory`]s, for choosing providers at runtime.
+///
+/// The registry starts empty; each provider crate exposes a `provider()` factory:
+///
+/// ```ignore
+/// let registry = ProviderRegistry::new()
+///     .with(rig_bailian::provider())
+///     .with(rig_volcengine::provider())
+///     .with(rig_tei::provider());
+/// let model = registry.client_from_env(&tenant.provider)?.completion_model(&tenant.model)?;
+/// ```
+///
+/// Clients named in a [`Config`] are built once and reused by every model on them, so the
+/// models share the client's rate limits and circuit breaker. A client is rebuilt when its
+/// entry changes. Clones of the registry share these clients.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderRegistry {
+    providers: BTreeMap<&'static str, Arc<ProviderFactory>>,
+    clients: Arc<Mutex<ClientCache>>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, factory: ProviderFactory) -> Self {
+        self.register(factory);
+        self
+    }
+
+    /// Add `factory` under its id, replacing any provider registered with the same id.
+    pub fn register(&mut self, factory: ProviderFactory) {
+        self.lock_clients()
+            .retain(|_, (_, client)| client.provider() != factory.id);
+        self.providers.insert(factory.id, Arc::new(factory));
+    }
+
+    pub fn get(&self, provider: &str) -> Option<&ProviderFactory> {
+        self.providers.get(provider).map(Arc::as_ref)
+    }
+
+    /// Registered provider ids, sorted.
+    pub fn providers(&self) -> Vec<&'static str> {
+        self.providers.keys().copied().collect()
+    }
+
+    /// Build a new client from `config`; `name` is used in error messages.
+    pub fn build_client(
+        &self,
+        name: &str,
+        config: &ClientConfig,
+    ) -> Result<DynClient, ConfigError> {
+        let factory = self
+            .providers
+            .get(config.provider.as_str())
+            .ok_or_else(|| ConfigError::UnknownProvider {
+                provider: config.provider.clone(),
+                known: self.providers().join(", "),
+            })?;
+        Ok(DynClient {
+            name: name.to_string(),
+            client: (factory.client)(name, config)?,
+            factory: factory.clone(),
+        })
+    }
+
+    /// A client of `provider` configured like its `from_env`; every call builds a new one.
+    pub fn client_from_env(&self, provider: &str) -> Result<DynClient, ConfigError> {
+        self.build_client(provider, &ClientConfig::new(provider))
+    }
+
+    /// The client named `name` in `config`, built on first use.
+    pub fn client(&self, config: &Config, name: &str) -> Result<DynClient, ConfigError> {
+        let client_config = config.client_config(name)?;
+        if let Some((cached, client)) = self.lock_clients().get(name)
+            && cached == client_config
+        {
+            return Ok(client.clone());
+        }
+        let client = self.build_client(name, client_config)?;
+        self.lock_clients()
+            .insert(name.to_string(), (client_config.clone(), client.clone()));
+        Ok(client)
+    }
+
+    fn lock_clients(&self) -> MutexGuard<'_, ClientCache> {
+        self.clients.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn completion_model(
+        &self,
+        config: &Config,
+        name: &str,
+    ) -> Result<DynCompletionModel, ConfigError> {
+        let (client, model) = self.model(config, name, ModelKind::Completion)?;
+        client.completion_model_with(model)
+    }
+
+    pub fn embedding_model(
+        &self,
+        config: &Config,
+        name: &str,
+    ) -> Result<DynEmbeddingModel, ConfigError> {
+        let (client, model) = self.model(config, name, ModelKind::Embedding)?;
+        client.embedding_model_with(model)
+    }
+
+    pub fn rerank_model(&self, config: &Config, name: &str) -> Result<DynRerankModel, ConfigError> {
+        let (client, model) = self.model(config, name, ModelKind::Rerank)?;
+        client.rerank_model_with(model)
+    }
+
+    fn model<'a>(
+        &self,
+        config: &'a Config,
+        name: &str,
+        kind: ModelKind,
+    ) -> Result<(DynClient, &'a ModelConfig), ConfigError> {
+        let model = config.model_config(name)?;
+        if model.kind != kind {
+            return Err(ConfigError::KindMismatch {
+                model: name.to_string(),
+                expected: kind,
+                found: model.kind,
+            });
+        }
+        Ok((self.client(config, &model.client)?, model))
+    }
+}
+
+/// A client built by a [`ProviderRegistry`], of whichever provider its config names.
+#[derive(Clone)]
+pub struct DynClient {
+    name: String,
+    client: AnyClient,
+    factory: Arc<ProviderFactory>,
+}
+
+impl fmt::Debug for DynClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynClient")
+            .field("name", &self.name)
+            .field("provider", &self.factory.id)
+            .finish()
+    }
+}
+
+impl DynClient {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn provider(&self) -> &'static str {
+        self.factory.id
+    }
+
+    /// The concrete client, e.g. `rig_bailian::Client`.
+    pub fn downcast_ref<C: 'static>(&self) -> Option<&C> {
+        self.client.downcast_ref()
+    }
+
+    pub fn completion_model(&self, model: &str) -> Result<DynCompletionModel, ConfigError> {
+        self.completion_model_with(&self.model_config(ModelKind::Completion, model))
+    }
+
+    pub fn embedding_model(&self, model: &str) -> Result<DynEmbeddingModel, ConfigError> {
+        self.embedding_model_with(&self.model_config(ModelKind::Embedding, model))
+    }
+
+    pub fn rerank_model(&self, model: &str) -> Result<DynRerankModel, ConfigError> {
+        self.rerank_model_with(&self.model_config(ModelKind::Rerank, model))
+    }
+
+    /// Build a completion model with the defaults in `config`.
+    pub fn completion_model_with(
+        &self,
+        config: &ModelConfig,
+    ) -> Result<DynCompletionModel, ConfigError> {
+        self.build(ModelKind::Completion, &self.factory.completion, config)
+    }
+
+    pub fn embedding_model_with(
+        &self,
+        config: &ModelConfig,
+    ) -> Result<DynEmbeddingModel, ConfigError> {
+        self.build(ModelKind::Embedding, &self.factory.embedding, config)
+    }
+
+    pub fn rerank_model_with(&self, config: &ModelConfig) -> Result<DynRerankModel, ConfigError> {
+        self.build(ModelKind::Rerank, &self.factory.rerank, config)
+    }
+
+    fn model_config(&self, kind: ModelKind, model: &str) -> ModelConfig {
+        ModelConfig::new(&self.name, kind, model)
+    }
+
+    fn build<M>(
+        &self,
+        kind: ModelKind,
+        build: &Option<Arc<ModelFn<M>>>,
+        config: &ModelConfig,
+    ) -> Result<M, ConfigError> {
+        let build = build.as_ref().ok_or_else(|| ConfigError::Unsupported {
+            provider: self.factory.id.to_string(),
+            kind,
+        })?;
+        build(&self.client, config)
+    }
+}
+
+/// Object-safe view of a completion model with the raw responses erased.
+trait ErasedCompletion: WasmCompatSend + WasmCompatSync {
+    fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> WasmBoxedFuture<'_, Result<CompletionResponse<Value>, CompletionError>>;
+
+    fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> WasmBoxedFuture<
+        '_,
+        Result<StreamingCompletionResponse<FinalCompletionResponse>, CompletionError>,
+    >;
+}
+
+impl<M> ErasedCompletion for M
+where
+    M: completion::CompletionModel + 'static,
+    M::Response: Serialize,
+{
+    fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> WasmBoxedFuture<'_, Result<CompletionResponse<Value>, CompletionError>> {
+        Box::pin(async move {
+            let response = completion::CompletionModel::completion(self, request).await?;
+            Ok(CompletionResponse {
+                choice: response.choice,
+                usage: response.usage,
+                raw_response: serde_json::to_value(&response.raw_response)?,
+            })
+        })
+    }
+
+    fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> WasmBoxedFuture<
+        '_,
+        Result<StreamingCompletionResponse<FinalCompletionResponse>, CompletionError>,
+    > {
+        Box::pin(async move {
+            let stream = completion::CompletionModel::stream(self, request).await?;
+            Ok(StreamingCompletionResponse::stream(Box::pin(stream.map(
+                |item| {
+                    item.map(|content| {
+                        raw_choice(content, |response: M::StreamingResponse| {
+                            FinalCompletionResponse {
+                                usage: response.token_usage(),
+                            }
+                        })
+                    })
+                },
+            ))))
+        })
+    }
+}
+
+/// Completion model built at runtime by a [`ProviderRegistry`].
+///
+/// Raw responses are kept as JSON; streams end with the token usage only.
+#[derive(Clone)]
+pub struct DynCompletionModel {
+    inner: Arc<dyn ErasedCompletion>,
+}
+
+impl DynCompletionModel {
+    pub fn new<M>(model: M) -> Self
+    where
+        M: completion::CompletionModel + 'static,
+        M::Response: Serialize,
+    {
+        Self {
+            inner: Arc::new(model),
+        }
+    }
+}
+
+impl completion::CompletionModel for DynCompletionModel {
+    type Response = Value;
+    type StreamingResponse = FinalCompletionResponse;
+    /// The model name is fixed when the model is built; `make` clones it and ignores `model`.
+    type Client = Self;
+
+    fn make(client: &Self::Client, _model: impl Into<String>) -> Self {
+        client.clone()
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<Value>, CompletionError> {
+        self.inner.completion(request).await
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<FinalCompletionResponse>, CompletionError> {
+        self.inner.stream(request).await
+    }
+}
+
+/// Object-safe view of an embedding model.
+trait ErasedEmbedding: WasmCompatSend + WasmCompatSync {
+    fn ndims(&self) -> usize;
+
+    fn embed_texts(
+        &self,
+        texts: Vec<String>,
+    ) -> WasmBoxedFuture<'_, Result<Vec<Embedding>, EmbeddingError>>;
+}
+
+impl<M> ErasedEmbedding for M
+where
+    M: embeddings::EmbeddingModel + 'static,
+{
+    fn ndims(&self) -> usize {
+        embeddings::EmbeddingModel::ndims(self)
+    }
+
+    fn embed_texts(
+        &self,
+        texts: Vec<String>,
+    ) -> WasmBoxedFuture<'_, Result<Vec<Embedding>, EmbeddingError>> {
+        Box::pin(embeddings::EmbeddingModel::embed_texts(self, texts))
+    }
+}
+
+/// Embedding model built at runtime by a [`ProviderRegistry`].
+#[derive(Clone)]
+pub struct DynEmbeddingModel {
+    inner: Arc<dyn ErasedEmbedding>,
+}
+
+impl DynEmbeddingModel {
+    pub fn new<M>(model: M) -> Self
+    where
+        M: embeddings::EmbeddingModel + 'static,
+    {
+        Self {
+            inner: Arc::new(model),
+        }
+    }
+}
+
+impl embeddings::EmbeddingModel for DynEmbeddingModel {
+    /// Providers split larger calls into batches themselves.
+    const MAX_DOCUMENTS: usize = 1024;
+
+    /// The model is fixed when it is built; `make` clones it and ignores the arguments.
+    type Client = Self;
+
+    fn make(client: &Self::Client, _model: impl Into<String>, _ndims: Option<usize>) -> Self {
+        client.clone()
+    }
+
+    fn ndims(&self) -> usize {
+        self.inner.ndims()
+    }
+
+    async fn embed_texts(
+        &self,
+        texts: impl IntoIterator<Item = String> + WasmCompatSend,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        self.inner.embed_texts(texts.into_iter().collect()).await
+    }
+}
+
+/// Reranker built at runtime by a [`ProviderRegistry`].
+#[derive(Clone)]
+pub struct DynRerankModel {
+    inner: Arc<dyn RerankerDyn>,
+}
+
+impl DynRerankModel {
+    pub fn new<R: Reranker + 'static>(reranker: R) -> Self {
+        Self {
+            inner: Arc::new(reranker),
+        }
+    }
+}
+
+impl Reranker for DynRerankModel {
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[String],
+        top_n: Option<usize>,
+    ) -> Result<Vec<RerankResult>, RerankError> {
+        self.inner.rerank(query, documents, top_n).await
+    }
+}
@@ -0,0 +1,95 @@
+//! Category: rerank.rs (provider-agnostic reranking: Reranker trait, result and error types)
+
+use rig::http_client;
+use rig::wasm_compat::{WasmBoxedFuture, WasmCompatSend, WasmCompatSync};
+use serde::{Deserialize, Serialize};
+
+/// One reranked document, shared by every rerank provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RerankResult {
+    /// Position of the document in the input slice.
+    pub index: usize,
+    pub relevance_score: f64,
+    /// Document text, only present when the provider echoes documents back.
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RerankError {
+    #[error("validation error: {0}")]
+    ValidationError(String),
+    #[error("http error: {0}")]
+    HttpError(#[from] http_client::Error),
+    #[error("json error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("provider error: {0}")]
+    ProviderError(String),
+    #[error("response error: {0}")]
+    ResponseError(String),
+}
+
+/// A model that orders documents by relevance to a query.
+///
+/// Implemented by `rig_bailian::RerankModel` (DashScope) and `rig_tei::Client` (TEI `/rerank`),
+/// so retrieval code can be written once against `R: Reranker`.
+pub trait Reranker: WasmCompatSend + WasmCompatSync {
+    /// Rerank `documents` against `query`, best match first.
+    /// If `top_n` is provided, at most `top_n` results are returned.
+    fn rerank(
+        &self,
+        query: &str,
+        documents: &[String],
+        top_n: Option<usize>,
+    ) -> impl Future<Output = Result<Vec<RerankResult>, RerankError>> + WasmCompatSend;
+}
+
+/// Object-safe counterpart of [`Reranker`], for choosing the provider at runtime.
+pub trait RerankerDyn: WasmCompatSend + WasmCompatSync {
+    fn rerank<'a>(
+        &'a self,
+        query: &'a str,
+        documents: &'a [String],
+        top_n: Option<usize>,
+    ) -> WasmBoxedFuture<'a, Result<Vec<RerankResult>, RerankError>>;
+}
+
+impl<R> RerankerDyn for R
+where
+    R: Reranker,
+{
+    fn rerank<'a>(
+        &'a self,
+        query: &'a str,
+        documents: &'a [String],
+        top_n: Option<usize>,
+    ) -> WasmBoxedFuture<'a, Result<Vec<RerankResult>, RerankError>> {
+        Box::pin(Reranker::rerank(self, query, documents, top_n))
+    }
+}
+
+impl Reranker for Box<dyn RerankerDyn> {
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[String],
+        top_n: Option<usize>,
+    ) -> Result<Vec<RerankResult>, RerankError> {
+        RerankerDyn::rerank(self.as_ref(), query, documents, top_n).await
+    }
+}
+
+/// Shared input checks applied before any request is sent.
+pub fn validate(query: &str, documents: &[String]) -> Result<(), RerankError> {
+    if query.trim().is_empty() {
+        return Err(RerankError::ValidationError(
+            "Query cannot be empty".to_string(),
+        ));
+    }
+    if documents.is_empty() {
+        return Err(RerankError::ValidationError(
+            "Documents cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
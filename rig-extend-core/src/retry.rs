@@ -0,0 +1,165 @@
+//! Category: retry.rs (retry policy, backoff and Retry-After parsing)
+
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, UNIX_EPOCH};
+
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use rig::http_client::Error;
+
+use super::error::ProviderError;
+use super::rt::unix_now;
+
+/// Status codes retried by [`RetryPolicy::default`].
+pub const DEFAULT_RETRY_STATUSES: &[u16] = &[408, 429, 500, 502, 503, 504];
+
+/// When and how often a failed request is re-sent.
+///
+/// Backoff for attempt `n` is `initial_backoff * multiplier^(n-1)`, capped at `max_backoff`,
+/// with full jitter applied. A `Retry-After` header (seconds or HTTP date) replaces the
+/// computed backoff when `respect_retry_after` is set, still capped at `max_backoff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one; `1` disables retries.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    /// Randomise each delay in `[0, backoff]` to avoid synchronised retries.
+    pub jitter: bool,
+    pub respect_retry_after: bool,
+    /// HTTP status codes that are retried.
+    pub retry_statuses: Vec<u16>,
+    /// Retry connection failures, timeouts and other transport errors.
+    pub retry_transport_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            respect_retry_after: true,
+            retry_statuses: DEFAULT_RETRY_STATUSES.to_vec(),
+            retry_transport_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries (the client default).
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn respect_retry_after(mut self, respect: bool) -> Self {
+        self.respect_retry_after = respect;
+        self
+    }
+
+    pub fn retry_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retry_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    pub fn retry_transport_errors(mut self, retry: bool) -> Self {
+        self.retry_transport_errors = retry;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_attempts > 1
+    }
+
+    /// Whether a failed attempt is worth repeating.
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        match error {
+            Error::InvalidStatusCode(status) | Error::InvalidStatusCodeWithMessage(status, _) => {
+                self.is_retryable_status(*status)
+            }
+            Error::Instance(err) => match err.downcast_ref::<ProviderError>() {
+                Some(ProviderError {
+                    status: Some(status),
+                    ..
+                }) => StatusCode::from_u16(*status).is_ok_and(|s| self.is_retryable_status(s)),
+                Some(_) => false,
+                None => self.retry_transport_errors,
+            },
+            _ => false,
+        }
+    }
+
+    pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status.as_u16())
+    }
+
+    /// Delay before attempt `attempt + 1`, given the server's `Retry-After` (if any).
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if self.respect_retry_after
+            && let Some(retry_after) = retry_after
+        {
+            return retry_after.min(self.max_backoff);
+        }
+
+        // In f64 seconds: the exponential overflows `Duration` after a few dozen attempts, and
+        // a negative or NaN multiplier set in code would make `mul_f64` panic.
+        let exp = self
+            .multiplier
+            .powi(i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX));
+        let secs = (self.initial_backoff.as_secs_f64() * exp).min(self.max_backoff.as_secs_f64());
+        let secs = if self.jitter {
+            secs * random_unit()
+        } else {
+            secs
+        };
+        Duration::try_from_secs_f64(secs).unwrap_or(self.max_backoff)
+    }
+}
+
+/// Parse a `Retry-After` header given as delta-seconds or an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(
+        at.duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .saturating_sub(unix_now()),
+    )
+}
+
+/// Uniform value in `[0, 1)` without pulling in an RNG crate.
+pub(crate) fn random_unit() -> f64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(unix_now().as_nanos());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
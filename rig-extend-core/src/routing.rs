@@ -0,0 +1,483 @@
+//! Category: routing.rs (fallback and weighted routing over several completion models)
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use rig::completion::{
+    self, CompletionError, CompletionRequest, CompletionResponse, GetTokenUsage,
+};
+use rig::http_client;
+use rig::providers::openai;
+use rig::streaming::StreamingCompletionResponse;
+use rig::wasm_compat::{WasmBoxedFuture, WasmCompatSend, WasmCompatSync};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::{Instrument, info_span};
+
+use super::error::{ErrorClass, ProviderErrorExt};
+use super::retry::random_unit;
+use super::rt::{self, Elapsed};
+use super::streaming::raw_choice;
+
+/// Raw responses that can report a content-filter refusal returned with a 200 status.
+pub trait ContentFiltered {
+    fn content_filtered(&self) -> bool {
+        false
+    }
+}
+
+impl ContentFiltered for openai::CompletionResponse {
+    fn content_filtered(&self) -> bool {
+        self.choices
+            .iter()
+            .any(|choice| choice.finish_reason == "content_filter")
+    }
+}
+
+impl ContentFiltered for () {}
+
+/// Order in which backends are tried for each request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Always start with the first backend and fail over in the order they were added.
+    #[default]
+    Ordered,
+    /// Pick backends at random in proportion to their weights (without replacement), so the
+    /// first choice spreads the load and the rest serve as fallbacks. Backends with weight 0
+    /// are skipped, unless every weight is 0; then the backends are tried in order.
+    Weighted,
+}
+
+/// Which failures move a request on to the next backend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverPolicy {
+    /// Provider error classes that trigger failover.
+    pub classes: Vec<ErrorClass>,
+    /// Fail over on connection failures and other errors without a provider response.
+    pub transport_errors: bool,
+}
+
+impl Default for FailoverPolicy {
+    fn default() -> Self {
+        Self {
+            classes: vec![
+                ErrorClass::RateLimit,
+                ErrorClass::Quota,
+                ErrorClass::Server,
+                ErrorClass::ContentFilter,
+            ],
+            transport_errors: true,
+        }
+    }
+}
+
+impl FailoverPolicy {
+    pub fn classes(mut self, classes: impl IntoIterator<Item = ErrorClass>) -> Self {
+        self.classes = classes.into_iter().collect();
+        self
+    }
+
+    pub fn transport_errors(mut self, failover: bool) -> Self {
+        self.transport_errors = failover;
+        self
+    }
+
+    /// Whether `error` should be retried on another backend. Timeouts always are.
+    pub fn should_failover(&self, error: &CompletionError) -> bool {
+        if let Some(class) = error.error_class() {
+            return self.classes.contains(&class);
+        }
+        match error {
+            CompletionError::HttpError(http_client::Error::Instance(err))
+                if err.is::<Elapsed>() =>
+            {
+                true
+            }
+            CompletionError::HttpError(_) => self.transport_errors,
+            _ => false,
+        }
+    }
+}
+
+/// Object-safe view of a completion model with fixed response types.
+trait Backend<R, S>: WasmCompatSend + WasmCompatSync
+where
+    S: Clone + Unpin + GetTokenUsage,
+{
+    fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> WasmBoxedFuture<'_, Result<CompletionResponse<R>, CompletionError>>;
+
+    fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> WasmBoxedFuture<'_, Result<StreamingCompletionResponse<S>, CompletionError>>;
+}
+
+impl<M> Backend<M::Response, M::StreamingResponse> for M
+where
+    M: completion::CompletionModel + 'static,
+{
+    fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> WasmBoxedFuture<'_, Result<CompletionResponse<M::Response>, CompletionError>> {
+        Box::pin(completion::CompletionModel::completion(self, request))
+    }
+
+    fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> WasmBoxedFuture<
+        '_,
+        Result<StreamingCompletionResponse<M::StreamingResponse>, CompletionError>,
+    > {
+        Box::pin(completion::CompletionModel::stream(self, request))
+    }
+}
+
+struct Route<R, S> {
+    name: String,
+    weight: u32,
+    timeout: Option<Duration>,
+    model: Box<dyn Backend<R, S>>,
+}
+
+/// Completion model that spreads requests over several backends and fails over between them.
+///
+/// Backends can be any rig completion models with the same raw response types, such as a
+/// `rig_bailian::CompletionModel` and a `rig_volcengine::CompletionModel`. A request moves on
+/// to the next backend when the [`FailoverPolicy`] says so, when an attempt exceeds its
+/// timeout, or when a 200 response was refused by content filtering; otherwise the error is
+/// returned as is. When every backend fails, the last error is returned.
+///
+/// Streaming requests fail over until the first event arrives. The serving backend is
+/// recorded as `route.backend` on the `chat` / `chat_streaming` span.
+pub struct RoutingModel<R = openai::CompletionResponse, S = openai::StreamingCompletionResponse> {
+    routes: Vec<Arc<Route<R, S>>>,
+    strategy: Strategy,
+    failover: FailoverPolicy,
+    attempt_timeout: Option<Duration>,
+}
+
+impl<R, S> Clone for RoutingModel<R, S> {
+    fn clone(&self) -> Self {
+        Self {
+            routes: self.routes.clone(),
+            strategy: self.strategy,
+            failover: self.failover.clone(),
+            attempt_timeout: self.attempt_timeout,
+        }
+    }
+}
+
+impl<R, S> Default for RoutingModel<R, S> {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            strategy: Strategy::default(),
+            failover: FailoverPolicy::default(),
+            attempt_timeout: None,
+        }
+    }
+}
+
+impl<R, S> fmt::Debug for RoutingModel<R, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutingModel")
+            .field("backends", &self.backends())
+            .field("strategy", &self.strategy)
+            .field("failover", &self.failover)
+            .field("attempt_timeout", &self.attempt_timeout)
+            .finish()
+    }
+}
+
+impl<R, S> RoutingModel<R, S>
+where
+    R: 'static,
+    S: Clone + Unpin + GetTokenUsage + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a backend with weight 1. `name` identifies it in logs and spans.
+    pub fn backend<M>(self, name: impl Into<String>, model: M) -> Self
+    where
+        M: completion::CompletionModel<Response = R, StreamingResponse = S> + 'static,
+    {
+        self.weighted_backend(name, model, 1)
+    }
+
+    /// Add a backend with a relative weight, used by [`Strategy::Weighted`].
+    pub fn weighted_backend<M>(mut self, name: impl Into<String>, model: M, weight: u32) -> Self
+    where
+        M: completion::CompletionModel<Response = R, StreamingResponse = S> + 'static,
+    {
+        self.routes.push(Arc::new(Route {
+            name: name.into(),
+            weight,
+            timeout: None,
+            model: Box::new(model),
+        }));
+        self
+    }
+
+    /// Time limit for attempts on the most recently added backend, overriding
+    /// [`RoutingModel::attempt_timeout`]. Has no effect once the model has been cloned.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        if let Some(route) = self.routes.last_mut().and_then(Arc::get_mut) {
+            route.timeout = Some(timeout);
+        }
+        self
+    }
+}
+
+impl<R, S> RoutingModel<R, S> {
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn failover(mut self, policy: FailoverPolicy) -> Self {
+        self.failover = policy;
+        self
+    }
+
+    /// Time limit for each attempt; for streaming, the time until the first event.
+    pub fn attempt_timeout(mut self, timeout: Duration) -> Self {
+        self.attempt_timeout = Some(timeout);
+        self
+    }
+
+    /// Backend names in the order they were added.
+    pub fn backends(&self) -> Vec<&str> {
+        self.routes.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    pub fn failover_policy(&self) -> &FailoverPolicy {
+        &self.failover
+    }
+
+    /// Backends to try for one request.
+    fn plan(&self) -> Vec<Arc<Route<R, S>>> {
+        match self.strategy {
+            Strategy::Ordered => self.routes.clone(),
+            Strategy::Weighted => {
+                let mut pool: Vec<_> = self.routes.iter().filter(|r| r.weight > 0).collect();
+                if pool.is_empty() {
+                    return self.routes.clone();
+                }
+                let mut plan = Vec::with_capacity(self.routes.len());
+                while !pool.is_empty() {
+                    let total: u64 = pool.iter().map(|r| r.weight as u64).sum();
+                    let mut pick = (random_unit() * total as f64) as u64;
+                    let idx = pool
+                        .iter()
+                        .position(|r| {
+                            let hit = pick < r.weight as u64;
+                            pick = pick.saturating_sub(r.weight as u64);
+                            hit
+                        })
+                        .unwrap_or(pool.len() - 1);
+                    plan.push(pool.remove(idx).clone());
+                }
+                plan
+            }
+        }
+    }
+
+    fn timeout_for(&self, route: &Route<R, S>) -> Option<Duration> {
+        route.timeout.or(self.attempt_timeout)
+    }
+}
+
+async fn timed<T>(
+    timeout: Option<Duration>,
+    fut: impl Future<Output = Result<T, CompletionError>>,
+) -> Result<T, CompletionError> {
+    match timeout {
+        Some(limit) => rt::timeout(limit, fut).await.unwrap_or_else(|elapsed| {
+            Err(CompletionError::HttpError(http_client::Error::Instance(
+                Box::new(elapsed),
+            )))
+        }),
+        None => fut.await,
+    }
+}
+
+fn no_backends() -> CompletionError {
+    CompletionError::ProviderError("routing model has no backends".to_string())
+}
+
+impl<R, S> completion::CompletionModel for RoutingModel<R, S>
+where
+    R: ContentFiltered + WasmCompatSend + WasmCompatSync + Serialize + DeserializeOwned + 'static,
+    S: Clone
+        + Unpin
+        + WasmCompatSend
+        + WasmCompatSync
+        + Serialize
+        + DeserializeOwned
+        + GetTokenUsage
+        + 'static,
+{
+    type Response = R;
+    type StreamingResponse = S;
+    /// Backends carry their own model names; `make` clones the router and ignores `model`.
+    type Client = Self;
+
+    fn make(client: &Self::Client, _model: impl Into<String>) -> Self {
+        client.clone()
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<R>, CompletionError> {
+        let span = info_span!(
+            target: "rig::completions",
+            "chat",
+            gen_ai.operation.name = "chat",
+            gen_ai.provider.name = "routing",
+            gen_ai.request.model = tracing::field::Empty,
+            gen_ai.response.id = tracing::field::Empty,
+            gen_ai.response.model = tracing::field::Empty,
+            gen_ai.usage.output_tokens = tracing::field::Empty,
+            gen_ai.usage.input_tokens = tracing::field::Empty,
+            gen_ai.output.messages = tracing::field::Empty,
+            route.backend = tracing::field::Empty,
+            route.attempts = tracing::field::Empty,
+        );
+
+        async move {
+            let plan = self.plan();
+            let mut last_error = None;
+            for (attempt, route) in plan.iter().enumerate() {
+                let span = tracing::Span::current();
+                span.record("route.attempts", attempt + 1);
+
+                let result = timed(
+                    self.timeout_for(route),
+                    route.model.completion(request.clone()),
+                )
+                .await;
+                let error = match result {
+                    Ok(response)
+                        if response.raw_response.content_filtered()
+                            && self.failover.classes.contains(&ErrorClass::ContentFilter)
+                            && attempt + 1 < plan.len() =>
+                    {
+                        CompletionError::ResponseError(
+                            "response refused by content filter".to_string(),
+                        )
+                    }
+                    Ok(response) => {
+                        span.record("route.backend", route.name.as_str());
+                        return Ok(response);
+                    }
+                    Err(err) if self.failover.should_failover(&err) => err,
+                    Err(err) => {
+                        span.record("route.backend", route.name.as_str());
+                        return Err(err);
+                    }
+                };
+                if attempt + 1 < plan.len() {
+                    tracing::warn!(
+                        target: "rig::routing",
+                        backend = route.name,
+                        attempt = attempt + 1,
+                        "backend failed, trying the next one: {error}"
+                    );
+                } else {
+                    tracing::warn!(
+                        target: "rig::routing",
+                        backend = route.name,
+                        attempt = attempt + 1,
+                        "all backends failed, last error: {error}"
+                    );
+                }
+                last_error = Some(error);
+            }
+            Err(last_error.unwrap_or_else(no_backends))
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<S>, CompletionError> {
+        let span = info_span!(
+            target: "rig::completions",
+            "chat_streaming",
+            gen_ai.operation.name = "chat_streaming",
+            gen_ai.provider.name = "routing",
+            gen_ai.request.model = tracing::field::Empty,
+            gen_ai.response.id = tracing::field::Empty,
+            gen_ai.response.model = tracing::field::Empty,
+            gen_ai.usage.output_tokens = tracing::field::Empty,
+            gen_ai.usage.input_tokens = tracing::field::Empty,
+            gen_ai.output.messages = tracing::field::Empty,
+            route.backend = tracing::field::Empty,
+            route.attempts = tracing::field::Empty,
+        );
+
+        async move {
+            let plan = self.plan();
+            let mut last_error = None;
+            for (attempt, route) in plan.iter().enumerate() {
+                let span = tracing::Span::current();
+                span.record("route.attempts", attempt + 1);
+
+                // Wait for the first event so failures before any output can still fail over.
+                let opened = timed(self.timeout_for(route), async {
+                    let mut stream = route.model.stream(request.clone()).await?;
+                    let first = stream.next().await;
+                    Ok((stream, first))
+                })
+                .await;
+                let error = match opened {
+                    Ok((_, Some(Err(err)))) | Err(err) if self.failover.should_failover(&err) => {
+                        err
+                    }
+                    Ok((_, Some(Err(err)))) | Err(err) => {
+                        span.record("route.backend", route.name.as_str());
+                        return Err(err);
+                    }
+                    Ok((stream, first)) => {
+                        span.record("route.backend", route.name.as_str());
+                        let rest = futures::stream::iter(first).chain(stream);
+                        return Ok(StreamingCompletionResponse::stream(Box::pin(rest.map(
+                            |item| item.map(|content| raw_choice(content, |response| response)),
+                        ))));
+                    }
+                };
+                if attempt + 1 < plan.len() {
+                    tracing::warn!(
+                        target: "rig::routing",
+                        backend = route.name,
+                        attempt = attempt + 1,
+                        "backend failed before streaming, trying the next one: {error}"
+                    );
+                } else {
+                    tracing::warn!(
+                        target: "rig::routing",
+                        backend = route.name,
+                        attempt = attempt + 1,
+                        "all backends failed before streaming, last error: {error}"
+                    );
+                }
+                last_error = Some(error);
+            }
+            Err(last_error.unwrap_or_else(no_backends))
+        }
+        .instrument(span)
+        .await
+    }
+}
@@ -0,0 +1,24 @@
+//! Category: rt.rs (timers and clocks that work natively and on wasm32)
+//!
+//! Natively these are tokio's, so `tokio::time::pause` drives them in tests. On wasm
+//! (browsers, Cloudflare Workers) std's clocks panic and tokio has no timer driver, so they
+//! come from `wasmtimer` and `web-time`, which use the JS event loop and `performance.now()`.
+
+#[cfg(not(target_family = "wasm"))]
+pub use std::time::SystemTime;
+#[cfg(not(target_family = "wasm"))]
+pub use tokio::time::{Instant, error::Elapsed, sleep, timeout};
+
+#[cfg(target_family = "wasm")]
+pub use wasmtimer::std::Instant;
+#[cfg(target_family = "wasm")]
+pub use wasmtimer::tokio::{error::Elapsed, sleep, timeout};
+#[cfg(target_family = "wasm")]
+pub use web_time::SystemTime;
+
+/// Time since the Unix epoch.
+pub fn unix_now() -> std::time::Duration {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+}
@@ -0,0 +1,390 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use rig::completion::{CompletionError, CompletionRequest, GetTokenUsage};
+use rig::http_client::sse::BoxedStream;
+use rig::http_client::{self, HttpClientExt, LazyBody, Request, Response};
+use rig::providers::openai::send_compatible_streaming_request;
+use rig::streaming::{RawStreamingChoice, StreamedAssistantContent, StreamingCompletionResponse};
+use rig::wasm_compat::WasmCompatSend;
+use serde_json::{Value, json};
+use tracing::info_span;
+
+use super::completion::CompletionModel;
+use super::error::ProviderError;
+use super::json_utils::merge;
+use super::profile::Profile;
+use super::usage::{Operation, TokenUsage};
+
+pub(crate) async fn stream_completion<P, T>(
+    model: &CompletionModel<P, T>,
+    request: CompletionRequest,
+) -> Result<
+    StreamingCompletionResponse<
+        <CompletionModel<P, T> as rig::completion::CompletionModel>::StreamingResponse,
+    >,
+    CompletionError,
+>
+where
+    P: Profile,
+    T: rig::http_client::HttpClientExt + Clone + Default + Send + 'static,
+{
+    let preamble = request.preamble.clone();
+    let mut request = model.create_completion_request(request)?;
+
+    // OpenAI-compatible streaming flags (per-provider quirks live on the profile)
+    request = merge(request, P::streaming_params());
+
+    let req = model
+        .client
+        .post("/chat/completions")?
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_vec(&request)?)
+        .map_err(|e| CompletionError::HttpError(e.into()))?;
+
+    let span = if tracing::Span::current().is_disabled() {
+        info_span!(
+            target: "rig::completions",
+            "chat_streaming",
+            gen_ai.operation.name = "chat_streaming",
+            gen_ai.provider.name = P::NAME,
+            gen_ai.request.model = model.model,
+            gen_ai.system_instructions = preamble,
+            gen_ai.response.id = tracing::field::Empty,
+            gen_ai.response.model = tracing::field::Empty,
+            gen_ai.usage.output_tokens = tracing::field::Empty,
+            gen_ai.usage.input_tokens = tracing::field::Empty,
+            gen_ai.input.messages = serde_json::to_string(&request.get("messages").unwrap_or(&json!([]))).unwrap(),
+            gen_ai.output.messages = tracing::field::Empty,
+        )
+    } else {
+        tracing::Span::current()
+    };
+
+    tracing::Instrument::instrument(
+        async move {
+            // Open the stream here so failures before the first event come back as typed
+            // errors; rig's SSE reader would turn them into a string inside the stream.
+            let (parts, body) = req.into_parts();
+            let response = model
+                .client
+                .http_client
+                .send_streaming(Request::from_parts(parts.clone(), body.clone()))
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let headers = response.headers().clone();
+                let mut stream = response.into_body();
+                let mut text = Vec::new();
+                while let Some(chunk) = stream.next().await {
+                    text.extend_from_slice(&chunk?);
+                }
+                let text = String::from_utf8_lossy(&text);
+                return Err(CompletionError::HttpError(
+                    ProviderError::from_response(status, &headers, &text).into(),
+                ));
+            }
+
+            let record: Option<RecordUsage> = model.client.usage_ledger().is_some().then(|| {
+                let (client, name) = (model.client.clone(), model.model.clone());
+                Box::new(move |usage: Option<TokenUsage>| {
+                    client.record_usage(&name, Operation::ChatStreaming, usage.unwrap_or_default())
+                }) as RecordUsage
+            });
+            let reasoning = ReasoningQueue::default();
+            let response = response.map(|stream| -> BoxedStream {
+                Box::pin(UsageTap::new(stream, record, reasoning.clone()))
+            });
+
+            let client = Opened {
+                inner: model.client.http_client.clone(),
+                response: Arc::new(Mutex::new(Some(response))),
+            };
+            let inner =
+                send_compatible_streaming_request(client, Request::from_parts(parts, body)).await?;
+            Ok(StreamingCompletionResponse::stream(Box::pin(
+                WithReasoning {
+                    inner,
+                    reasoning,
+                    pending: None,
+                    done: false,
+                },
+            )))
+        },
+        span,
+    )
+    .await
+}
+
+/// Hands an already opened response to the first `send_streaming` call; everything else
+/// (including reconnects) goes to the wrapped client.
+#[derive(Clone)]
+struct Opened<T> {
+    inner: T,
+    response: Arc<Mutex<Option<http_client::StreamingResponse>>>,
+}
+
+impl<T> HttpClientExt for Opened<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    fn send<B, U>(
+        &self,
+        req: Request<B>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        B: Into<Bytes>,
+        B: WasmCompatSend,
+        U: From<Bytes>,
+        U: WasmCompatSend + 'static,
+    {
+        self.inner.send(req)
+    }
+
+    fn send_multipart<U>(
+        &self,
+        req: Request<reqwest::multipart::Form>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        U: From<Bytes>,
+        U: WasmCompatSend + 'static,
+    {
+        self.inner.send_multipart(req)
+    }
+
+    fn send_streaming<B>(
+        &self,
+        req: Request<B>,
+    ) -> impl Future<Output = http_client::Result<http_client::StreamingResponse>> + WasmCompatSend
+    where
+        B: Into<Bytes>,
+    {
+        let opened = self
+            .response
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        let (parts, body) = req.into_parts();
+        let req = Request::from_parts(parts, body.into());
+        let inner = self.inner.clone();
+        async move {
+            match opened {
+                Some(response) => Ok(response),
+                None => inner.send_streaming(req).await,
+            }
+        }
+    }
+}
+
+type RecordUsage = Box<dyn FnOnce(Option<TokenUsage>) + Send>;
+
+/// `reasoning_content` deltas seen by the tap, waiting to be merged into rig's stream.
+type ReasoningQueue = Arc<Mutex<VecDeque<String>>>;
+
+/// Watches the SSE body for the usage chunk sent with `include_usage` and for reasoning.
+///
+/// The usage chunk has no choices, and rig skips choice-less chunks, which loses the usage.
+/// The tap gives it an empty choice so rig keeps it, and reports the usage to the ledger. A
+/// stream that ends without usage is still counted as one request. rig also ignores
+/// `reasoning_content` deltas; the tap queues them for [`WithReasoning`].
+struct UsageTap {
+    inner: BoxedStream,
+    /// Bytes after the last complete line.
+    partial: Vec<u8>,
+    record: Option<RecordUsage>,
+    reasoning: ReasoningQueue,
+    finished: bool,
+}
+
+impl UsageTap {
+    fn new(inner: BoxedStream, record: Option<RecordUsage>, reasoning: ReasoningQueue) -> Self {
+        Self {
+            inner,
+            partial: Vec::new(),
+            record,
+            reasoning,
+            finished: false,
+        }
+    }
+
+    /// Pass `line` through, queueing its reasoning and rewriting it if it is the usage chunk.
+    fn line(&mut self, line: &[u8], out: &mut Vec<u8>) {
+        let text = String::from_utf8_lossy(line);
+        let Some(data) = text.trim_end().strip_prefix("data:") else {
+            out.extend_from_slice(line);
+            return;
+        };
+        if !data.contains("\"usage\"") && !data.contains("\"reasoning_content\"") {
+            out.extend_from_slice(line);
+            return;
+        }
+        let Ok(mut chunk) = serde_json::from_str::<Value>(data.trim()) else {
+            out.extend_from_slice(line);
+            return;
+        };
+        if let Some(reasoning) = chunk["choices"][0]["delta"]["reasoning_content"]
+            .as_str()
+            .filter(|reasoning| !reasoning.is_empty())
+        {
+            lock(&self.reasoning).push_back(reasoning.to_string());
+        }
+        if !chunk["usage"].is_object() {
+            out.extend_from_slice(line);
+            return;
+        }
+
+        if let Some(record) = self.record.take() {
+            record(Some(TokenUsage::from_openai(&chunk["usage"])));
+        }
+        if chunk["choices"].as_array().is_some_and(Vec::is_empty) {
+            chunk["choices"] = json!([{ "index": 0, "delta": {}, "finish_reason": null }]);
+        }
+        out.extend_from_slice(b"data: ");
+        out.extend_from_slice(chunk.to_string().as_bytes());
+        out.extend_from_slice(&line[text.trim_end().len()..]);
+    }
+}
+
+impl Stream for UsageTap {
+    type Item = http_client::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.finished {
+                return Poll::Ready(None);
+            }
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(Some(Ok(bytes))) => {
+                    this.partial.extend_from_slice(&bytes);
+                    let Some(end) = this.partial.iter().rposition(|b| *b == b'\n') else {
+                        continue;
+                    };
+                    let complete: Vec<u8> = this.partial.drain(..=end).collect();
+                    let mut out = Vec::with_capacity(complete.len());
+                    for line in complete.split_inclusive(|b| *b == b'\n') {
+                        this.line(line, &mut out);
+                    }
+                    return Poll::Ready(Some(Ok(Bytes::from(out))));
+                }
+                Poll::Ready(None) => {
+                    this.finished = true;
+                    if this.partial.is_empty() {
+                        return Poll::Ready(None);
+                    }
+                    let rest = std::mem::take(&mut this.partial);
+                    let mut out = Vec::with_capacity(rest.len());
+                    this.line(&rest, &mut out);
+                    return Poll::Ready(Some(Ok(Bytes::from(out))));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for UsageTap {
+    fn drop(&mut self) {
+        if let Some(record) = self.record.take() {
+            record(None);
+        }
+    }
+}
+
+fn lock(queue: &ReasoningQueue) -> std::sync::MutexGuard<'_, VecDeque<String>> {
+    queue.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// rig's stream with the tap's reasoning deltas put back in.
+///
+/// The tap runs while rig reads the body, so deltas queued by the time rig yields an item (or
+/// waits for more data) came before it. Reasoning is yielded first, so thinking shows up
+/// while the model has not started its answer yet.
+struct WithReasoning<R: Clone + Unpin + GetTokenUsage> {
+    inner: StreamingCompletionResponse<R>,
+    reasoning: ReasoningQueue,
+    pending: Option<Result<RawStreamingChoice<R>, CompletionError>>,
+    done: bool,
+}
+
+impl<R: Clone + Unpin + GetTokenUsage> WithReasoning<R> {
+    fn next_reasoning(&self) -> Option<Result<RawStreamingChoice<R>, CompletionError>> {
+        let reasoning = lock(&self.reasoning).pop_front()?;
+        Some(Ok(RawStreamingChoice::Reasoning {
+            id: None,
+            reasoning,
+            signature: None,
+        }))
+    }
+}
+
+impl<R: Clone + Unpin + GetTokenUsage> Stream for WithReasoning<R> {
+    type Item = Result<RawStreamingChoice<R>, CompletionError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(reasoning) = this.next_reasoning() {
+            return Poll::Ready(Some(reasoning));
+        }
+        if let Some(item) = this.pending.take() {
+            return Poll::Ready(Some(item));
+        }
+        if this.done {
+            return Poll::Ready(None);
+        }
+        match this.inner.poll_next_unpin(cx) {
+            Poll::Pending => match this.next_reasoning() {
+                Some(reasoning) => Poll::Ready(Some(reasoning)),
+                None => Poll::Pending,
+            },
+            Poll::Ready(None) => {
+                this.done = true;
+                Poll::Ready(this.next_reasoning())
+            }
+            Poll::Ready(Some(item)) => {
+                let item = item.map(|content| raw_choice(content, |response| response));
+                match this.next_reasoning() {
+                    Some(reasoning) => {
+                        this.pending = Some(item);
+                        Poll::Ready(Some(reasoning))
+                    }
+                    None => Poll::Ready(Some(item)),
+                }
+            }
+        }
+    }
+}
+
+/// Turn an already consumed stream item back into the raw form rig expects, converting the
+/// final response with `finish`.
+pub(crate) fn raw_choice<S: Clone, T: Clone>(
+    content: StreamedAssistantContent<S>,
+    finish: impl FnOnce(S) -> T,
+) -> RawStreamingChoice<T> {
+    match content {
+        StreamedAssistantContent::Text(text) => RawStreamingChoice::Message(text.text),
+        StreamedAssistantContent::ToolCall(call) => RawStreamingChoice::ToolCall {
+            id: call.id,
+            call_id: call.call_id,
+            name: call.function.name,
+            arguments: call.function.arguments,
+        },
+        StreamedAssistantContent::ToolCallDelta { id, delta } => {
+            RawStreamingChoice::ToolCallDelta { id, delta }
+        }
+        StreamedAssistantContent::Reasoning(reasoning) => RawStreamingChoice::Reasoning {
+            id: reasoning.id,
+            reasoning: reasoning.reasoning.concat(),
+            signature: reasoning.signature,
+        },
+        StreamedAssistantContent::Final(response) => {
+            RawStreamingChoice::FinalResponse(finish(response))
+        }
+    }
+}
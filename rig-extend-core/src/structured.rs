@@ -0,0 +1,286 @@
+//! Category: structured.rs (JSON response_format and typed structured-output extraction)
+
+use std::marker::PhantomData;
+
+use rig::completion::{self, AssistantContent, CompletionError, Message};
+use rig::http_client;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::completion::CompletionModel;
+use super::error::{ProviderError, ProviderErrorExt};
+use super::profile::Profile;
+
+/// OpenAI-compatible `response_format`, accepted by Qwen (DashScope) and Doubao (Ark).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    /// Any JSON object. Qwen rejects it unless the prompt mentions JSON, and the shape has to
+    /// be described in the prompt.
+    JsonObject,
+    /// JSON matching a schema.
+    JsonSchema {
+        json_schema: JsonSchemaFormat,
+    },
+}
+
+/// The `json_schema` member of [`ResponseFormat::JsonSchema`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    /// `[A-Za-z0-9_-]`, at most 64 characters.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub schema: Value,
+    /// Ask the provider to enforce the schema exactly rather than as guidance.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl ResponseFormat {
+    /// `json_schema` format for `O`, named after its schema name.
+    pub fn json_schema<O: JsonSchema>() -> Self {
+        let mut schema = serde_json::to_value(schemars::schema_for!(O)).unwrap_or_default();
+        if let Some(schema) = schema.as_object_mut() {
+            schema.remove("$schema");
+        }
+        Self::schema(O::schema_name(), schema)
+    }
+
+    /// `json_schema` format from a hand-written schema.
+    pub fn schema(name: impl AsRef<str>, schema: Value) -> Self {
+        let name: String = name
+            .as_ref()
+            .chars()
+            .map(|c| match c {
+                'A'..='Z' | 'a'..='z' | '0'..='9' | '_' | '-' => c,
+                _ => '_',
+            })
+            .take(64)
+            .collect();
+        Self::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name,
+                description: None,
+                schema,
+                strict: false,
+            },
+        }
+    }
+
+    /// Sets `strict` on a `json_schema` format; other formats are returned unchanged.
+    pub fn strict(mut self, strict: bool) -> Self {
+        if let Self::JsonSchema { json_schema } = &mut self {
+            json_schema.strict = strict;
+        }
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        if let Self::JsonSchema { json_schema } = &mut self {
+            json_schema.description = Some(description.into());
+        }
+        self
+    }
+}
+
+/// Typed structured output: requests JSON matching `O`'s schema, parses the reply into `O`
+/// and, when it does not parse, shows the model its reply with the error and asks again.
+///
+/// ```ignore
+/// #[derive(Deserialize, JsonSchema)]
+/// struct Person { name: String, age: u32 }
+///
+/// let person: Person = qwen
+///     .structured::<Person>()
+///     .preamble("Extract the person mentioned in the text.")
+///     .extract("Alice turned 30 last week.")
+///     .await?;
+/// ```
+pub struct StructuredExtractor<P, T, O> {
+    model: CompletionModel<P, T>,
+    preamble: Option<String>,
+    retries: usize,
+    _output: PhantomData<fn() -> O>,
+}
+
+impl<P, T, O> Clone for StructuredExtractor<P, T, O>
+where
+    CompletionModel<P, T>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            model: self.model.clone(),
+            preamble: self.preamble.clone(),
+            retries: self.retries,
+            _output: PhantomData,
+        }
+    }
+}
+
+impl<P, T> CompletionModel<P, T> {
+    /// Extractor for `O` over a copy of this model, using a `json_schema` response format.
+    pub fn structured<O: JsonSchema>(&self) -> StructuredExtractor<P, T, O>
+    where
+        Self: Clone,
+    {
+        StructuredExtractor::new(self.clone())
+    }
+}
+
+impl<P, T, O: JsonSchema> StructuredExtractor<P, T, O> {
+    /// Repair attempts after the first reply when it does not parse.
+    pub const DEFAULT_RETRIES: usize = 2;
+
+    pub fn new(model: CompletionModel<P, T>) -> Self {
+        Self {
+            model: model.with_response_format(ResponseFormat::json_schema::<O>()),
+            preamble: None,
+            retries: Self::DEFAULT_RETRIES,
+            _output: PhantomData,
+        }
+    }
+
+    /// System prompt; an instruction to answer with JSON is appended to it.
+    pub fn preamble(mut self, preamble: impl Into<String>) -> Self {
+        self.preamble = Some(preamble.into());
+        self
+    }
+
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Ask the provider to enforce the schema exactly.
+    pub fn strict(mut self, strict: bool) -> Self {
+        let format = self
+            .model
+            .response_format()
+            .cloned()
+            .map(|f| f.strict(strict));
+        self.model.set_response_format(format);
+        self
+    }
+
+    /// Use `json_object` with the schema in the system prompt, for models that do not
+    /// support `json_schema`.
+    pub fn json_object(mut self) -> Self {
+        self.model
+            .set_response_format(Some(ResponseFormat::JsonObject));
+        self
+    }
+
+    pub fn model(&self) -> &CompletionModel<P, T> {
+        &self.model
+    }
+
+    fn instruction(&self) -> String {
+        let name = O::schema_name();
+        let instruction = match self.model.response_format() {
+            Some(ResponseFormat::JsonSchema { .. }) => {
+                format!("Reply with a single JSON object matching the `{name}` schema.")
+            }
+            _ => {
+                let schema = serde_json::to_string(&schemars::schema_for!(O)).unwrap_or_default();
+                format!(
+                    "Reply with a single JSON object matching this JSON Schema, \
+                     and nothing else:\n{schema}"
+                )
+            }
+        };
+        match &self.preamble {
+            Some(preamble) => format!("{preamble}\n\n{instruction}"),
+            None => instruction,
+        }
+    }
+}
+
+impl<P, T, O> StructuredExtractor<P, T, O>
+where
+    P: Profile,
+    T: http_client::HttpClientExt + Clone + Default + Send + 'static,
+    O: JsonSchema + DeserializeOwned,
+{
+    /// Sends `input` and parses the reply into `O`, repairing up to `retries` times.
+    pub async fn extract(&self, input: impl Into<Message>) -> Result<O, StructuredOutputError> {
+        let preamble = self.instruction();
+        let mut history = vec![];
+        let mut prompt = input.into();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let response =
+                completion::CompletionModel::completion_request(&self.model, prompt.clone())
+                    .preamble(preamble.clone())
+                    .messages(history.clone())
+                    .send()
+                    .await?;
+            let reply = response
+                .choice
+                .iter()
+                .filter_map(|content| match content {
+                    AssistantContent::Text(text) => Some(text.text.as_str()),
+                    _ => None,
+                })
+                .collect::<String>();
+
+            let error = match serde_json::from_str::<O>(strip_code_fence(&reply)) {
+                Ok(output) => return Ok(output),
+                Err(error) => error,
+            };
+            if attempts > self.retries {
+                return Err(StructuredOutputError::Invalid {
+                    schema: O::schema_name().into_owned(),
+                    attempts,
+                    error,
+                    reply,
+                });
+            }
+            tracing::debug!(attempts, %error, "structured output did not parse, asking for a repair");
+            history.push(prompt);
+            history.push(Message::assistant(reply));
+            prompt = Message::user(format!(
+                "That reply is not valid: {error}. Reply again with only the corrected JSON object."
+            ));
+        }
+    }
+}
+
+/// Models often wrap JSON in a Markdown code fence even when asked not to.
+fn strip_code_fence(reply: &str) -> &str {
+    let reply = reply.trim();
+    let Some(fenced) = reply.strip_prefix("```") else {
+        return reply;
+    };
+    let body = fenced.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+/// Failure of [`StructuredExtractor::extract`].
+#[derive(Debug, thiserror::Error)]
+pub enum StructuredOutputError {
+    #[error(transparent)]
+    Completion(#[from] CompletionError),
+    /// The last reply still did not parse into the target type.
+    #[error("reply does not match `{schema}` after {attempts} attempt(s): {error}")]
+    Invalid {
+        schema: String,
+        attempts: usize,
+        #[source]
+        error: serde_json::Error,
+        reply: String,
+    },
+}
+
+impl ProviderErrorExt for StructuredOutputError {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            StructuredOutputError::Completion(err) => err.provider_error(),
+            StructuredOutputError::Invalid { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,437 @@
+//! Category: transport.rs (HTTP client wrapper applying retries, rate limits and circuit breaking)
+
+#[cfg(feature = "reqwest")]
+use std::any::Any;
+#[cfg(feature = "reqwest")]
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::StreamExt;
+#[cfg(feature = "reqwest")]
+use rig::http_client::Error;
+use rig::http_client::sse::BoxedStream;
+use rig::http_client::{self, HttpClientExt, LazyBody, Request, Response};
+use rig::wasm_compat::WasmCompatSend;
+#[cfg(feature = "reqwest")]
+use rig::wasm_compat::WasmCompatSendStream;
+
+use super::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerPolicy, CircuitPermit, Probe, endpoint_key,
+};
+use super::error::ProviderError;
+use super::rate_limit::{RateLimiter, RateLimits, RatePermit};
+use super::retry::{RetryPolicy, retry_after};
+use super::rt::sleep;
+
+/// HTTP client wrapper used by every provider client.
+///
+/// Applies the client's [`RetryPolicy`] and, when configured, its [`RateLimits`] and
+/// [`CircuitBreaker`], so completion, embedding, rerank and predict calls share the same
+/// behaviour. Clones share the rate limiter and circuits, which is how all models built from
+/// one client draw from one budget and see the same endpoint health.
+///
+/// Streaming requests are only retried until a response is received; once the body starts
+/// flowing, errors are surfaced to the caller.
+#[derive(Debug, Clone, Default)]
+pub struct Transport<T> {
+    inner: T,
+    policy: Arc<RetryPolicy>,
+    limiter: Option<Arc<RateLimiter>>,
+    breaker: Option<Arc<CircuitBreaker>>,
+}
+
+impl<T> Transport<T> {
+    pub fn new(inner: T, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy: Arc::new(policy),
+            limiter: None,
+            breaker: None,
+        }
+    }
+
+    /// Enforce `limits` on every request; disabled limits leave the transport unthrottled.
+    pub fn with_rate_limits(mut self, limits: RateLimits) -> Self {
+        self.limiter = limits
+            .is_enabled()
+            .then(|| Arc::new(RateLimiter::new(limits)));
+        self
+    }
+
+    /// Stop sending to endpoints that keep failing; see [`CircuitBreakerPolicy`].
+    pub fn with_circuit_breaker(mut self, policy: CircuitBreakerPolicy) -> Self {
+        self.breaker = Some(Arc::new(CircuitBreaker::new(policy)));
+        self
+    }
+
+    /// Test half-open circuits with `probe`; see [`CircuitBreaker::set_probe`]. Without a
+    /// circuit breaker this does nothing.
+    pub fn with_probe(self, probe: Probe) -> Self {
+        if let Some(breaker) = &self.breaker {
+            breaker.set_probe(probe);
+        }
+        self
+    }
+
+    /// The same HTTP client without retries, rate limits or circuit breaker, e.g. for a
+    /// [`verify_probe`](crate::circuit_breaker::verify_probe).
+    pub fn unguarded(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(self.inner.clone(), RetryPolicy::none())
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.limiter.as_deref()
+    }
+
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.breaker.as_deref()
+    }
+}
+
+impl<T> Transport<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    async fn run<R, F, Fut>(policy: &RetryPolicy, mut attempt_fn: F) -> http_client::Result<R>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = (http_client::Result<R>, Option<Duration>)>,
+    {
+        let mut attempt = 1;
+        loop {
+            let (result, retry_after) = attempt_fn().await;
+            match result {
+                Err(err) if attempt < policy.max_attempts && policy.is_retryable(&err) => {
+                    let delay = policy.delay(attempt, retry_after);
+                    tracing::warn!(
+                        target: "rig::retry",
+                        attempt,
+                        max_attempts = policy.max_attempts,
+                        delay_ms = delay.as_millis() as u64,
+                        "retrying request after error: {err}"
+                    );
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result.map_err(ProviderError::lift),
+            }
+        }
+    }
+}
+
+async fn acquire(limiter: Option<&RateLimiter>, tokens: u32) -> Option<RatePermit> {
+    match limiter {
+        Some(limiter) => Some(limiter.acquire(tokens).await),
+        None => None,
+    }
+}
+
+/// Rejects the request up front while its endpoint's circuit is open.
+async fn admit(
+    breaker: Option<&Arc<CircuitBreaker>>,
+    endpoint: &str,
+) -> Result<Option<CircuitPermit>, ProviderError> {
+    match breaker {
+        Some(breaker) => breaker.admit(endpoint).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+impl<T> HttpClientExt for Transport<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    fn send<B, U>(
+        &self,
+        req: Request<B>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        B: Into<Bytes>,
+        B: WasmCompatSend,
+        U: From<Bytes>,
+        U: WasmCompatSend + 'static,
+    {
+        let (parts, body) = req.into_parts();
+        let body: Bytes = body.into();
+        let inner = self.inner.clone();
+        let policy = self.policy.clone();
+        let limiter = self.limiter.clone();
+        let breaker = self.breaker.clone();
+        let endpoint = endpoint_key(&parts.uri);
+        let tokens = limiter.as_ref().map_or(0, |l| l.cost(&body));
+
+        async move {
+            Self::run(&policy, || {
+                let req = Request::from_parts(parts.clone(), body.clone());
+                let (inner, limiter, breaker) = (&inner, limiter.as_deref(), breaker.as_ref());
+                let endpoint = &endpoint;
+                async move {
+                    let circuit = match admit(breaker, endpoint).await {
+                        Ok(circuit) => circuit,
+                        Err(err) => return (Err(err.into()), None),
+                    };
+                    let permit = acquire(limiter, tokens).await;
+                    let (result, retry_after) = send_once::<T, U>(inner, req).await;
+                    if let Some(circuit) = circuit {
+                        circuit.finish_with(&result);
+                    }
+                    // Keep the in-flight slot until the body has been read.
+                    let result = result.map(|response| {
+                        response.map(|body| -> LazyBody<U> {
+                            Box::pin(async move {
+                                let _permit = permit;
+                                body.await
+                            })
+                        })
+                    });
+                    (result, retry_after)
+                }
+            })
+            .await
+        }
+    }
+
+    fn send_multipart<U>(
+        &self,
+        req: Request<reqwest::multipart::Form>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        U: From<Bytes>,
+        U: WasmCompatSend + 'static,
+    {
+        // Multipart bodies cannot be replayed, so they are sent once.
+        let inner = self.inner.clone();
+        let limiter = self.limiter.clone();
+        let breaker = self.breaker.clone();
+        let endpoint = endpoint_key(req.uri());
+        async move {
+            let circuit = admit(breaker.as_ref(), &endpoint).await?;
+            let _permit = acquire(limiter.as_deref(), 0).await;
+            let result = inner.send_multipart(req).await;
+            if let Some(circuit) = circuit {
+                circuit.finish_with(&result);
+            }
+            result
+        }
+    }
+
+    fn send_streaming<B>(
+        &self,
+        req: Request<B>,
+    ) -> impl Future<Output = http_client::Result<http_client::StreamingResponse>> + WasmCompatSend
+    where
+        B: Into<Bytes>,
+    {
+        let (parts, body) = req.into_parts();
+        let body: Bytes = body.into();
+        let inner = self.inner.clone();
+        let policy = self.policy.clone();
+        let limiter = self.limiter.clone();
+        let breaker = self.breaker.clone();
+        let endpoint = endpoint_key(&parts.uri);
+        let tokens = limiter.as_ref().map_or(0, |l| l.cost(&body));
+
+        async move {
+            Self::run(&policy, || {
+                let req = Request::from_parts(parts.clone(), body.clone());
+                let (inner, limiter, breaker) = (&inner, limiter.as_deref(), breaker.as_ref());
+                let endpoint = &endpoint;
+                async move {
+                    let circuit = match admit(breaker, endpoint).await {
+                        Ok(circuit) => circuit,
+                        Err(err) => return (Err(err.into()), None),
+                    };
+                    let permit = acquire(limiter, tokens).await;
+                    let (result, retry_after) = send_streaming_once(inner, req).await;
+                    // The circuit only judges whether the endpoint answered.
+                    if let Some(circuit) = circuit {
+                        circuit.finish_with(&result);
+                    }
+                    // Keep the in-flight slot until the stream is dropped.
+                    let result = result.map(|response| {
+                        response.map(|stream| -> BoxedStream {
+                            Box::pin(stream.map(move |chunk| {
+                                let _permit = &permit;
+                                chunk
+                            }))
+                        })
+                    });
+                    (result, retry_after)
+                }
+            })
+            .await
+        }
+    }
+}
+
+/// rig's reqwest backend turns error statuses into errors and drops the response headers,
+/// which is where `Retry-After` lives. For reqwest we therefore send directly and keep them.
+/// Other backends may answer with an error status; that becomes the same [`ProviderError`],
+/// so retries and failover work alike on every backend.
+async fn send_once<T, U>(
+    inner: &T,
+    req: Request<Bytes>,
+) -> (http_client::Result<Response<LazyBody<U>>>, Option<Duration>)
+where
+    T: HttpClientExt + 'static,
+    U: From<Bytes> + WasmCompatSend + 'static,
+{
+    #[cfg(feature = "reqwest")]
+    if let Some(client) = (inner as &dyn Any).downcast_ref::<reqwest::Client>() {
+        return reqwest_send_once(client, req).await;
+    }
+
+    match inner.send::<_, Bytes>(req).await {
+        Ok(response) if response.status().is_success() => {
+            let response = response
+                .map(|body| -> LazyBody<U> { Box::pin(async move { body.await.map(U::from) }) });
+            (Ok(response), None)
+        }
+        Ok(response) => {
+            let (parts, body) = response.into_parts();
+            let text = body.await.unwrap_or_default();
+            error_status(parts.status, &parts.headers, &text)
+        }
+        Err(err) => (Err(err), None),
+    }
+}
+
+async fn send_streaming_once<T>(
+    inner: &T,
+    req: Request<Bytes>,
+) -> (
+    http_client::Result<http_client::StreamingResponse>,
+    Option<Duration>,
+)
+where
+    T: HttpClientExt + 'static,
+{
+    #[cfg(feature = "reqwest")]
+    if let Some(client) = (inner as &dyn Any).downcast_ref::<reqwest::Client>() {
+        return reqwest_send_streaming_once(client, req).await;
+    }
+
+    match inner.send_streaming(req).await {
+        Ok(response) if response.status().is_success() => (Ok(response), None),
+        Ok(response) => {
+            let (parts, mut stream) = response.into_parts();
+            let mut text = Vec::new();
+            while let Some(Ok(chunk)) = stream.next().await {
+                text.extend_from_slice(&chunk);
+            }
+            error_status(parts.status, &parts.headers, &text)
+        }
+        Err(err) => (Err(err), None),
+    }
+}
+
+/// A non-success response from a backend other than reqwest, as a [`ProviderError`] together
+/// with the parsed `Retry-After`.
+fn error_status<R>(
+    status: reqwest::StatusCode,
+    headers: &http_client::HeaderMap,
+    body: &[u8],
+) -> (http_client::Result<R>, Option<Duration>) {
+    let text = String::from_utf8_lossy(body);
+    (
+        Err(ProviderError::from_response(status, headers, &text).into()),
+        retry_after(headers),
+    )
+}
+
+#[cfg(feature = "reqwest")]
+async fn reqwest_send_once<U>(
+    client: &reqwest::Client,
+    req: Request<Bytes>,
+) -> (http_client::Result<Response<LazyBody<U>>>, Option<Duration>)
+where
+    U: From<Bytes> + WasmCompatSend + 'static,
+{
+    let response = match reqwest_execute(client, req).await {
+        Ok(response) => response,
+        Err(failure) => return failure,
+    };
+
+    let mut res = Response::builder().status(response.status());
+    if let Some(hs) = res.headers_mut() {
+        *hs = response.headers().clone();
+    }
+    let body: LazyBody<U> = Box::pin(async move {
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| Error::Instance(e.into()))?;
+        Ok(U::from(bytes))
+    });
+    (res.body(body).map_err(Error::Protocol), None)
+}
+
+#[cfg(feature = "reqwest")]
+async fn reqwest_send_streaming_once(
+    client: &reqwest::Client,
+    req: Request<Bytes>,
+) -> (
+    http_client::Result<http_client::StreamingResponse>,
+    Option<Duration>,
+) {
+    let response = match reqwest_execute(client, req).await {
+        Ok(response) => response,
+        Err(failure) => return failure,
+    };
+
+    let mut res = Response::builder().status(response.status());
+    if let Some(hs) = res.headers_mut() {
+        *hs = response.headers().clone();
+    }
+    let stream: Pin<Box<dyn WasmCompatSendStream<InnerItem = http_client::Result<Bytes>>>> =
+        Box::pin(
+            response
+                .bytes_stream()
+                .map(|chunk| chunk.map_err(|e| Error::Instance(Box::new(e)))),
+        );
+    let stream: BoxedStream = stream;
+    (res.body(stream).map_err(Error::Protocol), None)
+}
+
+/// Execute with reqwest; non-success statuses become a [`ProviderError`] (keeping the
+/// request id header) together with the parsed `Retry-After`.
+#[cfg(feature = "reqwest")]
+async fn reqwest_execute<R>(
+    client: &reqwest::Client,
+    req: Request<Bytes>,
+) -> Result<reqwest::Response, (http_client::Result<R>, Option<Duration>)> {
+    let (parts, body) = req.into_parts();
+    let response = client
+        .request(parts.method, parts.uri.to_string())
+        .headers(parts.headers)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| (Err(Error::Instance(e.into())), None))?;
+
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let retry_after = retry_after(&headers);
+    let text = response.text().await.unwrap_or_default();
+    Err((
+        Err(ProviderError::from_response(status, &headers, &text).into()),
+        retry_after,
+    ))
+}
@@ -2,12 +2,18 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct ApiErrorResponse {
-    pub error: BailianError,
+    pub error: ApiError,
+    #[serde(default)]
+    pub request_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct BailianError {
+pub struct ApiError {
     pub message: String,
+    #[serde(default, rename = "type")]
+    pub error_type: Option<String>,
+    #[serde(default)]
+    pub code: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
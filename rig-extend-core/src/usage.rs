@@ -0,0 +1,348 @@
+//! Category: usage.rs (token usage ledger, price table and exportable snapshots)
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::AddAssign;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::rt::unix_now;
+
+/// Kind of request a usage entry was recorded for; names match the `gen_ai.operation.name`
+/// span field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Chat,
+    ChatStreaming,
+    Embedding,
+    Rerank,
+    ImageGeneration,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Chat => "chat",
+            Self::ChatStreaming => "chat_streaming",
+            Self::Embedding => "embedding",
+            Self::Rerank => "rerank",
+            Self::ImageGeneration => "image_generation",
+        }
+    }
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Token counts in one shape for every operation.
+///
+/// `cached_tokens` is the part of `prompt_tokens` served from the provider's prompt cache and
+/// `reasoning_tokens` the part of `completion_tokens` spent on thinking, as in OpenAI's
+/// `*_tokens_details`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cached_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Read an OpenAI-compatible `usage` object; DashScope's `input_tokens` / `output_tokens`
+    /// spelling is accepted as well. Missing fields count as zero.
+    pub fn from_openai(usage: &Value) -> Self {
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| usage[name].as_u64())
+                .unwrap_or(0)
+        };
+        let prompt_tokens = field(&["prompt_tokens", "input_tokens"]);
+        let completion_tokens = field(&["completion_tokens", "output_tokens"]);
+        let total_tokens = usage["total_tokens"]
+            .as_u64()
+            .unwrap_or(prompt_tokens + completion_tokens);
+        let cached_tokens = ["prompt_tokens_details", "input_tokens_details"]
+            .iter()
+            .find_map(|name| usage[name]["cached_tokens"].as_u64())
+            .unwrap_or(0);
+        let reasoning_tokens = ["completion_tokens_details", "output_tokens_details"]
+            .iter()
+            .find_map(|name| usage[name]["reasoning_tokens"].as_u64())
+            .unwrap_or(0);
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            cached_tokens,
+            reasoning_tokens,
+            total_tokens,
+        }
+    }
+
+    /// Usage for operations that only report a total (embeddings, rerank); it is all input.
+    pub fn input_only(tokens: u64) -> Self {
+        Self {
+            prompt_tokens: tokens,
+            total_tokens: tokens,
+            ..Self::default()
+        }
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.cached_tokens += other.cached_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
+/// Price of one model per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// Price of cache-hit input tokens; `None` bills them as regular input.
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+}
+
+impl ModelPrice {
+    pub fn per_million(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cached_input: None,
+        }
+    }
+
+    pub fn cached_input(mut self, price: f64) -> Self {
+        self.cached_input = Some(price);
+        self
+    }
+
+    /// Estimated cost of `usage`. Reasoning tokens are already part of the completion tokens.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let cached = usage.cached_tokens.min(usage.prompt_tokens);
+        let uncached = usage.prompt_tokens - cached;
+        (uncached as f64 * self.input
+            + cached as f64 * self.cached_input.unwrap_or(self.input)
+            + usage.completion_tokens as f64 * self.output)
+            / 1_000_000.0
+    }
+}
+
+/// Per-model prices in a single currency. Models without a price get no cost estimate.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PriceTable {
+    pub currency: String,
+    pub models: HashMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    pub fn new(currency: impl Into<String>) -> Self {
+        Self {
+            currency: currency.into(),
+            models: HashMap::new(),
+        }
+    }
+
+    pub fn model(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.models.insert(model.into(), price);
+        self
+    }
+
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.models.get(model)
+    }
+
+    pub fn cost(&self, model: &str, usage: &TokenUsage) -> Option<f64> {
+        self.price(model).map(|price| price.cost(usage))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Key {
+    provider: String,
+    model: String,
+    operation: Operation,
+    tag: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    prices: PriceTable,
+    entries: HashMap<Key, (u64, TokenUsage)>,
+}
+
+/// Running totals of token usage, shared by every clone and every client it is attached to.
+///
+/// Usage is aggregated per provider, model, [`Operation`] and optional caller tag; see
+/// `ClientBuilder::usage_ledger` and `Client::with_usage_tag`. Costs are estimated from the
+/// [`PriceTable`] when a snapshot is taken, so price updates apply to past usage too.
+#[derive(Debug, Clone, Default)]
+pub struct UsageLedger {
+    state: Arc<Mutex<State>>,
+}
+
+impl UsageLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_prices(self, prices: PriceTable) -> Self {
+        self.set_prices(prices);
+        self
+    }
+
+    pub fn set_prices(&self, prices: PriceTable) {
+        self.lock().prices = prices;
+    }
+
+    pub fn prices(&self) -> PriceTable {
+        self.lock().prices.clone()
+    }
+
+    /// Add one request's usage.
+    pub fn record(
+        &self,
+        provider: &str,
+        model: &str,
+        operation: Operation,
+        tag: Option<&str>,
+        usage: TokenUsage,
+    ) {
+        let key = Key {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            operation,
+            tag: tag.map(str::to_string),
+        };
+        let mut state = self.lock();
+        let entry = state.entries.entry(key).or_default();
+        entry.0 += 1;
+        entry.1 += usage;
+    }
+
+    pub fn snapshot(&self) -> UsageSnapshot {
+        Self::snapshot_of(&self.lock())
+    }
+
+    /// Snapshot and reset in one step, so no usage falls between two billing exports.
+    pub fn take(&self) -> UsageSnapshot {
+        let mut state = self.lock();
+        let snapshot = Self::snapshot_of(&state);
+        state.entries.clear();
+        snapshot
+    }
+
+    pub fn reset(&self) {
+        self.lock().entries.clear();
+    }
+
+    fn snapshot_of(state: &State) -> UsageSnapshot {
+        let mut entries: Vec<_> = state.entries.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        UsageSnapshot {
+            taken_at: unix_now().as_secs(),
+            currency: (!state.prices.currency.is_empty()).then(|| state.prices.currency.clone()),
+            entries: entries
+                .into_iter()
+                .map(|(key, (requests, usage))| UsageEntry {
+                    provider: key.provider.clone(),
+                    model: key.model.clone(),
+                    operation: key.operation,
+                    tag: key.tag.clone(),
+                    requests: *requests,
+                    usage: *usage,
+                    cost: state.prices.cost(&key.model, usage),
+                })
+                .collect(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Usage for one provider, model, operation and tag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageEntry {
+    pub provider: String,
+    pub model: String,
+    pub operation: Operation,
+    pub tag: Option<String>,
+    pub requests: u64,
+    pub usage: TokenUsage,
+    /// Estimated cost in the snapshot's currency; `None` when the model has no price.
+    pub cost: Option<f64>,
+}
+
+/// Sum over several entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub usage: TokenUsage,
+    /// Cost of the priced entries only.
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, entry: &UsageEntry) {
+        self.requests += entry.requests;
+        self.usage += entry.usage;
+        self.cost += entry.cost.unwrap_or(0.0);
+    }
+}
+
+/// Point-in-time copy of a [`UsageLedger`], serializable for export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageSnapshot {
+    /// Unix timestamp in seconds.
+    pub taken_at: u64,
+    pub currency: Option<String>,
+    pub entries: Vec<UsageEntry>,
+}
+
+impl UsageSnapshot {
+    pub fn total(&self) -> UsageTotals {
+        let mut totals = UsageTotals::default();
+        self.entries.iter().for_each(|entry| totals.add(entry));
+        totals
+    }
+
+    pub fn by_model(&self) -> BTreeMap<String, UsageTotals> {
+        self.group(|entry| Some(entry.model.clone()))
+    }
+
+    pub fn by_operation(&self) -> BTreeMap<Operation, UsageTotals> {
+        self.group(|entry| Some(entry.operation))
+    }
+
+    /// Totals per tag; untagged usage is left out.
+    pub fn by_tag(&self) -> BTreeMap<String, UsageTotals> {
+        self.group(|entry| entry.tag.clone())
+    }
+
+    fn group<K: Ord>(&self, key: impl Fn(&UsageEntry) -> Option<K>) -> BTreeMap<K, UsageTotals> {
+        let mut groups = BTreeMap::new();
+        for entry in &self.entries {
+            if let Some(key) = key(entry) {
+                groups
+                    .entry(key)
+                    .or_insert_with(UsageTotals::default)
+                    .add(entry);
+            }
+        }
+        groups
+    }
+}
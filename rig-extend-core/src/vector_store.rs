@@ -0,0 +1,159 @@
+//! Category: vector_store.rs (rerank-aware wrapper over any rig VectorStoreIndex)
+
+use rig::vector_store::request::VectorSearchRequest;
+use rig::vector_store::{VectorStoreError, VectorStoreIndex};
+use rig::wasm_compat::WasmCompatSend;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::rerank::Reranker;
+
+/// Default over-fetch factor: ask the inner index for 4x the requested samples.
+pub const DEFAULT_OVER_FETCH: u64 = 4;
+
+/// Vector index that over-fetches candidates from `I` and reorders them with `R`.
+///
+/// Scores in the returned tuples are the reranker's relevance scores, not vector distances.
+/// Because it implements [`VectorStoreIndex`], it can be passed straight to
+/// `agent.dynamic_context(...)`.
+#[derive(Clone, Debug)]
+pub struct RerankedIndex<I, R> {
+    index: I,
+    reranker: R,
+    over_fetch: u64,
+    text_field: Option<String>,
+}
+
+impl<I, R> RerankedIndex<I, R> {
+    pub fn new(index: I, reranker: R) -> Self {
+        Self {
+            index,
+            reranker,
+            over_fetch: DEFAULT_OVER_FETCH,
+            text_field: None,
+        }
+    }
+
+    /// How many candidates to fetch per requested sample (minimum 1).
+    pub fn over_fetch(mut self, factor: u64) -> Self {
+        self.over_fetch = factor.max(1);
+        self
+    }
+
+    /// Field of the stored document that holds the text sent to the reranker.
+    /// Without it, string documents are used as-is and anything else is sent as JSON.
+    pub fn text_field(mut self, field: impl Into<String>) -> Self {
+        self.text_field = Some(field.into());
+        self
+    }
+
+    pub fn inner(&self) -> &I {
+        &self.index
+    }
+
+    pub fn reranker(&self) -> &R {
+        &self.reranker
+    }
+
+    fn document_text(&self, document: &Value) -> String {
+        if let Some(text) = self
+            .text_field
+            .as_deref()
+            .and_then(|field| document.get(field))
+            .and_then(Value::as_str)
+        {
+            return text.to_string();
+        }
+        match document {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl<I, R> RerankedIndex<I, R>
+where
+    I: VectorStoreIndex,
+    I::Filter: Serialize + for<'de> Deserialize<'de>,
+    R: Reranker,
+{
+    /// Re-issue `req` against the inner index with `samples * over_fetch` samples.
+    /// Round-trips through serde so threshold, filter and additional params are kept.
+    fn widen(
+        &self,
+        req: &VectorSearchRequest<I::Filter>,
+    ) -> Result<VectorSearchRequest<I::Filter>, VectorStoreError> {
+        let mut value = serde_json::to_value(req)?;
+        value["samples"] = Value::from(req.samples().saturating_mul(self.over_fetch));
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Over-fetch, rerank and keep the best `req.samples()` candidates.
+    async fn reranked(
+        &self,
+        req: VectorSearchRequest<I::Filter>,
+    ) -> Result<Vec<(f64, String, Value)>, VectorStoreError> {
+        let samples = req.samples() as usize;
+        let candidates = self.index.top_n::<Value>(self.widen(&req)?).await?;
+        if candidates.is_empty() || samples == 0 {
+            return Ok(Vec::new());
+        }
+
+        let texts: Vec<String> = candidates
+            .iter()
+            .map(|(_, _, document)| self.document_text(document))
+            .collect();
+
+        let ranked = self
+            .reranker
+            .rerank(req.query(), &texts, Some(samples))
+            .await
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+        let mut candidates: Vec<Option<(f64, String, Value)>> =
+            candidates.into_iter().map(Some).collect();
+
+        Ok(ranked
+            .into_iter()
+            .filter_map(|result| {
+                candidates
+                    .get_mut(result.index)
+                    .and_then(Option::take)
+                    .map(|(_, id, document)| (result.relevance_score, id, document))
+            })
+            .take(samples)
+            .collect())
+    }
+}
+
+impl<I, R> VectorStoreIndex for RerankedIndex<I, R>
+where
+    I: VectorStoreIndex,
+    I::Filter: Serialize + for<'de> Deserialize<'de>,
+    R: Reranker,
+{
+    type Filter = I::Filter;
+
+    async fn top_n<T: for<'a> Deserialize<'a> + WasmCompatSend>(
+        &self,
+        req: VectorSearchRequest<Self::Filter>,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.reranked(req)
+            .await?
+            .into_iter()
+            .map(|(score, id, document)| Ok((score, id, serde_json::from_value(document)?)))
+            .collect()
+    }
+
+    async fn top_n_ids(
+        &self,
+        req: VectorSearchRequest<Self::Filter>,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        Ok(self
+            .reranked(req)
+            .await?
+            .into_iter()
+            .map(|(score, id, _)| (score, id))
+            .collect())
+    }
+}
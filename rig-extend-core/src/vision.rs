@@ -0,0 +1,219 @@
+//! Category: vision.rs (image and video parts in user messages, inline image checks)
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rig::OneOrMany;
+use rig::completion::CompletionError;
+use rig::message::{self, DocumentSourceKind, MimeType, UserContent};
+use rig::providers::openai;
+use serde_json::{Map, Value, json};
+
+/// What a provider accepts as an inline (base64 or raw) image. Image URLs are passed through
+/// unchecked; the provider fetches them itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLimits {
+    /// Largest inline image, counted as the base64 text that is uploaded.
+    pub max_base64_bytes: usize,
+    /// Accepted MIME types.
+    pub media_types: &'static [&'static str],
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self {
+            max_base64_bytes: 10 * 1024 * 1024,
+            media_types: &["image/jpeg", "image/png", "image/gif", "image/webp"],
+        }
+    }
+}
+
+/// An image or video part rejected before upload.
+#[derive(Debug, thiserror::Error)]
+pub enum VisionError {
+    #[error("inline image is {size} bytes as base64, over the {limit}-byte limit")]
+    TooLarge { size: usize, limit: usize },
+    #[error("image format `{0}` is not supported; accepted: {1}")]
+    UnsupportedFormat(String, String),
+    #[error("image data is not valid base64: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+impl From<VisionError> for CompletionError {
+    fn from(err: VisionError) -> Self {
+        CompletionError::RequestError(Box::new(err))
+    }
+}
+
+/// A video given as its frames (image URLs, in order), for Qwen-VL's `{"type": "video"}` part.
+pub fn video_frames<I, S>(frames: I) -> UserContent
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let frames: Vec<String> = frames.into_iter().map(Into::into).collect();
+    UserContent::Video(message::Video {
+        data: DocumentSourceKind::Unknown,
+        media_type: None,
+        additional_params: Some(json!({ "frames": frames })),
+    })
+}
+
+/// A video file by URL, sent as a `video_url` part.
+pub fn video_url(url: impl Into<String>) -> UserContent {
+    UserContent::Video(message::Video {
+        data: DocumentSourceKind::Url(url.into()),
+        media_type: None,
+        additional_params: None,
+    })
+}
+
+/// Data URI for inline image bytes, checked against `limits` like image parts in messages;
+/// e.g. for reference images in image generation requests.
+pub fn data_uri(bytes: &[u8], limits: &ImageLimits) -> Result<String, VisionError> {
+    inline_image(bytes, &STANDARD.encode(bytes), None, limits)
+}
+
+/// The user message as OpenAI-compatible JSON when it has image or video parts, which rig's
+/// conversion either rejects (raw bytes, base64 without `detail`, video) or passes unchecked.
+/// `None` leaves the message to rig.
+pub(crate) fn user_message(
+    content: &OneOrMany<UserContent>,
+    limits: &ImageLimits,
+) -> Result<Option<Value>, CompletionError> {
+    let visual = content
+        .iter()
+        .any(|part| matches!(part, UserContent::Image(_) | UserContent::Video(_)));
+    let tool_result = content
+        .iter()
+        .any(|part| matches!(part, UserContent::ToolResult(_)));
+    if !visual || tool_result {
+        return Ok(None);
+    }
+
+    let parts = content
+        .iter()
+        .map(|part| match part {
+            UserContent::Image(image) => image_part(image, limits),
+            UserContent::Video(video) => video_part(video),
+            other => {
+                let part = openai::UserContent::try_from(other.clone())?;
+                Ok(serde_json::to_value(part)?)
+            }
+        })
+        .collect::<Result<Vec<_>, CompletionError>>()?;
+    Ok(Some(json!({ "role": "user", "content": parts })))
+}
+
+fn image_part(image: &message::Image, limits: &ImageLimits) -> Result<Value, CompletionError> {
+    let declared = image.media_type.as_ref().map(|media| media.to_mime_type());
+    let url = match &image.data {
+        DocumentSourceKind::Url(url) => url.clone(),
+        DocumentSourceKind::Base64(data) => {
+            let bytes = STANDARD.decode(data).map_err(VisionError::from)?;
+            inline_image(&bytes, data, declared, limits)?
+        }
+        DocumentSourceKind::Raw(bytes) => {
+            inline_image(bytes, &STANDARD.encode(bytes), declared, limits)?
+        }
+        other => {
+            return Err(VisionError::Unsupported(format!("image has no data: {other:?}")).into());
+        }
+    };
+
+    let mut image_url = Map::new();
+    image_url.insert("url".into(), url.into());
+    if let Some(detail) = &image.detail {
+        image_url.insert("detail".into(), serde_json::to_value(detail)?);
+    }
+    let mut part = json!({ "type": "image_url", "image_url": image_url });
+    extend(&mut part, image.additional_params.as_ref());
+    Ok(part)
+}
+
+/// Checks an inline image against `limits` and returns its data URI.
+fn inline_image(
+    bytes: &[u8],
+    base64: &str,
+    declared: Option<&str>,
+    limits: &ImageLimits,
+) -> Result<String, VisionError> {
+    if base64.len() > limits.max_base64_bytes {
+        return Err(VisionError::TooLarge {
+            size: base64.len(),
+            limit: limits.max_base64_bytes,
+        });
+    }
+    let media_type = sniff(bytes).or(declared).unwrap_or("unknown");
+    if !limits.media_types.contains(&media_type) {
+        return Err(VisionError::UnsupportedFormat(
+            media_type.to_string(),
+            limits.media_types.join(", "),
+        ));
+    }
+    Ok(format!("data:{media_type};base64,{base64}"))
+}
+
+/// Image format from the file's magic bytes.
+fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    let media_type = match bytes {
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => "image/webp",
+        [b'B', b'M', ..] => "image/bmp",
+        [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => "image/tiff",
+        [_, _, _, _, b'f', b't', b'y', b'p', brand @ ..] => match brand.get(..4)? {
+            b"heic" | b"heix" | b"heim" | b"heis" => "image/heic",
+            b"mif1" | b"msf1" | b"heif" => "image/heif",
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(media_type)
+}
+
+fn video_part(video: &message::Video) -> Result<Value, CompletionError> {
+    let mut extra = video.additional_params.clone().unwrap_or_default();
+    let frames = extra
+        .as_object_mut()
+        .and_then(|extra| extra.remove("frames"));
+    let mut part = match (&video.data, frames) {
+        (_, Some(frames)) => json!({ "type": "video", "video": frames }),
+        (DocumentSourceKind::Url(url), None) => {
+            json!({ "type": "video_url", "video_url": { "url": url } })
+        }
+        (other, None) => {
+            return Err(VisionError::Unsupported(format!(
+                "videos must be a URL or a list of frames, got {other:?}"
+            ))
+            .into());
+        }
+    };
+    extend(&mut part, Some(&extra));
+    Ok(part)
+}
+
+/// Provider-specific fields (e.g. Qwen-VL's `fps`) from `additional_params`, on the part.
+fn extend(part: &mut Value, extra: Option<&Value>) {
+    if let (Some(part), Some(Value::Object(extra))) = (part.as_object_mut(), extra) {
+        for (key, value) in extra {
+            part.insert(key.clone(), value.clone());
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::time::Duration;
+
+use rig::completion::CompletionModel as _;
+use rig::http_client::HttpClientExt;
+use rig::prelude::*;
+use rig_extend_core::{
+    CIRCUIT_OPEN, CircuitBreakerPolicy, CircuitState, ErrorClass, Profile, ProviderErrorExt,
+    RetryPolicy,
+};
+use rig_extend_mock::{Mock, MockResponse, MockServer, Recorder, fixtures, paths};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_CIRCUIT_API_KEY_UNSET";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_CIRCUIT_BASE_URL_UNSET";
+}
+
+type Client = rig_extend_core::Client<Ark>;
+
+fn overloaded() -> MockResponse {
+    MockResponse::error(
+        503,
+        fixtures::openai_error("Service is overloaded", "server_error", "ServerOverloaded"),
+    )
+}
+
+fn client(server: &MockServer, policy: CircuitBreakerPolicy) -> Client {
+    Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .circuit_breaker(policy)
+        .build()
+}
+
+async fn chat<T>(
+    client: &rig_extend_core::Client<Ark, T>,
+) -> Result<(), rig::completion::CompletionError>
+where
+    T: HttpClientExt + Clone + Default + std::fmt::Debug + Send + Sync + 'static,
+{
+    client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await
+        .map(|_| ())
+}
+
+#[tokio::test]
+async fn opens_after_consecutive_failures_and_fails_fast() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()).times(3));
+    let client = client(
+        &server,
+        CircuitBreakerPolicy::default()
+            .consecutive_failures(3)
+            .open_for(Duration::from_secs(60)),
+    );
+    let endpoint = format!("{}/chat/completions", server.ark_base_url());
+
+    for _ in 0..3 {
+        chat(&client).await.unwrap_err();
+    }
+    let breaker = client.circuit_breaker().unwrap();
+    assert_eq!(breaker.state(&endpoint), CircuitState::Open);
+
+    let err = chat(&client).await.unwrap_err();
+    let provider_error = err.provider_error().unwrap();
+    assert_eq!(provider_error.code.as_deref(), Some(CIRCUIT_OPEN));
+    assert_eq!(err.error_class(), Some(ErrorClass::Server));
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 3);
+
+    // Other endpoints of the same client have their own circuit.
+    assert_eq!(
+        breaker.state(&format!("{}/embeddings", server.ark_base_url())),
+        CircuitState::Closed
+    );
+}
+
+#[tokio::test]
+async fn half_open_probe_uses_verify() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()).times(2));
+    server.mock(
+        Mock::get(&format!("{}/models", paths::ARK))
+            .respond(overloaded())
+            .times(1),
+    );
+    let client = client(
+        &server,
+        CircuitBreakerPolicy::default()
+            .consecutive_failures(2)
+            .open_for(Duration::from_millis(50)),
+    );
+
+    chat(&client).await.unwrap_err();
+    chat(&client).await.unwrap_err();
+
+    // The first probe fails, so the circuit opens again without sending the request.
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let err = chat(&client).await.unwrap_err();
+    assert_eq!(
+        err.provider_error().unwrap().code.as_deref(),
+        Some(CIRCUIT_OPEN)
+    );
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 2);
+
+    // The second probe succeeds and the request goes through.
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    chat(&client).await.unwrap();
+    assert_eq!(
+        server.requests_to(&format!("{}/models", paths::ARK)).len(),
+        2
+    );
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 3);
+    let endpoint = format!("{}/chat/completions", server.ark_base_url());
+    assert_eq!(
+        client.circuit_breaker().unwrap().state(&endpoint),
+        CircuitState::Closed
+    );
+}
+
+#[tokio::test]
+async fn half_open_probe_works_with_another_http_backend() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()).times(2));
+    let path = std::env::temp_dir().join(format!("rig-circuit-probe-{}.json", std::process::id()));
+    let recorder = Recorder::record(reqwest::Client::new(), &path);
+    let client: rig_extend_core::Client<Ark, Recorder> = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .with_client(recorder.clone())
+        .circuit_breaker(
+            CircuitBreakerPolicy::default()
+                .consecutive_failures(2)
+                .open_for(Duration::from_millis(50)),
+        )
+        .build();
+
+    chat(&client).await.unwrap_err();
+    chat(&client).await.unwrap_err();
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    chat(&client).await.unwrap();
+
+    let uris: Vec<String> = recorder
+        .cassette()
+        .interactions
+        .iter()
+        .map(|interaction| interaction.request.uri.clone())
+        .collect();
+    assert_eq!(uris.len(), 4);
+    assert!(uris[2].ends_with("/models"), "{uris:?}");
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn client_errors_do_not_open_the_circuit() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_CHAT)
+            .respond(MockResponse::error(
+                400,
+                fixtures::openai_error("bad", "invalid_request_error", "InvalidParameter"),
+            ))
+            .times(2),
+    );
+    server.mock(
+        Mock::post(paths::ARK_CHAT)
+            .respond(MockResponse::error(
+                429,
+                fixtures::openai_error("slow down", "rate_limit", "RateLimitExceeded"),
+            ))
+            .times(2),
+    );
+    let client = client(
+        &server,
+        CircuitBreakerPolicy::default().consecutive_failures(1),
+    );
+
+    for _ in 0..4 {
+        chat(&client).await.unwrap_err();
+    }
+    chat(&client).await.unwrap();
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 5);
+}
+
+#[tokio::test]
+async fn error_rate_opens_the_circuit() {
+    let server = MockServer::start().await;
+    let client = client(
+        &server,
+        CircuitBreakerPolicy {
+            consecutive_failures: None,
+            ..CircuitBreakerPolicy::default().error_rate(0.5, 4)
+        },
+    );
+    for fail in [false, true, false, true] {
+        if fail {
+            server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()).times(1));
+            chat(&client).await.unwrap_err();
+        } else {
+            chat(&client).await.unwrap();
+        }
+    }
+
+    let err = chat(&client).await.unwrap_err();
+    assert_eq!(
+        err.provider_error().unwrap().code.as_deref(),
+        Some(CIRCUIT_OPEN)
+    );
+}
+
+#[tokio::test]
+async fn retries_stop_once_the_circuit_opens() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()));
+    let client = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .retry_policy(
+            RetryPolicy::default()
+                .max_attempts(5)
+                .backoff(Duration::from_millis(1), Duration::from_millis(2)),
+        )
+        .circuit_breaker(CircuitBreakerPolicy::default().consecutive_failures(2))
+        .build();
+
+    let err = chat(&client).await.unwrap_err();
+    assert_eq!(
+        err.provider_error().unwrap().code.as_deref(),
+        Some(CIRCUIT_OPEN)
+    );
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 2);
+}
+
+#[tokio::test]
+async fn multipart_requests_go_through_the_circuit() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()));
+    let client = client(
+        &server,
+        CircuitBreakerPolicy::default().consecutive_failures(2),
+    );
+    let endpoint = format!("{}/chat/completions", server.ark_base_url());
+    let upload = || {
+        let form = reqwest::multipart::Form::new().text("purpose", "batch");
+        let req = rig::http_client::Request::post(&endpoint)
+            .body(form)
+            .unwrap();
+        client.http_client().send_multipart::<bytes::Bytes>(req)
+    };
+
+    assert!(upload().await.is_err());
+    assert!(upload().await.is_err());
+    assert_eq!(
+        client.circuit_breaker().unwrap().state(&endpoint),
+        CircuitState::Open
+    );
+
+    let Err(err) = upload().await else {
+        panic!("the open circuit should reject the upload");
+    };
+    assert_eq!(
+        err.provider_error().unwrap().code.as_deref(),
+        Some(CIRCUIT_OPEN)
+    );
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 2);
+}
+
+#[test]
+fn config_enables_the_breaker() {
+    let config = rig_extend_core::Config::from_toml_str(
+        r#"
+[clients.ark]
+provider = "mock-ark"
+api_key = "k"
+circuit_breaker = { error_rate = 0.5, window = 20, open_for = "10s" }
+"#,
+    )
+    .unwrap();
+    let client: Client = config.client("ark").unwrap();
+    let policy = client.circuit_breaker().unwrap().policy();
+    assert_eq!(policy.consecutive_failures, Some(5));
+    assert_eq!(policy.error_rate, Some((0.5, 20)));
+    assert_eq!(policy.open_for, Duration::from_secs(10));
+
+    let err = rig_extend_core::Config::from_toml_str(
+        "[clients.ark]\nprovider = \"mock-ark\"\ncircuit_breaker = { error_rate = 1.5 }\n",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("clients.ark.circuit_breaker.error_rate: must be between 0.0 and 1.0"),
+        "{err}"
+    );
+}
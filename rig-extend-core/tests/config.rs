@@ -0,0 +1,288 @@
+use std::time::{Duration, Instant};
+
+use rig::completion::CompletionModel as _;
+use rig::embeddings::EmbeddingModel as _;
+use rig_extend_core::{Config, ConfigError, ModelKind, Profile};
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_CONFIG_API_KEY_UNSET";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_CONFIG_BASE_URL_UNSET";
+}
+
+type Client = rig_extend_core::Client<Ark>;
+type CompletionModel = rig_extend_core::CompletionModel<Ark>;
+type EmbeddingModel = rig_extend_core::EmbeddingModel<Ark>;
+
+fn toml(base_url: &str) -> String {
+    format!(
+        r#"
+[clients.ark]
+provider = "mock-ark"
+base_url = "{base_url}"
+api_key = "test-key"
+timeout = "30s"
+retry = {{ max_attempts = 4, initial_backoff = "10ms" }}
+rate_limits = {{ max_in_flight = 2 }}
+
+[models.chat]
+client = "ark"
+kind = "chat"
+model = "doubao-seed"
+temperature = 0.2
+max_tokens = 256
+params = {{ thinking = {{ type = "disabled" }} }}
+
+[models.vectors]
+client = "ark"
+kind = "embedding"
+model = "doubao-embedding"
+dimensions = 8
+batch_size = 2
+"#
+    )
+}
+
+#[tokio::test]
+async fn builds_clients_and_models_by_name() {
+    let server = MockServer::start().await;
+    let config = Config::from_toml_str(&toml(&server.ark_base_url())).unwrap();
+
+    let client: Client = config.client("ark").unwrap();
+    assert_eq!(client.retry_policy().max_attempts, 4);
+    assert_eq!(
+        client.retry_policy().initial_backoff,
+        Duration::from_millis(10)
+    );
+    assert_eq!(client.rate_limits().unwrap().max_in_flight, Some(2));
+
+    let chat: CompletionModel = config.model("chat").unwrap();
+    chat.completion_request("hi")
+        .temperature(0.9)
+        .send()
+        .await
+        .unwrap();
+    let body = server.requests_to(paths::ARK_CHAT)[0].json();
+    assert_eq!(body["model"], "doubao-seed");
+    // Request values win over the configured defaults.
+    assert_eq!(body["temperature"], 0.9);
+    assert_eq!(body["max_tokens"], 256);
+    assert_eq!(body["thinking"]["type"], "disabled");
+
+    let vectors: EmbeddingModel = config.model("vectors").unwrap();
+    let embeddings = vectors
+        .embed_texts(["a", "b", "c"].map(String::from))
+        .await
+        .unwrap();
+    assert_eq!(embeddings[2].vec.len(), 8);
+    assert_eq!(server.requests_to(paths::ARK_EMBEDDINGS).len(), 2);
+}
+
+#[tokio::test]
+async fn models_on_one_client_share_its_limits() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond_with(|req| {
+        let model = req.json()["model"].as_str().unwrap_or_default().to_string();
+        MockResponse::json(fixtures::chat_completion(&model, "ok"))
+            .delay(Duration::from_millis(150))
+    }));
+    let config = Config::from_toml_str(&format!(
+        r#"
+[clients.ark]
+provider = "mock-ark"
+base_url = "{}"
+api_key = "test-key"
+rate_limits = {{ max_in_flight = 1 }}
+
+[models.fast]
+client = "ark"
+kind = "chat"
+model = "doubao-seed-flash"
+
+[models.smart]
+client = "ark"
+kind = "chat"
+model = "doubao-seed"
+"#,
+        server.ark_base_url()
+    ))
+    .unwrap();
+
+    let client: Client = config.client("ark").unwrap();
+    let fast: CompletionModel = config.model_on(&client, "fast").unwrap();
+    let smart: CompletionModel = config.model_on(&client, "smart").unwrap();
+
+    let started = Instant::now();
+    let (a, b) = tokio::join!(
+        fast.completion_request("hi").send(),
+        smart.completion_request("hi").send()
+    );
+    a.unwrap();
+    b.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(300));
+
+    let err = config
+        .model_on::<EmbeddingModel>(&client, "fast")
+        .err()
+        .unwrap();
+    assert!(matches!(err, ConfigError::KindMismatch { .. }));
+}
+
+#[test]
+fn yaml_matches_toml() {
+    let yaml = r#"
+clients:
+  ark:
+    provider: mock-ark
+    base_url: http://127.0.0.1:1/api/v3
+    api_key: test-key
+    timeout: 30s
+    retry: { max_attempts: 4, initial_backoff: 10ms }
+    rate_limits: { max_in_flight: 2 }
+models:
+  chat:
+    client: ark
+    kind: chat
+    model: doubao-seed
+    temperature: 0.2
+    max_tokens: 256
+    params: { thinking: { type: disabled } }
+  vectors:
+    client: ark
+    kind: embedding
+    model: doubao-embedding
+    dimensions: 8
+    batch_size: 2
+"#;
+    assert_eq!(
+        Config::from_yaml_str(yaml).unwrap(),
+        Config::from_toml_str(&toml("http://127.0.0.1:1/api/v3")).unwrap()
+    );
+}
+
+#[test]
+fn reports_every_validation_issue() {
+    let err = Config::from_toml_str(
+        r#"
+[clients.ark]
+provider = "mock-ark"
+base_url = "127.0.0.1:8080"
+api_key = "k"
+api_key_env = "K"
+
+[models.chat]
+client = "missing"
+kind = "completion"
+model = "doubao-seed"
+dimensions = 8
+
+[models.rank]
+client = "ark"
+kind = "rerank"
+model = ""
+"#,
+    )
+    .unwrap_err();
+
+    let ConfigError::Invalid { issues, .. } = &err else {
+        panic!("{err}");
+    };
+    assert_eq!(
+        issues,
+        &[
+            "clients.ark: set `api_key` or `api_key_env`, not both",
+            "clients.ark.base_url: `127.0.0.1:8080` is not an http(s) URL",
+            "models.chat.client: no client named `missing` in `clients`",
+            "models.chat.dimensions: not used by completion models",
+            "models.rank.model: must not be empty",
+        ]
+    );
+    assert!(
+        err.to_string().contains("\n  - models.chat.client"),
+        "{err}"
+    );
+
+    let err =
+        Config::from_toml_str("[clients.ark]\nprovider = \"x\"\napi_keys = \"k\"\n").unwrap_err();
+    assert!(matches!(err, ConfigError::Parse { .. }));
+    assert!(err.to_string().contains("api_keys"), "{err}");
+}
+
+#[test]
+fn build_errors_name_the_entry() {
+    let config = Config::from_toml_str(
+        r#"
+[clients.ark]
+provider = "mock-ark"
+
+[clients.other]
+provider = "bailian"
+api_key = "k"
+
+[models.vectors]
+client = "ark"
+kind = "embedding"
+model = "doubao-embedding"
+"#,
+    )
+    .unwrap();
+
+    let err = config.client::<Client>("ark").unwrap_err();
+    assert!(matches!(err, ConfigError::MissingApiKey { .. }));
+    assert_eq!(
+        err.to_string(),
+        "no API key for `ark`: MOCK_ARK_CONFIG_API_KEY_UNSET is not set"
+    );
+
+    let err = config.client::<Client>("other").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "client `other` is a `bailian` client, not `mock-ark`"
+    );
+
+    assert!(matches!(
+        config.model::<CompletionModel>("vectors"),
+        Err(ConfigError::KindMismatch {
+            expected: ModelKind::Completion,
+            found: ModelKind::Embedding,
+            ..
+        })
+    ));
+    assert!(matches!(
+        config.model::<CompletionModel>("nope"),
+        Err(ConfigError::UnknownModel(_))
+    ));
+
+    assert!(matches!(
+        Client::try_from_env(),
+        Err(ConfigError::MissingApiKey { .. })
+    ));
+}
+
+#[test]
+fn load_picks_the_format_from_the_extension() {
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("rig-extend-config-{}.yml", std::process::id()));
+    std::fs::write(
+        &path,
+        "clients:\n  ark:\n    provider: mock-ark\n    api_key: k\n",
+    )
+    .unwrap();
+    let config = Config::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.unwrap().clients["ark"].provider, "mock-ark");
+
+    let err = Config::load(dir.join("rig-extend-config.ini")).unwrap_err();
+    assert!(matches!(err, ConfigError::Read { .. }), "{err}");
+    let path = dir.join(format!("rig-extend-config-{}.ini", std::process::id()));
+    std::fs::write(&path, "").unwrap();
+    let err = Config::load(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(err, ConfigError::UnknownFormat { .. }), "{err}");
+}
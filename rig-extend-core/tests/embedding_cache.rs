@@ -0,0 +1,136 @@
+use rig::embeddings::EmbeddingModel as _;
+use rig::prelude::*;
+use rig_extend_core::{CachedEmbeddingModel, EmbeddingCache, Profile};
+use rig_extend_mock::{MockServer, fixtures, paths};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_API_KEY";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_BASE_URL";
+}
+
+type Client = rig_extend_core::Client<Ark>;
+
+fn cached(
+    server: &MockServer,
+    cache: &EmbeddingCache,
+    model: &str,
+    dims: usize,
+) -> CachedEmbeddingModel<rig_extend_core::EmbeddingModel<Ark>> {
+    let inner = Client::builder("test-key")
+        .base_url(&server.ark_base_url())
+        .build()
+        .embedding_model_with_ndims(model, dims);
+    CachedEmbeddingModel::new(inner, cache.clone(), model)
+}
+
+fn texts(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[tokio::test]
+async fn second_call_is_served_from_cache() {
+    let server = MockServer::start().await;
+    let cache = EmbeddingCache::open_in_memory().await.unwrap();
+    let model = cached(&server, &cache, "doubao-embedding", 8);
+
+    let first = model.embed_texts(texts(&["alpha", "beta"])).await.unwrap();
+    let second = model.embed_texts(texts(&["alpha", "beta"])).await.unwrap();
+
+    assert_eq!(server.requests_to(paths::ARK_EMBEDDINGS).len(), 1);
+    for (a, b) in first.iter().zip(&second) {
+        assert_eq!(a.document, b.document);
+        assert_eq!(a.vec, b.vec);
+    }
+    assert_eq!(second[0].vec, fixtures::embedding("alpha", 8));
+
+    let stats = model.stats();
+    assert_eq!((stats.hits, stats.misses), (2, 2));
+    assert_eq!(stats.hit_rate(), 0.5);
+    assert_eq!(cache.len().await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn only_unique_misses_reach_the_provider() {
+    let server = MockServer::start().await;
+    let cache = EmbeddingCache::open_in_memory().await.unwrap();
+    let model = cached(&server, &cache, "doubao-embedding", 8);
+
+    model.embed_text("alpha").await.unwrap();
+    let result = model
+        .embed_texts(texts(&["gamma", "alpha", "gamma", "delta"]))
+        .await
+        .unwrap();
+
+    let requests = server.requests_to(paths::ARK_EMBEDDINGS);
+    assert_eq!(requests.len(), 2);
+    let body = requests[1].json();
+    assert_eq!(body["input"], serde_json::json!(["gamma", "delta"]));
+
+    let documents: Vec<&str> = result.iter().map(|e| e.document.as_str()).collect();
+    assert_eq!(documents, ["gamma", "alpha", "gamma", "delta"]);
+    for embedding in &result {
+        assert_eq!(embedding.vec, fixtures::embedding(&embedding.document, 8));
+    }
+    assert_eq!(model.stats().hits, 1);
+    assert_eq!(model.stats().misses, 4);
+}
+
+#[tokio::test]
+async fn entries_are_keyed_by_model_and_dims() {
+    let server = MockServer::start().await;
+    let cache = EmbeddingCache::open_in_memory().await.unwrap();
+
+    cached(&server, &cache, "doubao-embedding", 8)
+        .embed_text("alpha")
+        .await
+        .unwrap();
+    let wider = cached(&server, &cache, "doubao-embedding", 16)
+        .embed_text("alpha")
+        .await
+        .unwrap();
+    cached(&server, &cache, "doubao-embedding-large", 8)
+        .embed_text("alpha")
+        .await
+        .unwrap();
+
+    assert_eq!(wider.vec.len(), 16);
+    assert_eq!(server.requests_to(paths::ARK_EMBEDDINGS).len(), 3);
+    assert_eq!(cache.len().await.unwrap(), 3);
+
+    assert_eq!(cache.clear(Some("doubao-embedding")).await.unwrap(), 2);
+    assert_eq!(cache.clear(None).await.unwrap(), 1);
+    assert!(cache.is_empty().await.unwrap());
+}
+
+#[tokio::test]
+async fn cache_persists_across_reopen() {
+    let server = MockServer::start().await;
+    let path = std::env::temp_dir().join(format!(
+        "rig-extend-embedding-cache-{}.sqlite",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let cache = EmbeddingCache::open(&path).await.unwrap();
+    let first = cached(&server, &cache, "doubao-embedding", 8)
+        .embed_text("alpha")
+        .await
+        .unwrap();
+    drop(cache);
+
+    let cache = EmbeddingCache::open(&path).await.unwrap();
+    let model = cached(&server, &cache, "doubao-embedding", 8);
+    let second = model.embed_text("alpha").await.unwrap();
+
+    assert_eq!(first.vec, second.vec);
+    assert_eq!(server.requests_to(paths::ARK_EMBEDDINGS).len(), 1);
+    assert_eq!(model.stats().hits, 1);
+    drop(cache);
+    let _ = std::fs::remove_file(&path);
+}
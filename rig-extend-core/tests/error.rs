@@ -0,0 +1,215 @@
+use rig::client::{VerifyClient, VerifyError};
+use rig::completion::CompletionModel as _;
+use rig::prelude::*;
+use rig_extend_core::{ErrorClass, Profile, ProviderError, ProviderErrorExt};
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+use serde_json::json;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_API_KEY";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_BASE_URL";
+}
+
+type Client = rig_extend_core::Client<Ark>;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder("test-key")
+        .base_url(&server.ark_base_url())
+        .build()
+}
+
+#[test]
+fn classifies_provider_payloads() {
+    let cases = [
+        (
+            401,
+            fixtures::openai_error(
+                "Incorrect API key provided.",
+                "invalid_request_error",
+                "invalid_api_key",
+            ),
+            ErrorClass::Auth,
+        ),
+        (
+            400,
+            fixtures::openai_error(
+                "Access denied, please make sure your account is in good standing.",
+                "Arrearage",
+                "Arrearage",
+            ),
+            ErrorClass::Quota,
+        ),
+        (
+            429,
+            fixtures::dashscope_error("Throttling.RateQuota", "Requests rate limit exceeded"),
+            ErrorClass::RateLimit,
+        ),
+        (
+            400,
+            fixtures::openai_error(
+                "Input data may contain inappropriate content.",
+                "data_inspection_failed",
+                "data_inspection_failed",
+            ),
+            ErrorClass::ContentFilter,
+        ),
+        (
+            400,
+            fixtures::openai_error(
+                "This model's maximum context length is 32768 tokens.",
+                "invalid_request_error",
+                "context_length_exceeded",
+            ),
+            ErrorClass::ContextLength,
+        ),
+        (
+            400,
+            fixtures::openai_error(
+                "The parameter `temperature` is invalid.",
+                "BadRequest",
+                "InvalidParameter",
+            ),
+            ErrorClass::InvalidParam,
+        ),
+        (
+            413,
+            fixtures::tei_error(
+                "batch size 64 > maximum allowed batch size 32",
+                "Validation",
+            ),
+            ErrorClass::InvalidParam,
+        ),
+        (
+            503,
+            fixtures::openai_error("Service is overloaded", "server_error", "ServerOverloaded"),
+            ErrorClass::Server,
+        ),
+    ];
+
+    for (status, body, class) in cases {
+        let err = ProviderError::from_body(Some(status), &body.to_string());
+        assert_eq!(err.class, class, "{body}");
+        assert_eq!(err.status, Some(status));
+    }
+}
+
+#[test]
+fn parses_codes_and_request_ids() {
+    let err = ProviderError::from_body(
+        Some(401),
+        &fixtures::dashscope_error("InvalidApiKey", "Invalid API-key provided.").to_string(),
+    );
+    assert_eq!(err.code.as_deref(), Some("InvalidApiKey"));
+    assert_eq!(err.message, "Invalid API-key provided.");
+    assert_eq!(err.request_id.as_deref(), Some(fixtures::REQUEST_ID));
+
+    // Ark puts the id in the message.
+    let body = json!({ "error": {
+        "code": "AuthenticationError",
+        "message": "The API key format is incorrect. Request id: 0217000abc-def",
+        "type": "Unauthorized",
+    }});
+    let err = ProviderError::from_body(Some(401), &body.to_string());
+    assert_eq!(err.error_type.as_deref(), Some("Unauthorized"));
+    assert_eq!(err.request_id.as_deref(), Some("0217000abc-def"));
+
+    // Anything else is kept verbatim.
+    let err = ProviderError::from_body(Some(502), "<html>Bad Gateway</html>");
+    assert_eq!(err.message, "<html>Bad Gateway</html>");
+    assert_eq!(err.class, ErrorClass::Server);
+    assert!(err.is_transient());
+}
+
+#[tokio::test]
+async fn completion_error_keeps_status_and_request_id_header() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_CHAT).respond(
+            MockResponse::error(
+                429,
+                json!({ "error": {
+                    "code": "RateLimitExceeded.EndpointRPMExceeded",
+                    "message": "The request rate has exceeded the limit.",
+                    "type": "TooManyRequests",
+                }}),
+            )
+            .header("x-request-id", "ark-req-1"),
+        ),
+    );
+
+    let model = client(&server).completion_model("doubao");
+    let err = model.completion_request("hi").send().await.unwrap_err();
+
+    let provider = err.provider_error().expect("typed provider error");
+    assert_eq!(provider.status, Some(429));
+    assert_eq!(
+        provider.code.as_deref(),
+        Some("RateLimitExceeded.EndpointRPMExceeded")
+    );
+    assert_eq!(provider.class, ErrorClass::RateLimit);
+    assert_eq!(err.request_id(), Some("ark-req-1"));
+    assert!(err.to_string().contains("request_id: ark-req-1"), "{err}");
+}
+
+#[tokio::test]
+async fn streaming_error_is_typed() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(MockResponse::error(
+        400,
+        fixtures::openai_error(
+            "The request failed because the input text may contain sensitive information.",
+            "BadRequest",
+            "SensitiveContentDetected",
+        ),
+    )));
+
+    let model = client(&server).completion_model("doubao");
+    let Err(err) = model.completion_request("hi").stream().await else {
+        panic!("expected an error");
+    };
+    assert_eq!(err.error_class(), Some(ErrorClass::ContentFilter));
+}
+
+#[tokio::test]
+async fn embedding_error_is_typed() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_EMBEDDINGS).respond(MockResponse::error(
+            404,
+            fixtures::openai_error(
+                "The model or endpoint does not exist.",
+                "NotFound",
+                "InvalidEndpointOrModel.NotFound",
+            ),
+        )),
+    );
+
+    let model = client(&server).embedding_model("missing");
+    let err = rig::embeddings::EmbeddingModel::embed_text(&model, "hi")
+        .await
+        .unwrap_err();
+    assert_eq!(err.error_class(), Some(ErrorClass::InvalidParam));
+    assert_eq!(err.request_id(), Some(fixtures::REQUEST_ID));
+}
+
+#[tokio::test]
+async fn verify_reports_invalid_authentication() {
+    let server = MockServer::start().await;
+    server.mock(Mock::get("/api/v3/models").respond(MockResponse::error(
+        401,
+        fixtures::openai_error(
+            "The API key format is incorrect.",
+            "Unauthorized",
+            "AuthenticationError",
+        ),
+    )));
+
+    let err = client(&server).verify().await.unwrap_err();
+    assert!(matches!(err, VerifyError::InvalidAuthentication), "{err}");
+}
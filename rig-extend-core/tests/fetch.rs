@@ -0,0 +1,38 @@
+#![cfg(feature = "workers")]
+
+use rig::embeddings::EmbeddingModel as _;
+use rig::prelude::*;
+use rig_extend_core::{FetchClient, Profile, RetryPolicy};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "https://ark.example/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_FETCH_API_KEY_UNSET";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_FETCH_BASE_URL_UNSET";
+}
+
+type Client = rig_extend_core::Client<Ark, FetchClient>;
+
+fn assert_completion_model<M: rig::completion::CompletionModel>(_: &M) {}
+fn assert_embedding_model<M: rig::embeddings::EmbeddingModel>(_: &M) {}
+
+// Requests need a JS runtime; natively we can only check that the models build on top of it.
+#[test]
+fn fetch_client_backs_completion_and_embedding_models() {
+    let client: Client = rig_extend_core::Client::builder("k")
+        .with_client(FetchClient::new())
+        .retry_policy(RetryPolicy::default())
+        .build();
+
+    let completion = client.completion_model("doubao-seed-1-6");
+    assert_completion_model(&completion);
+    assert_eq!(completion.model, "doubao-seed-1-6");
+
+    let embedding = client.embedding_model_with_ndims("doubao-embedding", 2048);
+    assert_embedding_model(&embedding);
+    assert_eq!(embedding.ndims(), 2048);
+}
@@ -0,0 +1,188 @@
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use reqwest::header::{HeaderName, HeaderValue};
+use rig::completion::CompletionModel as _;
+use rig::prelude::*;
+use rig_extend_core::{Config, ConfigError, HttpOptionsExt, Profile};
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_HTTP_API_KEY_UNSET";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_HTTP_BASE_URL_UNSET";
+}
+
+type Client = rig_extend_core::Client<Ark>;
+
+#[tokio::test]
+async fn sends_default_headers_and_user_agent() {
+    let server = MockServer::start().await;
+    let client = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .header(
+            HeaderName::from_static("x-tenant"),
+            HeaderValue::from_static("acme"),
+        )
+        .header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_static("Bearer ignored"),
+        )
+        .user_agent(HeaderValue::from_static("acme-rag/1.2"))
+        .connect_timeout(Duration::from_secs(5))
+        .try_build()
+        .unwrap();
+
+    client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+
+    let request = &server.requests_to(paths::ARK_CHAT)[0];
+    assert_eq!(request.header("x-tenant"), Some("acme"));
+    assert_eq!(request.header("user-agent"), Some("acme-rag/1.2"));
+    // The API key set on the request wins over a default header.
+    assert_eq!(request.header("authorization"), Some("Bearer k"));
+}
+
+#[tokio::test]
+async fn request_timeout_fails_slow_responses() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_CHAT).respond(
+            MockResponse::json(fixtures::chat_completion("m", "late"))
+                .delay(Duration::from_millis(500)),
+        ),
+    );
+    let client = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .timeout(Duration::from_millis(100))
+        .build();
+
+    let started = Instant::now();
+    let result = client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await;
+    assert!(result.is_err());
+    assert!(started.elapsed() < Duration::from_millis(400));
+}
+
+#[tokio::test]
+async fn stream_idle_timeout_ends_stalled_streams() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_CHAT).respond(
+            MockResponse::sse(fixtures::chat_stream("m", &["a", "b"]))
+                .event_delay(Duration::from_millis(500)),
+        ),
+    );
+    let client = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .stream_idle_timeout(Duration::from_millis(100))
+        .build();
+
+    let mut stream = client
+        .completion_model("m")
+        .completion_request("hi")
+        .stream()
+        .await
+        .unwrap();
+    let mut failed = false;
+    while let Some(item) = stream.next().await {
+        if item.is_err() {
+            failed = true;
+            break;
+        }
+    }
+    assert!(failed, "stalled stream should fail");
+}
+
+#[tokio::test]
+async fn routes_requests_through_the_proxy() {
+    let server = MockServer::start().await;
+    let client = Client::builder("k")
+        .base_url("http://ark.invalid/api/v3")
+        .proxy(reqwest::Proxy::all(server.url()).unwrap())
+        .build();
+
+    client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 1);
+}
+
+#[tokio::test]
+async fn config_sets_http_options() {
+    let server = MockServer::start().await;
+    let config = Config::from_toml_str(&format!(
+        r#"
+[clients.ark]
+provider = "mock-ark"
+base_url = "http://ark.invalid/api/v3"
+api_key = "k"
+proxy = "{}"
+user_agent = "acme-rag/1.2"
+stream_idle_timeout = "30s"
+headers = {{ x-tenant = "acme" }}
+"#,
+        server.url()
+    ))
+    .unwrap();
+
+    let client: Client = config.client("ark").unwrap();
+    client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    let request = &server.requests_to(paths::ARK_CHAT)[0];
+    assert_eq!(request.header("x-tenant"), Some("acme"));
+    assert_eq!(request.header("user-agent"), Some("acme-rag/1.2"));
+}
+
+#[test]
+fn config_reports_bad_http_options() {
+    let err = Config::from_toml_str(
+        r#"
+[clients.ark]
+provider = "mock-ark"
+api_key = "k"
+proxy = "not a url"
+user_agent = "bad\u0007agent"
+headers = { "x tenant" = "acme", x-ok = "line\nbreak" }
+"#,
+    )
+    .unwrap_err();
+    let ConfigError::Invalid { issues, .. } = &err else {
+        panic!("{err}");
+    };
+    assert_eq!(
+        issues,
+        &[
+            "clients.ark.proxy: `not a url` is not a proxy URL",
+            "clients.ark.headers: `x tenant` is not a header name",
+            "clients.ark.headers.x-ok: not a valid header value",
+            "clients.ark.user_agent: not a valid header value",
+        ]
+    );
+
+    let config = Config::from_toml_str(
+        "[clients.ark]\nprovider = \"mock-ark\"\napi_key = \"k\"\nca_cert = \"/nonexistent/ca.pem\"\n",
+    )
+    .unwrap();
+    let err = config.client::<Client>("ark").unwrap_err();
+    assert!(matches!(err, ConfigError::Read { .. }), "{err}");
+}
@@ -0,0 +1,261 @@
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use rig::completion::{CompletionModel as _, GetTokenUsage};
+use rig::embeddings::EmbeddingModel as _;
+use rig::streaming::StreamedAssistantContent;
+use rig_extend_core::{
+    ClientConfig, Config, ConfigError, FromClientConfig, FromModelConfig, ModelConfig, ModelKind,
+    Profile, ProviderFactory, ProviderRegistry, RerankError, RerankResult, Reranker,
+};
+use rig_extend_mock::{Mock, MockResponse, MockServer, defaults, fixtures, paths};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_REGISTRY_API_KEY_UNSET";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_REGISTRY_BASE_URL_UNSET";
+}
+
+/// A provider from another crate: ranks documents by how many query words they contain.
+#[derive(Clone)]
+struct KeywordReranker;
+
+impl FromClientConfig for KeywordReranker {
+    const PROVIDER: &'static str = "keywords";
+
+    fn from_client_config(_name: &str, _config: &ClientConfig) -> Result<Self, ConfigError> {
+        Ok(Self)
+    }
+}
+
+impl FromModelConfig for KeywordReranker {
+    type Client = Self;
+    const KIND: ModelKind = ModelKind::Rerank;
+
+    fn from_model_config(client: &Self, _config: &ModelConfig) -> Result<Self, ConfigError> {
+        Ok(client.clone())
+    }
+}
+
+impl Reranker for KeywordReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[String],
+        top_n: Option<usize>,
+    ) -> Result<Vec<RerankResult>, RerankError> {
+        let mut results: Vec<RerankResult> = documents
+            .iter()
+            .enumerate()
+            .map(|(index, document)| RerankResult {
+                index,
+                relevance_score: query
+                    .split_whitespace()
+                    .filter(|word| document.contains(word))
+                    .count() as f64,
+                text: None,
+            })
+            .collect();
+        results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        results.truncate(top_n.unwrap_or(results.len()));
+        Ok(results)
+    }
+}
+
+fn registry() -> ProviderRegistry {
+    ProviderRegistry::new()
+        .with(
+            ProviderFactory::new::<rig_extend_core::Client<Ark>>()
+                .completion::<rig_extend_core::CompletionModel<Ark>>()
+                .embedding::<rig_extend_core::EmbeddingModel<Ark>>(),
+        )
+        .with(ProviderFactory::new::<KeywordReranker>().rerank::<KeywordReranker>())
+}
+
+fn config(server: &MockServer) -> Config {
+    Config::from_toml_str(&format!(
+        r#"
+[clients.ark]
+provider = "mock-ark"
+base_url = "{}"
+api_key = "test-key"
+
+[clients.local]
+provider = "keywords"
+
+[models.chat]
+client = "ark"
+kind = "completion"
+model = "doubao-seed"
+max_tokens = 64
+
+[models.vectors]
+client = "ark"
+kind = "embedding"
+model = "doubao-embedding"
+dimensions = 8
+
+[models.ranker]
+client = "local"
+kind = "rerank"
+model = "keywords"
+"#,
+        server.ark_base_url()
+    ))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn builds_models_from_provider_ids() {
+    let server = MockServer::start().await;
+    let registry = registry();
+    assert_eq!(registry.providers(), ["keywords", "mock-ark"]);
+
+    let mut config = ClientConfig::new("mock-ark");
+    config.base_url = Some(server.ark_base_url());
+    config.api_key = Some("test-key".to_string());
+    let client = registry.build_client("tenant-a", &config).unwrap();
+    assert_eq!(client.provider(), "mock-ark");
+    assert!(
+        client
+            .downcast_ref::<rig_extend_core::Client<Ark>>()
+            .is_some()
+    );
+
+    let model = client.completion_model("doubao-seed").unwrap();
+    let response = model.completion_request("hi").send().await.unwrap();
+    assert_eq!(response.usage.input_tokens, 10);
+    assert_eq!(response.raw_response["model"], "doubao-seed");
+    assert_eq!(
+        response.raw_response["choices"][0]["message"]["content"][0]["text"],
+        defaults::DEFAULT_REPLY
+    );
+
+    let mut stream = model.completion_request("hi").stream().await.unwrap();
+    let mut text = String::new();
+    let mut usage = None;
+    while let Some(item) = stream.next().await {
+        match item.unwrap() {
+            StreamedAssistantContent::Text(t) => text.push_str(&t.text),
+            StreamedAssistantContent::Final(response) => usage = response.token_usage(),
+            _ => {}
+        }
+    }
+    assert_eq!(text, defaults::DEFAULT_REPLY);
+    assert_eq!(usage.unwrap().output_tokens, 5);
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 2);
+}
+
+#[tokio::test]
+async fn builds_configured_models_by_name() {
+    let server = MockServer::start().await;
+    let registry = registry();
+    let config = config(&server);
+
+    let chat = registry.completion_model(&config, "chat").unwrap();
+    chat.completion_request("hi").send().await.unwrap();
+    assert_eq!(
+        server.requests_to(paths::ARK_CHAT)[0].json()["max_tokens"],
+        64
+    );
+
+    let vectors = registry.embedding_model(&config, "vectors").unwrap();
+    assert_eq!(vectors.ndims(), 8);
+    let embeddings = vectors.embed_texts(vec!["a".to_string()]).await.unwrap();
+    assert_eq!(embeddings[0].vec.len(), 8);
+
+    let ranker = registry.rerank_model(&config, "ranker").unwrap();
+    let documents = ["cats purr", "dogs bark loudly", "dogs"].map(String::from);
+    let results = ranker
+        .rerank("dogs bark", &documents, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(results[0].index, 1);
+}
+
+#[tokio::test]
+async fn models_on_one_client_share_it() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond_with(|req| {
+        let model = req.json()["model"].as_str().unwrap_or_default().to_string();
+        MockResponse::json(fixtures::chat_completion(&model, "ok"))
+            .delay(Duration::from_millis(150))
+    }));
+    let mut config = config(&server);
+    config
+        .clients
+        .get_mut("ark")
+        .unwrap()
+        .rate_limits
+        .get_or_insert_default()
+        .max_in_flight = Some(1);
+    config.models.insert(
+        "flash".to_string(),
+        ModelConfig::new("ark", ModelKind::Completion, "doubao-seed-flash"),
+    );
+    let registry = registry();
+
+    let ark = |client: &rig_extend_core::DynClient| {
+        client
+            .downcast_ref::<rig_extend_core::Client<Ark>>()
+            .unwrap() as *const _
+    };
+    let client = registry.client(&config, "ark").unwrap();
+    assert_eq!(ark(&client), ark(&registry.client(&config, "ark").unwrap()));
+    assert_eq!(
+        ark(&client),
+        ark(&registry.clone().client(&config, "ark").unwrap())
+    );
+
+    let chat = registry.completion_model(&config, "chat").unwrap();
+    let flash = registry.completion_model(&config, "flash").unwrap();
+    let started = Instant::now();
+    let (a, b) = tokio::join!(
+        chat.completion_request("hi").send(),
+        flash.completion_request("hi").send()
+    );
+    a.unwrap();
+    b.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(300));
+
+    // A changed entry builds a new client.
+    config.clients.get_mut("ark").unwrap().rate_limits = None;
+    assert_ne!(ark(&client), ark(&registry.client(&config, "ark").unwrap()));
+}
+
+#[test]
+fn reports_unknown_and_unsupported_providers() {
+    let registry = registry();
+
+    let err = registry.client_from_env("openai").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unknown provider `openai` (registered: keywords, mock-ark)"
+    );
+
+    let err = registry.client_from_env("mock-ark").unwrap_err();
+    assert!(matches!(err, ConfigError::MissingApiKey { .. }), "{err}");
+
+    let local = registry.client_from_env("keywords").unwrap();
+    let err = local.completion_model("any").err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "provider `keywords` has no completion models"
+    );
+    assert_eq!(
+        registry.get("keywords").unwrap().kinds(),
+        [ModelKind::Rerank]
+    );
+}
+
+#[test]
+#[should_panic(expected = "is not built on the `keywords` client type")]
+fn rejects_models_of_another_client() {
+    let _ = ProviderFactory::new::<KeywordReranker>()
+        .completion::<rig_extend_core::CompletionModel<Ark>>();
+}
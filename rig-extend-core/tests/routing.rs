@@ -0,0 +1,267 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use rig::agent::AgentBuilder;
+use rig::completion::{AssistantContent, CompletionModel as _, Prompt};
+use rig::prelude::*;
+use rig::streaming::StreamedAssistantContent;
+use rig_extend_core::{
+    ErrorClass, FailoverPolicy, Profile, ProviderErrorExt, RoutingModel, Strategy,
+};
+use rig_extend_mock::{Mock, MockResponse, MockServer, defaults, fixtures, paths};
+use serde_json::json;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Qwen;
+
+impl Profile for Qwen {
+    const NAME: &'static str = "mock-qwen";
+    const DISPLAY_NAME: &'static str = "MockQwen";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/compatible-mode/v1";
+    const API_KEY_ENV: &'static str = "MOCK_QWEN_API_KEY";
+    const BASE_URL_ENV: &'static str = "MOCK_QWEN_BASE_URL";
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_API_KEY";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_BASE_URL";
+}
+
+/// `qwen` (DashScope paths) first, then `doubao` (Ark paths), both on one mock server.
+fn router(server: &MockServer) -> RoutingModel {
+    let qwen = rig_extend_core::Client::<Qwen>::builder("qwen-key")
+        .base_url(&server.dashscope_base_url())
+        .build()
+        .completion_model("qwen3-max");
+    let doubao = rig_extend_core::Client::<Ark>::builder("ark-key")
+        .base_url(&server.ark_base_url())
+        .build()
+        .completion_model("doubao-seed");
+    RoutingModel::new()
+        .backend("qwen", qwen)
+        .backend("doubao", doubao)
+}
+
+fn text(choice: &AssistantContent) -> &str {
+    match choice {
+        AssistantContent::Text(t) => &t.text,
+        other => panic!("expected text, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn fails_over_on_server_error() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::error(
+            503,
+            fixtures::openai_error("Service is overloaded", "server_error", "ServerOverloaded"),
+        )),
+    );
+
+    let response = router(&server)
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(text(&response.choice.first()), defaults::DEFAULT_REPLY);
+    assert_eq!(server.requests_to(paths::DASHSCOPE_CHAT).len(), 1);
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 1);
+}
+
+#[tokio::test]
+async fn non_failover_errors_are_returned_immediately() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::error(
+            401,
+            fixtures::openai_error(
+                "Incorrect API key provided.",
+                "invalid_request_error",
+                "invalid_api_key",
+            ),
+        )),
+    );
+
+    let err = router(&server)
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(err.error_class(), Some(ErrorClass::Auth));
+    assert!(server.requests_to(paths::ARK_CHAT).is_empty());
+
+    // Opting in to auth failover sends it on.
+    let router = router(&server).failover(FailoverPolicy::default().classes([ErrorClass::Auth]));
+    router.completion_request("hi").send().await.unwrap();
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 1);
+}
+
+#[tokio::test]
+async fn content_filter_refusal_fails_over() {
+    let server = MockServer::start().await;
+    // Empty refusals surface as content-filter errors, partial ones as responses.
+    for content in ["", "I can't help with"] {
+        let mut refused = fixtures::chat_completion("qwen3-max", content);
+        refused["choices"][0]["finish_reason"] = json!("content_filter");
+        server.mock(
+            Mock::post(paths::DASHSCOPE_CHAT)
+                .respond(MockResponse::json(refused))
+                .once(),
+        );
+    }
+    let router = router(&server);
+
+    for _ in 0..2 {
+        let response = router.completion_request("hi").send().await.unwrap();
+        assert_eq!(response.raw_response.model, "doubao-seed");
+    }
+    assert_eq!(server.requests_to(paths::DASHSCOPE_CHAT).len(), 2);
+
+    // Without failover the empty refusal is a typed error.
+    let model = router.failover(FailoverPolicy::default().classes([]));
+    let mut refused = fixtures::chat_completion("qwen3-max", "");
+    refused["choices"][0]["finish_reason"] = json!("content_filter");
+    server.mock(Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::json(refused)));
+    let err = model.completion_request("hi").send().await.unwrap_err();
+    assert_eq!(err.error_class(), Some(ErrorClass::ContentFilter));
+}
+
+#[tokio::test]
+async fn slow_backend_times_out_and_fails_over() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT).respond(
+            MockResponse::json(fixtures::chat_completion("qwen3-max", "late"))
+                .delay(Duration::from_secs(5)),
+        ),
+    );
+
+    let router = router(&server).attempt_timeout(Duration::from_millis(200));
+    let response = router.completion_request("hi").send().await.unwrap();
+    assert_eq!(text(&response.choice.first()), defaults::DEFAULT_REPLY);
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 1);
+}
+
+#[tokio::test]
+async fn streaming_fails_over_before_first_event() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::error(
+            429,
+            fixtures::dashscope_error("Throttling.RateQuota", "Requests rate limit exceeded"),
+        )),
+    );
+    server.mock(
+        Mock::post(paths::ARK_CHAT).respond(MockResponse::sse(fixtures::chat_stream(
+            "doubao-seed",
+            &["Hel", "lo"],
+        ))),
+    );
+
+    let mut stream = router(&server)
+        .completion_request("hi")
+        .stream()
+        .await
+        .unwrap();
+    let mut text = String::new();
+    let mut finished = false;
+    while let Some(item) = stream.next().await {
+        match item.unwrap() {
+            StreamedAssistantContent::Text(t) => text.push_str(&t.text),
+            StreamedAssistantContent::Final(_) => finished = true,
+            _ => {}
+        }
+    }
+    assert_eq!(text, "Hello");
+    assert!(finished);
+    assert_eq!(server.requests_to(paths::DASHSCOPE_CHAT).len(), 1);
+}
+
+#[tokio::test]
+async fn works_as_agent_model() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT).respond(MockResponse::error(
+            500,
+            fixtures::openai_error("Internal error", "server_error", "InternalError"),
+        )),
+    );
+
+    let agent = AgentBuilder::new(router(&server))
+        .preamble("Be brief.")
+        .build();
+    let reply = agent.prompt("hi").await.unwrap();
+    assert_eq!(reply, defaults::DEFAULT_REPLY);
+}
+
+#[tokio::test]
+async fn weighted_strategy_spreads_load_and_skips_zero_weights() {
+    let server = MockServer::start().await;
+    let client = |profile_url: String| {
+        rig_extend_core::Client::<Qwen>::builder("key")
+            .base_url(&profile_url)
+            .build()
+            .completion_model("qwen3-max")
+    };
+    let doubao = rig_extend_core::Client::<Ark>::builder("key")
+        .base_url(&server.ark_base_url())
+        .build()
+        .completion_model("doubao-seed");
+
+    let router = RoutingModel::new()
+        .weighted_backend("qwen", client(server.dashscope_base_url()), 1)
+        .weighted_backend("doubao", doubao.clone(), 1)
+        .strategy(Strategy::Weighted);
+    for _ in 0..40 {
+        router.completion_request("hi").send().await.unwrap();
+    }
+    assert!(!server.requests_to(paths::DASHSCOPE_CHAT).is_empty());
+    assert!(!server.requests_to(paths::ARK_CHAT).is_empty());
+
+    server.reset();
+    let router = RoutingModel::new()
+        .weighted_backend("qwen", client(server.dashscope_base_url()), 0)
+        .weighted_backend("doubao", doubao, 1)
+        .strategy(Strategy::Weighted);
+    for _ in 0..5 {
+        router.completion_request("hi").send().await.unwrap();
+    }
+    assert!(server.requests_to(paths::DASHSCOPE_CHAT).is_empty());
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 5);
+}
+
+#[tokio::test]
+async fn all_zero_weights_fall_back_to_ordered_routes() {
+    let server = MockServer::start().await;
+    let router = RoutingModel::new()
+        .weighted_backend(
+            "qwen",
+            rig_extend_core::Client::<Qwen>::builder("key")
+                .base_url(&server.dashscope_base_url())
+                .build()
+                .completion_model("qwen3-max"),
+            0,
+        )
+        .weighted_backend(
+            "doubao",
+            rig_extend_core::Client::<Ark>::builder("key")
+                .base_url(&server.ark_base_url())
+                .build()
+                .completion_model("doubao-seed"),
+            0,
+        )
+        .strategy(Strategy::Weighted);
+
+    for _ in 0..3 {
+        router.completion_request("hi").send().await.unwrap();
+    }
+    assert_eq!(server.requests_to(paths::DASHSCOPE_CHAT).len(), 3);
+    assert!(server.requests_to(paths::ARK_CHAT).is_empty());
+}
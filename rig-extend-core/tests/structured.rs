@@ -0,0 +1,174 @@
+use futures::StreamExt;
+use rig::completion::CompletionModel as _;
+use rig::prelude::*;
+use rig_extend_core::{CompletionModel, Profile, ResponseFormat, StructuredOutputError};
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Qwen;
+
+impl Profile for Qwen {
+    const NAME: &'static str = "mock-qwen";
+    const DISPLAY_NAME: &'static str = "MockQwen";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/compatible-mode/v1";
+    const API_KEY_ENV: &'static str = "MOCK_QWEN_API_KEY";
+    const BASE_URL_ENV: &'static str = "MOCK_QWEN_BASE_URL";
+}
+
+#[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+struct Person {
+    name: String,
+    age: u32,
+}
+
+fn qwen(server: &MockServer) -> CompletionModel<Qwen> {
+    rig_extend_core::Client::<Qwen>::builder("qwen-key")
+        .base_url(&server.dashscope_base_url())
+        .build()
+        .completion_model("qwen3-max")
+}
+
+/// Message content as a string or an array of text parts.
+fn text(content: &Value) -> String {
+    match content {
+        Value::Array(parts) => parts.iter().filter_map(|p| p["text"].as_str()).collect(),
+        other => other.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+fn reply(server: &MockServer, content: &str) {
+    server.mock(
+        Mock::post(paths::DASHSCOPE_CHAT)
+            .respond(MockResponse::json(fixtures::chat_completion(
+                "qwen3-max",
+                content,
+            )))
+            .times(1),
+    );
+}
+
+#[tokio::test]
+async fn extract_sends_the_json_schema_and_parses_the_reply() {
+    let server = MockServer::start().await;
+    reply(&server, r#"{"name": "Alice", "age": 30}"#);
+
+    let person = qwen(&server)
+        .structured::<Person>()
+        .preamble("Extract the person.")
+        .extract("Alice turned 30 last week.")
+        .await
+        .unwrap();
+    assert_eq!(
+        person,
+        Person {
+            name: "Alice".into(),
+            age: 30
+        }
+    );
+
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[0].json();
+    let format = &body["response_format"];
+    assert_eq!(format["type"], "json_schema");
+    assert_eq!(format["json_schema"]["name"], "Person");
+    assert_eq!(format["json_schema"]["strict"], false);
+    assert_eq!(
+        format["json_schema"]["schema"]["required"],
+        json!(["name", "age"])
+    );
+    assert!(format["json_schema"]["schema"].get("$schema").is_none());
+    let system = text(&body["messages"][0]["content"]);
+    assert!(system.starts_with("Extract the person."), "{system}");
+    assert!(system.contains("JSON"), "{system}");
+}
+
+#[tokio::test]
+async fn invalid_replies_are_repaired_and_retried() {
+    let server = MockServer::start().await;
+    reply(&server, "Alice is 30.");
+    reply(&server, "```json\n{\"name\": \"Alice\", \"age\": 30}\n```");
+
+    let person = qwen(&server)
+        .structured::<Person>()
+        .extract("Alice turned 30 last week.")
+        .await
+        .unwrap();
+    assert_eq!(person.age, 30);
+
+    let requests = server.requests_to(paths::DASHSCOPE_CHAT);
+    assert_eq!(requests.len(), 2);
+    let messages = requests[1].json()["messages"].clone();
+    assert_eq!(text(&messages[2]["content"]), "Alice is 30.");
+    let repair = text(&messages[3]["content"]);
+    assert!(repair.contains("not valid"), "{repair}");
+}
+
+#[tokio::test]
+async fn gives_up_after_the_retries() {
+    let server = MockServer::start().await;
+    for _ in 0..2 {
+        reply(&server, r#"{"name": "Alice"}"#);
+    }
+
+    let err = qwen(&server)
+        .structured::<Person>()
+        .retries(1)
+        .extract("Alice turned 30 last week.")
+        .await
+        .unwrap_err();
+    let StructuredOutputError::Invalid {
+        schema,
+        attempts,
+        reply,
+        ..
+    } = &err
+    else {
+        panic!("expected an invalid reply, got {err:?}");
+    };
+    assert_eq!((schema.as_str(), *attempts), ("Person", 2));
+    assert_eq!(reply, r#"{"name": "Alice"}"#);
+    assert!(err.to_string().contains("missing field `age`"), "{err}");
+}
+
+#[tokio::test]
+async fn json_object_mode_puts_the_schema_in_the_prompt() {
+    let server = MockServer::start().await;
+    reply(&server, r#"{"name": "Bob", "age": 41}"#);
+
+    qwen(&server)
+        .structured::<Person>()
+        .json_object()
+        .extract("Bob is 41.")
+        .await
+        .unwrap();
+
+    let body = server.requests_to(paths::DASHSCOPE_CHAT)[0].json();
+    assert_eq!(body["response_format"], json!({ "type": "json_object" }));
+    let system = text(&body["messages"][0]["content"]);
+    assert!(system.contains(r#""required":["name","age"]"#), "{system}");
+}
+
+#[tokio::test]
+async fn response_format_applies_to_plain_and_streaming_requests() {
+    let server = MockServer::start().await;
+    let model = qwen(&server).with_response_format(ResponseFormat::JsonObject);
+
+    model.completion_request("hi").send().await.unwrap();
+    let mut stream = model.completion_request("hi").stream().await.unwrap();
+    while stream.next().await.is_some() {}
+
+    // An explicit `additional_params` value wins.
+    model
+        .completion_request("hi")
+        .additional_params(json!({ "response_format": { "type": "text" } }))
+        .send()
+        .await
+        .unwrap();
+
+    let requests = server.requests_to(paths::DASHSCOPE_CHAT);
+    assert_eq!(requests[0].json()["response_format"]["type"], "json_object");
+    assert_eq!(requests[1].json()["response_format"]["type"], "json_object");
+    assert_eq!(requests[2].json()["response_format"]["type"], "text");
+}
@@ -0,0 +1,259 @@
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use rig::client::EmbeddingsClient;
+use rig::completion::CompletionModel as _;
+use rig::embeddings::EmbeddingModel as _;
+use rig::prelude::*;
+use rig_extend_core::{Profile, RateLimiter, RateLimits, RetryPolicy};
+use rig_extend_mock::{Mock, MockResponse, MockServer, Recorder, defaults, fixtures, paths};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_API_KEY";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_BASE_URL";
+}
+
+type Client = rig_extend_core::Client<Ark>;
+
+fn fast_retries() -> RetryPolicy {
+    RetryPolicy::default()
+        .max_attempts(3)
+        .backoff(Duration::from_millis(1), Duration::from_millis(5))
+}
+
+fn overloaded() -> MockResponse {
+    MockResponse::error(
+        503,
+        fixtures::openai_error("Service is overloaded", "server_error", "ServerOverloaded"),
+    )
+}
+
+#[tokio::test]
+async fn retries_retryable_status_then_succeeds() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()).times(2));
+
+    let client = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .retry_policy(fast_retries())
+        .build();
+    let response = client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        response.choice.first(),
+        rig::completion::AssistantContent::Text(t) if t.text == defaults::DEFAULT_REPLY
+    ));
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 3);
+}
+
+#[tokio::test]
+async fn retries_error_statuses_returned_as_responses() {
+    // Record a 503 and the retried success, then replay the 503 as a plain response, the way
+    // backends other than rig's reqwest return error statuses.
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()).once());
+    let path = std::env::temp_dir().join(format!("rig-status-retry-{}.json", std::process::id()));
+    let recorder = Recorder::record(reqwest::Client::new(), &path);
+    let client: rig_extend_core::Client<Ark, Recorder> = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .with_client(recorder.clone())
+        .retry_policy(fast_retries())
+        .build();
+    client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).ok();
+
+    let mut cassette = recorder.cassette();
+    assert_eq!(cassette.interactions.len(), 2);
+    cassette.interactions[0].response.error = false;
+    let replay = Recorder::from_cassette(cassette);
+    let client: rig_extend_core::Client<Ark, Recorder> = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .with_client(replay.clone())
+        .retry_policy(fast_retries())
+        .build();
+    client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    replay.assert_played();
+}
+
+#[tokio::test]
+async fn does_not_retry_client_errors_or_by_default() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_EMBEDDINGS).respond(MockResponse::error(
+            400,
+            fixtures::openai_error("bad input", "invalid_request_error", "InvalidParameter"),
+        )),
+    );
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()));
+
+    let retrying = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .retry_policy(fast_retries())
+        .build();
+    let embed = retrying.embedding_model("e").embed_text("x").await;
+    assert!(embed.is_err());
+    assert_eq!(server.requests_to(paths::ARK_EMBEDDINGS).len(), 1);
+
+    let plain = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .build();
+    let chat = plain
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await;
+    assert!(chat.is_err());
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 1);
+}
+
+#[tokio::test]
+async fn retry_after_header_is_honoured() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_CHAT)
+            .respond(overloaded().status(429).header("retry-after", "1"))
+            .once(),
+    );
+
+    let client = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .retry_policy(fast_retries().backoff(Duration::from_millis(1), Duration::from_secs(2)))
+        .build();
+    let started = Instant::now();
+    client
+        .completion_model("m")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn streaming_retries_before_the_response_starts() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(overloaded()).once());
+
+    let client = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .retry_policy(fast_retries())
+        .build();
+    let mut stream = client
+        .completion_model("m")
+        .completion_request("hi")
+        .stream()
+        .await
+        .unwrap();
+
+    let mut text = String::new();
+    while let Some(item) = stream.next().await {
+        if let rig::streaming::StreamedAssistantContent::Text(t) = item.unwrap() {
+            text.push_str(&t.text);
+        }
+    }
+    assert_eq!(text, defaults::DEFAULT_REPLY);
+    assert_eq!(server.requests_to(paths::ARK_CHAT).len(), 2);
+}
+
+#[test]
+fn backoff_is_capped_without_overflowing() {
+    let policy = RetryPolicy::default()
+        .max_attempts(200)
+        .backoff(Duration::from_millis(500), Duration::from_secs(30))
+        .jitter(false);
+    assert_eq!(policy.delay(1, None), Duration::from_millis(500));
+    assert_eq!(policy.delay(3, None), Duration::from_secs(2));
+    for attempt in [67, 100, 199, u32::MAX] {
+        assert_eq!(policy.delay(attempt, None), Duration::from_secs(30));
+    }
+
+    for multiplier in [-2.0, f64::NAN, f64::INFINITY] {
+        let policy = policy.clone().multiplier(multiplier);
+        for attempt in 1..200 {
+            assert!(policy.delay(attempt, None) <= Duration::from_secs(30));
+        }
+    }
+    let jittered = policy.jitter(true);
+    assert!(jittered.delay(150, None) <= Duration::from_secs(30));
+}
+
+#[tokio::test]
+async fn max_in_flight_is_shared_across_models() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_EMBEDDINGS).respond_with(|req| {
+        let input = req.json()["input"][0]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        MockResponse::json(fixtures::openai_embeddings(
+            "e",
+            &[fixtures::embedding(&input, 4)],
+        ))
+        .delay(Duration::from_millis(150))
+    }));
+
+    let client = Client::builder("k")
+        .base_url(&server.ark_base_url())
+        .max_in_flight(1)
+        .build();
+    let (a, b) = (client.embedding_model("a"), client.embedding_model("b"));
+
+    let started = Instant::now();
+    let (ra, rb) = tokio::join!(a.embed_text("x"), b.embed_text("y"));
+    ra.unwrap();
+    rb.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+async fn requests_per_minute_waits_for_capacity() {
+    let limiter = RateLimiter::new(RateLimits::default().requests_per_minute(60));
+
+    // The bucket starts full: a minute's worth of requests go through at once...
+    let started = tokio::time::Instant::now();
+    for _ in 0..60 {
+        limiter.acquire(0).await;
+    }
+    assert_eq!(started.elapsed(), Duration::ZERO);
+
+    // ...then capacity refills at one request per second.
+    limiter.acquire(0).await;
+    let waited = started.elapsed();
+    assert!(waited >= Duration::from_millis(999) && waited < Duration::from_millis(1100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn tokens_per_minute_counts_estimated_tokens() {
+    let limiter = RateLimiter::new(RateLimits::default().tokens_per_minute(600));
+    let body = br#"{"model":"m","messages":[],"max_tokens":400}"#;
+    let cost = limiter.cost(body);
+    assert!(cost > 400, "{cost}");
+
+    let started = tokio::time::Instant::now();
+    limiter.acquire(cost).await;
+    limiter.acquire(cost).await;
+    // The second request waited for `cost - (600 - cost)` tokens at 10 tokens/s.
+    let expected = Duration::from_secs_f64(f64::from(2 * cost - 600) / 10.0);
+    assert!(started.elapsed() >= expected - Duration::from_millis(10));
+}
@@ -0,0 +1,186 @@
+use futures::StreamExt;
+use rig::completion::{CompletionModel as _, GetTokenUsage};
+use rig::embeddings::EmbeddingModel as _;
+use rig::prelude::*;
+use rig::streaming::StreamedAssistantContent;
+use rig_extend_core::{
+    ModelPrice, Operation, PriceTable, Profile, TokenUsage, UsageLedger, UsageSnapshot,
+};
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+use serde_json::json;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Ark;
+
+impl Profile for Ark {
+    const NAME: &'static str = "mock-ark";
+    const DISPLAY_NAME: &'static str = "MockArk";
+    const BASE_URL: &'static str = "http://127.0.0.1:9/api/v3";
+    const API_KEY_ENV: &'static str = "MOCK_ARK_API_KEY";
+    const BASE_URL_ENV: &'static str = "MOCK_ARK_BASE_URL";
+}
+
+type Client = rig_extend_core::Client<Ark>;
+
+fn client(server: &MockServer, ledger: &UsageLedger) -> Client {
+    Client::builder("test-key")
+        .base_url(&server.ark_base_url())
+        .usage_ledger(ledger.clone())
+        .build()
+}
+
+#[test]
+fn reads_cached_and_reasoning_tokens() {
+    let usage = TokenUsage::from_openai(&json!({
+        "prompt_tokens": 120,
+        "completion_tokens": 80,
+        "total_tokens": 200,
+        "prompt_tokens_details": { "cached_tokens": 100 },
+        "completion_tokens_details": { "reasoning_tokens": 60 },
+    }));
+    assert_eq!(
+        usage,
+        TokenUsage {
+            prompt_tokens: 120,
+            completion_tokens: 80,
+            cached_tokens: 100,
+            reasoning_tokens: 60,
+            total_tokens: 200,
+        }
+    );
+
+    // DashScope native spelling
+    let usage = TokenUsage::from_openai(&json!({ "input_tokens": 7, "output_tokens": 3 }));
+    assert_eq!((usage.prompt_tokens, usage.total_tokens), (7, 10));
+}
+
+#[test]
+fn prices_cached_input_separately() {
+    let price = ModelPrice::per_million(2.0, 8.0).cached_input(0.5);
+    let usage = TokenUsage {
+        prompt_tokens: 1_000_000,
+        completion_tokens: 500_000,
+        cached_tokens: 400_000,
+        ..TokenUsage::default()
+    };
+    // 600k * 2 + 400k * 0.5 + 500k * 8, per million
+    assert!((price.cost(&usage) - 5.4).abs() < 1e-9);
+    assert!((ModelPrice::per_million(2.0, 8.0).cost(&usage) - 6.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn aggregates_per_model_operation_and_tag() {
+    let server = MockServer::start().await;
+    let ledger = UsageLedger::new().with_prices(
+        PriceTable::new("CNY").model("doubao-seed", ModelPrice::per_million(0.8, 2.0)),
+    );
+    let client = client(&server, &ledger);
+
+    let chat = client.completion_model("doubao-seed");
+    chat.completion_request("hi").send().await.unwrap();
+    client
+        .with_usage_tag("search")
+        .completion_model("doubao-seed")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    client
+        .embedding_model("doubao-embedding")
+        .embed_texts(vec!["a".to_string(), "b".to_string()])
+        .await
+        .unwrap();
+
+    let snapshot = ledger.snapshot();
+    assert_eq!(snapshot.currency.as_deref(), Some("CNY"));
+    assert_eq!(snapshot.entries.len(), 3);
+
+    let by_model = snapshot.by_model();
+    let chat_totals = &by_model["doubao-seed"];
+    assert_eq!(chat_totals.requests, 2);
+    assert_eq!(chat_totals.usage.prompt_tokens, 20);
+    assert_eq!(chat_totals.usage.completion_tokens, 10);
+    assert!((chat_totals.cost - (20.0 * 0.8 + 10.0 * 2.0) / 1e6).abs() < 1e-12);
+
+    let embedding = &by_model["doubao-embedding"];
+    assert_eq!(embedding.usage.prompt_tokens, 8);
+    let unpriced = snapshot
+        .entries
+        .iter()
+        .find(|e| e.model == "doubao-embedding")
+        .unwrap();
+    assert_eq!(unpriced.cost, None);
+    assert_eq!(unpriced.provider, "mock-ark");
+
+    assert_eq!(snapshot.by_operation()[&Operation::Chat].requests, 2);
+    assert_eq!(snapshot.by_operation()[&Operation::Embedding].requests, 1);
+    let by_tag = snapshot.by_tag();
+    assert_eq!(by_tag.len(), 1);
+    assert_eq!(by_tag["search"].usage.total_tokens, 15);
+    assert_eq!(snapshot.total().usage.total_tokens, 38);
+}
+
+#[tokio::test]
+async fn streaming_usage_is_recorded_and_reported() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_CHAT).respond(MockResponse::sse(fixtures::chat_stream(
+            "doubao-seed",
+            &["Hel", "lo"],
+        ))),
+    );
+    let ledger = UsageLedger::new();
+    let model = client(&server, &ledger).completion_model("doubao-seed");
+
+    let mut stream = model.completion_request("hi").stream().await.unwrap();
+    let mut text = String::new();
+    let mut final_usage = None;
+    while let Some(item) = stream.next().await {
+        match item.unwrap() {
+            StreamedAssistantContent::Text(t) => text.push_str(&t.text),
+            StreamedAssistantContent::Final(response) => final_usage = response.token_usage(),
+            _ => {}
+        }
+    }
+    assert_eq!(text, "Hello");
+
+    // The usage chunk has no choices; it still reaches rig's final response.
+    let final_usage = final_usage.unwrap();
+    assert_eq!(
+        (final_usage.input_tokens, final_usage.output_tokens),
+        (10, 5)
+    );
+
+    let entry = &ledger.snapshot().entries[0];
+    assert_eq!(entry.operation, Operation::ChatStreaming);
+    assert_eq!(entry.requests, 1);
+    assert_eq!(entry.usage.total_tokens, 15);
+}
+
+#[tokio::test]
+async fn snapshots_export_and_take_resets() {
+    let server = MockServer::start().await;
+    let ledger = UsageLedger::new();
+    let model = client(&server, &ledger).completion_model("doubao-seed");
+    model.completion_request("hi").send().await.unwrap();
+
+    let snapshot = ledger.take();
+    let exported = serde_json::to_string(&snapshot).unwrap();
+    assert!(exported.contains("\"operation\":\"chat\""), "{exported}");
+    assert_eq!(
+        serde_json::from_str::<UsageSnapshot>(&exported).unwrap(),
+        snapshot
+    );
+    assert!(ledger.snapshot().entries.is_empty());
+
+    // Clients without a ledger record nothing.
+    Client::builder("test-key")
+        .base_url(&server.ark_base_url())
+        .build()
+        .completion_model("doubao-seed")
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    assert!(ledger.snapshot().entries.is_empty());
+}
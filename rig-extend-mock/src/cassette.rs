@@ -0,0 +1,562 @@
+//! Category: cassette.rs (record/replay of HTTP interactions around any HttpClientExt)
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use axum::http::{StatusCode, request::Parts};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use bytes::Bytes;
+use futures::StreamExt;
+use rig::http_client::sse::BoxedStream;
+use rig::http_client::{
+    self, Error, HeaderMap, HttpClientExt, LazyBody, Request, Response, StreamingResponse,
+};
+use rig::wasm_compat::{WasmCompatSend, WasmCompatSendStream};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Environment variable that switches [`Recorder::from_env`] to record mode (`record`).
+pub const CASSETTE_MODE_ENV: &str = "RIG_CASSETTE";
+
+/// Request headers whose values are never written to a cassette.
+const REDACTED_HEADERS: &[&str] = &["authorization", "x-api-key", "api-key"];
+const REDACTED: &str = "<REDACTED>";
+
+#[derive(Debug, thiserror::Error)]
+pub enum CassetteError {
+    #[error("cassette io error ({path}): {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("cassette json error ({path}): {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("cassette mismatch for {method} {uri}: {reason}")]
+    Mismatch {
+        method: String,
+        uri: String,
+        reason: String,
+    },
+    #[error("cassette cannot {0} in replay mode")]
+    Unsupported(&'static str),
+}
+
+/// Body stored as UTF-8 text when possible, base64 otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "encoding", content = "data", rename_all = "snake_case")]
+pub enum RecordedBody {
+    Text(String),
+    Base64(String),
+}
+
+impl RecordedBody {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::Text(text.to_string()),
+            Err(_) => Self::Base64(BASE64.encode(bytes)),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        match self {
+            Self::Text(text) => Bytes::from(text.clone()),
+            Self::Base64(data) => BASE64.decode(data).unwrap_or_default().into(),
+        }
+    }
+
+    /// Equal as JSON when both sides parse, byte-for-byte otherwise.
+    fn matches(&self, other: &[u8]) -> bool {
+        let mine = self.to_bytes();
+        match (
+            serde_json::from_slice::<Value>(&mine),
+            serde_json::from_slice::<Value>(other),
+        ) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => mine == other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub uri: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: RecordedBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: RecordedBody,
+    /// Recorded from `send_streaming`; replayed as a stream of SSE events.
+    #[serde(default)]
+    pub streaming: bool,
+    /// The wrapped client returned the status as an error (rig's reqwest backend does this
+    /// for non-2xx); replay returns the same error.
+    #[serde(default)]
+    pub error: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// Ordered list of recorded interactions, stored as pretty-printed JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CassetteError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| CassetteError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        serde_json::from_str(&text).map_err(|source| CassetteError::Json {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CassetteError> {
+        let path = path.as_ref();
+        let io = |source| CassetteError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(io)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|source| CassetteError::Json {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        std::fs::write(path, json + "\n").map_err(io)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Send through the wrapped client and append every interaction to the cassette.
+    Record,
+    /// Answer from the cassette; the wrapped client is never called.
+    Replay,
+}
+
+#[derive(Debug)]
+struct State {
+    cassette: Cassette,
+    played: Vec<bool>,
+    path: Option<PathBuf>,
+}
+
+impl State {
+    fn record(&mut self, interaction: Interaction) {
+        self.cassette.interactions.push(interaction);
+        self.played.push(true);
+        if let Some(path) = &self.path
+            && let Err(err) = self.cassette.save(path)
+        {
+            tracing::error!(target: "rig::cassette", "failed to save cassette: {err}");
+        }
+    }
+
+    /// First unplayed interaction matching the request, marked as played.
+    fn replay(
+        &mut self,
+        method: &str,
+        uri: &str,
+        body: &[u8],
+    ) -> Result<RecordedResponse, CassetteError> {
+        let found = self
+            .cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .find(|(i, it)| {
+                !self.played[*i]
+                    && it.request.method == method
+                    && it.request.uri == uri
+                    && it.request.body.matches(body)
+            })
+            .map(|(i, it)| (i, it.response.clone()));
+
+        if let Some((i, response)) = found {
+            self.played[i] = true;
+            return Ok(response);
+        }
+
+        let reason = match self
+            .cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .find(|(i, _)| !self.played[*i])
+        {
+            Some((i, next)) => format!(
+                "no unplayed interaction matches; next unplayed is #{i} {} {} with body {}",
+                next.request.method,
+                next.request.uri,
+                preview(&next.request.body.to_bytes()),
+            ),
+            None => "all recorded interactions have been played".to_string(),
+        };
+        let err = CassetteError::Mismatch {
+            method: method.to_string(),
+            uri: uri.to_string(),
+            reason: format!("{reason}; request body {}", preview(body)),
+        };
+        tracing::error!(target: "rig::cassette", "{err}");
+        Err(err)
+    }
+}
+
+fn preview(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    match text.char_indices().nth(200) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.into_owned(),
+    }
+}
+
+fn header_map(headers: &HeaderMap, redact: bool) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = name.as_str().to_ascii_lowercase();
+            let value = if redact && REDACTED_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// `HttpClientExt` wrapper that records interactions to a cassette file, or replays them.
+///
+/// Use it as the `T` of any client: `Client::builder(key).with_client(recorder).build()`.
+/// Credentials in `Authorization` (and `api-key` style headers) are redacted before writing.
+/// In replay mode a request that matches no unplayed interaction (method, URI and body,
+/// JSON-aware) fails with [`CassetteError::Mismatch`]; call [`Recorder::assert_played`] at
+/// the end of a test to also catch interactions that were never requested.
+#[derive(Debug, Clone)]
+pub struct Recorder<T = reqwest::Client> {
+    inner: T,
+    mode: Mode,
+    state: Arc<Mutex<State>>,
+}
+
+impl<T> Recorder<T> {
+    /// Record through `inner`, (re)writing the cassette at `path` after every interaction.
+    pub fn record(inner: T, path: impl Into<PathBuf>) -> Self {
+        Self::with_state(inner, Mode::Record, Cassette::default(), Some(path.into()))
+    }
+
+    /// Replay the cassette stored at `path`.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, CassetteError>
+    where
+        T: Default,
+    {
+        let cassette = Cassette::load(path)?;
+        Ok(Self::from_cassette(cassette))
+    }
+
+    /// Replay an in-memory cassette.
+    pub fn from_cassette(cassette: Cassette) -> Self
+    where
+        T: Default,
+    {
+        Self::with_state(T::default(), Mode::Replay, cassette, None)
+    }
+
+    /// Record when `RIG_CASSETTE=record`, replay otherwise.
+    pub fn from_env(inner: T, path: impl Into<PathBuf>) -> Result<Self, CassetteError> {
+        let path = path.into();
+        if std::env::var(CASSETTE_MODE_ENV).is_ok_and(|mode| mode.eq_ignore_ascii_case("record")) {
+            Ok(Self::record(inner, path))
+        } else {
+            let cassette = Cassette::load(&path)?;
+            Ok(Self::with_state(inner, Mode::Replay, cassette, None))
+        }
+    }
+
+    fn with_state(inner: T, mode: Mode, cassette: Cassette, path: Option<PathBuf>) -> Self {
+        let played = vec![false; cassette.interactions.len()];
+        Self {
+            inner,
+            mode,
+            state: Arc::new(Mutex::new(State {
+                cassette,
+                played,
+                path,
+            })),
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Snapshot of the cassette (everything recorded so far in record mode).
+    pub fn cassette(&self) -> Cassette {
+        self.state.lock().unwrap().cassette.clone()
+    }
+
+    /// Panic if any recorded interaction was not replayed.
+    pub fn assert_played(&self) {
+        let state = self.state.lock().unwrap();
+        let unplayed: Vec<String> = state
+            .cassette
+            .interactions
+            .iter()
+            .zip(&state.played)
+            .filter(|(_, played)| !**played)
+            .map(|(it, _)| format!("{} {}", it.request.method, it.request.uri))
+            .collect();
+        assert!(
+            unplayed.is_empty(),
+            "cassette has unplayed interactions: {unplayed:?}"
+        );
+    }
+
+    fn recorded_request(parts: &Parts, body: &[u8]) -> RecordedRequest {
+        RecordedRequest {
+            method: parts.method.to_string(),
+            uri: parts.uri.to_string(),
+            headers: header_map(&parts.headers, true),
+            body: RecordedBody::from_bytes(body),
+        }
+    }
+
+    fn replay_response(&self, parts: &Parts, body: &[u8]) -> http_client::Result<RecordedResponse> {
+        let response =
+            self.state
+                .lock()
+                .unwrap()
+                .replay(parts.method.as_str(), &parts.uri.to_string(), body);
+        let response = response.map_err(|e| Error::Instance(Box::new(e)))?;
+        if response.error {
+            let status =
+                StatusCode::from_u16(response.status).map_err(|e| Error::Instance(Box::new(e)))?;
+            let text = String::from_utf8_lossy(&response.body.to_bytes()).into_owned();
+            return Err(Error::InvalidStatusCodeWithMessage(status, text));
+        }
+        Ok(response)
+    }
+}
+
+/// Default is an empty replay cassette: every request fails loudly.
+impl<T: Default> Default for Recorder<T> {
+    fn default() -> Self {
+        Self::from_cassette(Cassette::default())
+    }
+}
+
+fn build_response<B>(recorded: &RecordedResponse, body: B) -> http_client::Result<Response<B>> {
+    let mut builder = Response::builder().status(recorded.status);
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name, value);
+    }
+    builder.body(body).map_err(Error::Protocol)
+}
+
+/// Split a recorded SSE body back into one chunk per event.
+fn sse_chunks(body: Bytes) -> Vec<http_client::Result<Bytes>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for i in 0..body.len() {
+        if body[i..].starts_with(b"\n\n") {
+            chunks.push(Ok(body.slice(start..i + 2)));
+            start = i + 2;
+        }
+    }
+    if start < body.len() {
+        chunks.push(Ok(body.slice(start..)));
+    }
+    chunks
+}
+
+fn error_response(request: RecordedRequest, err: &Error, streaming: bool) -> Option<Interaction> {
+    let Error::InvalidStatusCodeWithMessage(status, text) = err else {
+        return None;
+    };
+    Some(Interaction {
+        request,
+        response: RecordedResponse {
+            status: status.as_u16(),
+            headers: BTreeMap::new(),
+            body: RecordedBody::Text(text.clone()),
+            streaming,
+            error: true,
+        },
+    })
+}
+
+impl<T> HttpClientExt for Recorder<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    fn send<B, U>(
+        &self,
+        req: Request<B>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        B: Into<Bytes>,
+        B: WasmCompatSend,
+        U: From<Bytes>,
+        U: WasmCompatSend + 'static,
+    {
+        let (parts, body) = req.into_parts();
+        let body: Bytes = body.into();
+        let recorder = self.clone();
+
+        async move {
+            if recorder.mode == Mode::Replay {
+                let recorded = recorder.replay_response(&parts, &body)?;
+                let bytes = recorded.body.to_bytes();
+                return build_response(
+                    &recorded,
+                    Box::pin(async move { Ok(U::from(bytes)) }) as LazyBody<U>,
+                );
+            }
+
+            let request = Self::recorded_request(&parts, &body);
+            let result = recorder
+                .inner
+                .send::<Bytes, Bytes>(Request::from_parts(parts, body))
+                .await;
+            let response = match result {
+                Ok(response) => response,
+                Err(err) => {
+                    if let Some(interaction) = error_response(request, &err, false) {
+                        recorder.state.lock().unwrap().record(interaction);
+                    }
+                    return Err(err);
+                }
+            };
+
+            let (parts, lazy) = response.into_parts();
+            let bytes = lazy.await?;
+            recorder.state.lock().unwrap().record(Interaction {
+                request,
+                response: RecordedResponse {
+                    status: parts.status.as_u16(),
+                    headers: header_map(&parts.headers, false),
+                    body: RecordedBody::from_bytes(&bytes),
+                    streaming: false,
+                    error: false,
+                },
+            });
+            let body: LazyBody<U> = Box::pin(async move { Ok(U::from(bytes)) });
+            Ok(Response::from_parts(parts, body))
+        }
+    }
+
+    fn send_multipart<U>(
+        &self,
+        req: Request<reqwest::multipart::Form>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        U: From<Bytes>,
+        U: WasmCompatSend + 'static,
+    {
+        // Multipart bodies are opaque; they pass through when recording and are not replayable.
+        let mode = self.mode;
+        let response = self.inner.send_multipart(req);
+        async move {
+            if mode == Mode::Replay {
+                return Err(Error::Instance(Box::new(CassetteError::Unsupported(
+                    "send multipart requests",
+                ))));
+            }
+            response.await
+        }
+    }
+
+    fn send_streaming<B>(
+        &self,
+        req: Request<B>,
+    ) -> impl Future<Output = http_client::Result<StreamingResponse>> + WasmCompatSend
+    where
+        B: Into<Bytes>,
+    {
+        let (parts, body) = req.into_parts();
+        let body: Bytes = body.into();
+        let recorder = self.clone();
+
+        async move {
+            if recorder.mode == Mode::Replay {
+                let recorded = recorder.replay_response(&parts, &body)?;
+                let stream: BoxedStream =
+                    Box::pin(futures::stream::iter(sse_chunks(recorded.body.to_bytes())));
+                return build_response(&recorded, stream);
+            }
+
+            let request = Self::recorded_request(&parts, &body);
+            let result = recorder
+                .inner
+                .send_streaming(Request::from_parts(parts, body))
+                .await;
+            let response = match result {
+                Ok(response) => response,
+                Err(err) => {
+                    if let Some(interaction) = error_response(request, &err, true) {
+                        recorder.state.lock().unwrap().record(interaction);
+                    }
+                    return Err(err);
+                }
+            };
+
+            // Tee the chunks and record once the stream is exhausted.
+            let (parts, stream) = response.into_parts();
+            let status = parts.status.as_u16();
+            let headers = header_map(&parts.headers, false);
+            let buffer = Arc::new(Mutex::new(Vec::new()));
+            let tee = buffer.clone();
+            let state = recorder.state.clone();
+            let finish = futures::stream::once(async move {
+                let bytes = std::mem::take(&mut *buffer.lock().unwrap());
+                state.lock().unwrap().record(Interaction {
+                    request,
+                    response: RecordedResponse {
+                        status,
+                        headers,
+                        body: RecordedBody::from_bytes(&bytes),
+                        streaming: true,
+                        error: false,
+                    },
+                });
+                None
+            });
+            let stream: std::pin::Pin<
+                Box<dyn WasmCompatSendStream<InnerItem = http_client::Result<Bytes>>>,
+            > = Box::pin(
+                stream
+                    .inspect(move |chunk| {
+                        if let Ok(bytes) = chunk {
+                            tee.lock().unwrap().extend_from_slice(bytes);
+                        }
+                    })
+                    .map(Some)
+                    .chain(finish)
+                    .filter_map(futures::future::ready),
+            );
+            Ok(Response::from_parts(parts, stream))
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Category: defaults.rs (built-in provider imitations used when no scripted mock matches)
+//!
+//! - `*/chat/completions`: a fixed reply ([`DEFAULT_REPLY`]), streamed when `stream` is true
+//! - `*/embeddings`, `/embed`: [`fixtures::embedding`] per input
+//! - DashScope rerank and TEI `/rerank`: lexical-overlap scores, best first
+//! - TEI `/embed` and `/rerank` report 4 tokens per input in `x-compute-tokens`
+//! - DashScope image synthesis: a pending task ([`fixtures::TASK_ID`]) that has succeeded by
+//!   the first poll, with one image served under [`paths::FILES`]
+//! - Ark `/images/generations`: one [`fixtures::IMAGE_PNG`] image, as a URL under
+//!   [`paths::FILES`] or as `b64_json`
+//! - TEI `/predict`: fixed positive/negative labels
+//! - TEI `/health`: 200 with an empty body
+//! - `*/models`: empty model list
+
+use serde_json::{Value, json};
+
+use super::fixtures;
+use super::mock::{MockResponse, ReceivedRequest};
+use super::paths;
+
+/// Text of the default chat reply.
+pub const DEFAULT_REPLY: &str = "Hello from the mock server.";
+/// Chunks the default reply is streamed in.
+pub const DEFAULT_REPLY_PARTS: &[&str] = &["Hello", " from the", " mock server."];
+
+/// Respond to `request` the way the imitated provider endpoint would.
+pub fn respond(request: &ReceivedRequest) -> MockResponse {
+    let body = request.json();
+    let path = request.path.as_str();
+
+    if path.ends_with("/chat/completions") {
+        chat(&body)
+    } else if path.ends_with("/embeddings") {
+        openai_embeddings(&body)
+    } else if path.ends_with("/models") {
+        MockResponse::json(json!({ "object": "list", "data": [] }))
+    } else if path == paths::DASHSCOPE_RERANK {
+        dashscope_rerank(&body)
+    } else if path == paths::ARK_IMAGES {
+        ark_images(request, &body)
+    } else if path == paths::DASHSCOPE_IMAGE_SYNTHESIS {
+        dashscope_image_synthesis(request)
+    } else if let Some(task_id) = path
+        .strip_prefix(paths::DASHSCOPE_TASKS)
+        .and_then(|rest| rest.strip_prefix('/'))
+    {
+        dashscope_task(request, task_id)
+    } else if path.starts_with(paths::FILES) {
+        MockResponse::bytes("image/png", fixtures::IMAGE_PNG)
+    } else if path == paths::TEI_EMBED {
+        tei_embed(&body)
+    } else if path == paths::TEI_RERANK {
+        tei_rerank(&body)
+    } else if path == paths::TEI_PREDICT {
+        tei_predict(&body)
+    } else if path == paths::TEI_HEALTH {
+        MockResponse::empty(200)
+    } else {
+        MockResponse::error(
+            404,
+            fixtures::openai_error(
+                &format!("no mock for {} {}", request.method, request.path),
+                "not_found",
+                "NotFound",
+            ),
+        )
+    }
+}
+
+fn chat(body: &Value) -> MockResponse {
+    let model = body["model"].as_str().unwrap_or("mock-model");
+    if body["stream"].as_bool().unwrap_or(false) {
+        MockResponse::sse(fixtures::chat_stream(model, DEFAULT_REPLY_PARTS))
+    } else {
+        MockResponse::json(fixtures::chat_completion(model, DEFAULT_REPLY))
+    }
+}
+
+/// `input`/`inputs` as a list of strings (a single string counts as one input).
+fn inputs(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(text) => vec![text.clone()],
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map_or_else(|| item.to_string(), str::to_string)
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn openai_embeddings(body: &Value) -> MockResponse {
+    let model = body["model"].as_str().unwrap_or("mock-embedding");
+    let dims = body["dimensions"]
+        .as_u64()
+        .map_or(fixtures::DEFAULT_DIMS, |d| d as usize);
+    let vectors: Vec<Vec<f64>> = inputs(&body["input"])
+        .iter()
+        .map(|text| fixtures::embedding(text, dims))
+        .collect();
+    MockResponse::json(fixtures::openai_embeddings(model, &vectors))
+}
+
+fn tei_embed(body: &Value) -> MockResponse {
+    let vectors: Vec<Vec<f64>> = inputs(&body["inputs"])
+        .iter()
+        .map(|text| fixtures::embedding(text, fixtures::DEFAULT_DIMS))
+        .collect();
+    MockResponse::json(fixtures::tei_embeddings(&vectors))
+        .header("x-compute-tokens", &(vectors.len() * 4).to_string())
+}
+
+/// Share of the query's characters that occur in `document`, in `[0, 1]`.
+fn overlap_score(query: &str, document: &str) -> f64 {
+    let document = document.to_lowercase();
+    let chars: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+    let hits = chars.iter().filter(|c| document.contains(**c)).count();
+    hits as f64 / chars.len() as f64
+}
+
+/// `(index, score)` pairs sorted best first, ties by index, truncated to `top_n`.
+fn rank(query: &str, documents: &[String], top_n: Option<usize>) -> Vec<(usize, f64)> {
+    let mut ranked: Vec<(usize, f64)> = documents
+        .iter()
+        .enumerate()
+        .map(|(index, document)| (index, overlap_score(query, document)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    if let Some(n) = top_n {
+        ranked.truncate(n);
+    }
+    ranked
+}
+
+fn dashscope_rerank(body: &Value) -> MockResponse {
+    let query = body["input"]["query"].as_str().unwrap_or_default();
+    let documents = inputs(&body["input"]["documents"]);
+    if query.is_empty() || documents.is_empty() {
+        return MockResponse::error(
+            400,
+            fixtures::dashscope_error("InvalidParameter", "query and documents are required"),
+        );
+    }
+
+    let top_n = body["parameters"]["top_n"].as_u64().map(|n| n as usize);
+    let return_documents = body["parameters"]["return_documents"]
+        .as_bool()
+        .unwrap_or(false);
+    let results: Vec<(usize, f64, Option<&str>)> = rank(query, &documents, top_n)
+        .into_iter()
+        .map(|(index, score)| {
+            let text = return_documents.then(|| documents[index].as_str());
+            (index, score, text)
+        })
+        .collect();
+    MockResponse::json(fixtures::dashscope_rerank(&results))
+}
+
+fn dashscope_image_synthesis(request: &ReceivedRequest) -> MockResponse {
+    if request.header("x-dashscope-async") != Some("enable") {
+        return MockResponse::error(
+            403,
+            fixtures::dashscope_error(
+                "AccessDenied",
+                "current user api does not support synchronous calls",
+            ),
+        );
+    }
+    MockResponse::json(fixtures::dashscope_task(fixtures::TASK_ID, "PENDING"))
+}
+
+fn ark_images(request: &ReceivedRequest, body: &Value) -> MockResponse {
+    let model = body["model"].as_str().unwrap_or("mock-seedream");
+    if body["response_format"] == "b64_json" {
+        return MockResponse::json(fixtures::ark_images_b64(model, &[fixtures::IMAGE_PNG]));
+    }
+    MockResponse::json(fixtures::ark_images(model, &[&file_url(request, "0.png")]))
+}
+
+/// URL of a generated image served by this mock.
+fn file_url(request: &ReceivedRequest, name: &str) -> String {
+    let host = request.header("host").unwrap_or("127.0.0.1");
+    format!("http://{host}{}/{name}", paths::FILES)
+}
+
+fn dashscope_task(request: &ReceivedRequest, task_id: &str) -> MockResponse {
+    let url = file_url(request, &format!("{task_id}.png"));
+    MockResponse::json(fixtures::dashscope_image_task(task_id, &[&url]))
+}
+
+fn tei_rerank(body: &Value) -> MockResponse {
+    let query = body["query"].as_str().unwrap_or_default();
+    let texts = inputs(&body["texts"]);
+    if texts.is_empty() {
+        return MockResponse::error(
+            422,
+            fixtures::tei_error("`texts` cannot be empty", "Validation"),
+        );
+    }
+
+    let top_n = body["top_n"].as_u64().map(|n| n as usize);
+    let return_text = body["return_text"].as_bool().unwrap_or(false);
+    let results: Vec<(usize, f64, Option<&str>)> = rank(query, &texts, top_n)
+        .into_iter()
+        .map(|(index, score)| (index, score, return_text.then(|| texts[index].as_str())))
+        .collect();
+    MockResponse::json(fixtures::tei_rerank(&results))
+        .header("x-compute-tokens", &(texts.len() * 4).to_string())
+}
+
+fn tei_predict(body: &Value) -> MockResponse {
+    let labels = [("positive", 0.9), ("negative", 0.1)];
+    match &body["inputs"] {
+        Value::Array(items) => {
+            let batch: Vec<Value> = items
+                .iter()
+                .map(|_| fixtures::tei_predict(&labels))
+                .collect();
+            MockResponse::json(Value::Array(batch))
+        }
+        _ => MockResponse::json(fixtures::tei_predict(&labels)),
+    }
+}
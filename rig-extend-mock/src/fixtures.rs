@@ -0,0 +1,369 @@
+//! Category: fixtures.rs (provider payload builders)
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Value, json};
+
+/// Request id returned in mock payloads.
+pub const REQUEST_ID: &str = "mock-request-id";
+/// Dimensions of default embeddings when the request does not ask for any.
+pub const DEFAULT_DIMS: usize = 8;
+
+/// Reasoning tokens reported (within the completion tokens) by the reasoning fixtures.
+pub const REASONING_TOKENS: u64 = 3;
+
+/// Task id of the default DashScope image synthesis task.
+pub const TASK_ID: &str = "mock-task-id";
+/// Bytes served for generated images: a PNG signature and an empty `IHDR` header.
+pub const IMAGE_PNG: &[u8] = &[
+    0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D, b'I', b'H', b'D', b'R',
+];
+
+const CREATED: u64 = 1_700_000_000;
+
+/// Non-streaming OpenAI-compatible chat completion with a text reply.
+pub fn chat_completion(model: &str, content: &str) -> Value {
+    chat_response(
+        model,
+        json!({ "role": "assistant", "content": content }),
+        "stop",
+    )
+}
+
+/// Non-streaming chat completion whose reply is a single function call.
+pub fn chat_tool_call(model: &str, id: &str, name: &str, arguments: Value) -> Value {
+    chat_response(
+        model,
+        json!({
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{
+                "id": id,
+                "type": "function",
+                "function": { "name": name, "arguments": arguments.to_string() },
+            }],
+        }),
+        "tool_calls",
+    )
+}
+
+/// Non-streaming chat completion from a thinking model, with `reasoning_content`.
+pub fn chat_reasoning_completion(model: &str, reasoning: &str, content: &str) -> Value {
+    let mut response = chat_response(
+        model,
+        json!({ "role": "assistant", "content": content, "reasoning_content": reasoning }),
+        "stop",
+    );
+    response["usage"] = reasoning_usage();
+    response
+}
+
+fn chat_response(model: &str, message: Value, finish_reason: &str) -> Value {
+    json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion",
+        "created": CREATED,
+        "model": model,
+        "system_fingerprint": null,
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "logprobs": null,
+            "finish_reason": finish_reason,
+        }],
+        "usage": usage(10, 5),
+    })
+}
+
+/// Usage object as returned by DashScope compatible-mode and Ark.
+pub fn usage(prompt_tokens: u64, completion_tokens: u64) -> Value {
+    json!({
+        "prompt_tokens": prompt_tokens,
+        "completion_tokens": completion_tokens,
+        "total_tokens": prompt_tokens + completion_tokens,
+    })
+}
+
+fn reasoning_usage() -> Value {
+    let mut usage = usage(10, 5);
+    usage["completion_tokens_details"] = json!({ "reasoning_tokens": REASONING_TOKENS });
+    usage
+}
+
+/// One `chat.completion.chunk` with the given delta.
+pub fn chat_chunk(model: &str, delta: Value, finish_reason: Option<&str>) -> Value {
+    json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion.chunk",
+        "created": CREATED,
+        "model": model,
+        "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+    })
+}
+
+/// SSE events streaming `parts` as text deltas, then a usage chunk and `[DONE]`.
+pub fn chat_stream(model: &str, parts: &[&str]) -> Vec<String> {
+    let mut events = vec![chat_chunk(
+        model,
+        json!({ "role": "assistant", "content": "" }),
+        None,
+    )];
+    events.extend(
+        parts
+            .iter()
+            .map(|part| chat_chunk(model, json!({ "content": part }), None)),
+    );
+    events.push(chat_chunk(model, json!({}), Some("stop")));
+    finish_stream(model, events, usage(10, 5))
+}
+
+/// SSE events streaming `reasoning_parts` as `reasoning_content` deltas (as Qwen does with
+/// `enable_thinking`), then `parts` as text deltas.
+pub fn chat_reasoning_stream(model: &str, reasoning_parts: &[&str], parts: &[&str]) -> Vec<String> {
+    let mut events = vec![chat_chunk(
+        model,
+        json!({ "role": "assistant", "content": null, "reasoning_content": "" }),
+        None,
+    )];
+    events.extend(reasoning_parts.iter().map(|part| {
+        chat_chunk(
+            model,
+            json!({ "content": null, "reasoning_content": part }),
+            None,
+        )
+    }));
+    events.extend(parts.iter().map(|part| {
+        chat_chunk(
+            model,
+            json!({ "content": part, "reasoning_content": null }),
+            None,
+        )
+    }));
+    events.push(chat_chunk(model, json!({}), Some("stop")));
+    finish_stream(model, events, reasoning_usage())
+}
+
+/// SSE events streaming one function call whose arguments arrive in `argument_parts`.
+pub fn chat_tool_call_stream(
+    model: &str,
+    id: &str,
+    name: &str,
+    argument_parts: &[&str],
+) -> Vec<String> {
+    let mut events = vec![chat_chunk(
+        model,
+        json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "index": 0,
+                "id": id,
+                "type": "function",
+                "function": { "name": name, "arguments": "" },
+            }],
+        }),
+        None,
+    )];
+    events.extend(argument_parts.iter().map(|part| {
+        chat_chunk(
+            model,
+            json!({ "tool_calls": [{ "index": 0, "function": { "arguments": part } }] }),
+            None,
+        )
+    }));
+    events.push(chat_chunk(model, json!({}), Some("tool_calls")));
+    finish_stream(model, events, usage(10, 5))
+}
+
+fn finish_stream(model: &str, mut events: Vec<Value>, usage: Value) -> Vec<String> {
+    events.push(json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion.chunk",
+        "created": CREATED,
+        "model": model,
+        "choices": [],
+        "usage": usage,
+    }));
+    let mut events: Vec<String> = events.iter().map(Value::to_string).collect();
+    events.push("[DONE]".to_string());
+    events
+}
+
+/// Deterministic unit-length embedding for `text`; the mock's default embeddings use it.
+/// Components are rounded to 6 decimals so they survive a JSON round trip unchanged.
+pub fn embedding(text: &str, dims: usize) -> Vec<f64> {
+    let vec: Vec<f64> = (0..dims)
+        .map(|i| {
+            let hash = fnv1a(text.as_bytes(), i as u64);
+            (hash >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+        })
+        .collect();
+    let norm = vec
+        .iter()
+        .map(|x| x * x)
+        .sum::<f64>()
+        .sqrt()
+        .max(f64::EPSILON);
+    vec.into_iter()
+        .map(|x| (x / norm * 1e6).round() / 1e6)
+        .collect()
+}
+
+fn fnv1a(bytes: &[u8], seed: u64) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// OpenAI-compatible `/embeddings` response.
+pub fn openai_embeddings(model: &str, vectors: &[Vec<f64>]) -> Value {
+    let data: Vec<Value> = vectors
+        .iter()
+        .enumerate()
+        .map(|(index, vec)| json!({ "object": "embedding", "embedding": vec, "index": index }))
+        .collect();
+    json!({
+        "object": "list",
+        "data": data,
+        "model": model,
+        "usage": { "prompt_tokens": vectors.len() * 4, "total_tokens": vectors.len() * 4 },
+    })
+}
+
+/// OpenAI-compatible error body, as returned by DashScope compatible-mode and Ark.
+pub fn openai_error(message: &str, error_type: &str, code: &str) -> Value {
+    json!({
+        "error": { "message": message, "type": error_type, "param": null, "code": code },
+        "request_id": REQUEST_ID,
+    })
+}
+
+/// DashScope native rerank response for `(index, relevance_score, document)` results.
+pub fn dashscope_rerank(results: &[(usize, f64, Option<&str>)]) -> Value {
+    let results: Vec<Value> = results
+        .iter()
+        .map(|(index, score, document)| {
+            let mut item = json!({ "index": index, "relevance_score": score });
+            if let Some(text) = document {
+                item["document"] = json!({ "text": text });
+            }
+            item
+        })
+        .collect();
+    json!({
+        "output": { "results": results },
+        "usage": { "total_tokens": 16 },
+        "request_id": REQUEST_ID,
+    })
+}
+
+/// DashScope native error body (`code` / `message` / `request_id`).
+pub fn dashscope_error(code: &str, message: &str) -> Value {
+    json!({ "code": code, "message": message, "request_id": REQUEST_ID })
+}
+
+/// DashScope async task that has not finished, as returned on submit and while polling.
+/// `status` is `PENDING` or `RUNNING`.
+pub fn dashscope_task(task_id: &str, status: &str) -> Value {
+    json!({
+        "output": { "task_id": task_id, "task_status": status },
+        "request_id": REQUEST_ID,
+    })
+}
+
+/// DashScope text-to-image task that succeeded with one result per URL.
+pub fn dashscope_image_task(task_id: &str, urls: &[&str]) -> Value {
+    let results: Vec<Value> = urls.iter().map(|url| json!({ "url": url })).collect();
+    json!({
+        "output": {
+            "task_id": task_id,
+            "task_status": "SUCCEEDED",
+            "results": results,
+            "task_metrics": { "TOTAL": urls.len(), "SUCCEEDED": urls.len(), "FAILED": 0 },
+        },
+        "usage": { "image_count": urls.len() },
+        "request_id": REQUEST_ID,
+    })
+}
+
+/// DashScope async task that failed; the error sits in `output`, with a 200 status.
+pub fn dashscope_failed_task(task_id: &str, code: &str, message: &str) -> Value {
+    json!({
+        "output": {
+            "task_id": task_id,
+            "task_status": "FAILED",
+            "code": code,
+            "message": message,
+        },
+        "request_id": REQUEST_ID,
+    })
+}
+
+/// Ark `/images/generations` response with one `url` item per URL.
+pub fn ark_images(model: &str, urls: &[&str]) -> Value {
+    let data: Vec<Value> = urls
+        .iter()
+        .map(|url| json!({ "url": url, "size": "1024x1024" }))
+        .collect();
+    ark_images_response(model, data)
+}
+
+/// Ark `/images/generations` response with `response_format: "b64_json"`.
+pub fn ark_images_b64(model: &str, images: &[&[u8]]) -> Value {
+    let data: Vec<Value> = images
+        .iter()
+        .map(|image| json!({ "b64_json": STANDARD.encode(image), "size": "1024x1024" }))
+        .collect();
+    ark_images_response(model, data)
+}
+
+fn ark_images_response(model: &str, data: Vec<Value>) -> Value {
+    let count = data.len();
+    json!({
+        "model": model,
+        "created": CREATED,
+        "data": data,
+        "usage": {
+            "generated_images": count,
+            "output_tokens": count * 4096,
+            "total_tokens": count * 4096,
+        },
+    })
+}
+
+/// TEI `/embed` response.
+pub fn tei_embeddings(vectors: &[Vec<f64>]) -> Value {
+    json!(vectors)
+}
+
+/// TEI `/rerank` response for `(index, score, text)` results.
+pub fn tei_rerank(results: &[(usize, f64, Option<&str>)]) -> Value {
+    let results: Vec<Value> = results
+        .iter()
+        .map(|(index, score, text)| {
+            let mut item = json!({ "index": index, "score": score });
+            if let Some(text) = text {
+                item["text"] = json!(text);
+            }
+            item
+        })
+        .collect();
+    json!(results)
+}
+
+/// TEI `/predict` response for a single input.
+pub fn tei_predict(labels: &[(&str, f64)]) -> Value {
+    let items: Vec<Value> = labels
+        .iter()
+        .map(|(label, score)| json!({ "label": label, "score": score }))
+        .collect();
+    json!(items)
+}
+
+/// TEI error body.
+pub fn tei_error(message: &str, error_type: &str) -> Value {
+    json!({ "error": message, "error_type": error_type })
+}
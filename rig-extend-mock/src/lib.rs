@@ -0,0 +1,23 @@
+//! Category: lib.rs (top-level module and re-exports)
+//! In-process mock of the provider HTTP APIs used by the rig-extend crates:
+//! - cassette.rs: Recorder (record/replay of real HTTP interactions around any HttpClientExt)
+//! - server.rs: MockServer (binds 127.0.0.1, records requests, serves scripted or default responses)
+//! - mock.rs: Mock, MockResponse and ReceivedRequest (request matching and scripted responses)
+//! - defaults.rs: Built-in provider imitations used when no scripted mock matches
+//! - fixtures.rs: Payload builders (chat completions, SSE chunks, embeddings, rerank, image tasks, errors)
+//! - paths.rs: Endpoint paths served by the mock
+//!
+//! Scripted mocks are matched in the order they were added; a mock limited with
+//! [`Mock::times`] stops matching once used up, so sequences such as "429, then 200" are
+//! written as two mocks.
+
+pub mod cassette;
+pub mod defaults;
+pub mod fixtures;
+pub mod mock;
+pub mod paths;
+pub mod server;
+
+pub use cassette::{Cassette, CassetteError, Mode, Recorder};
+pub use mock::{Mock, MockResponse, ReceivedRequest};
+pub use server::MockServer;
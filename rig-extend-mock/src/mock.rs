@@ -0,0 +1,283 @@
+//! Category: mock.rs (request matching and scripted responses)
+
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header};
+use axum::response::Response;
+use bytes::Bytes;
+use futures::StreamExt;
+use serde_json::Value;
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    pub method: Method,
+    /// Path without query string or trailing slash.
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl ReceivedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// Body parsed as JSON (`Value::Null` if it is not JSON).
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or(Value::Null)
+    }
+
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ResponseBody {
+    Bytes(Bytes),
+    Sse(Vec<String>),
+}
+
+/// A scripted HTTP response: plain body or a `text/event-stream` of `data:` events.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: ResponseBody,
+    delay: Duration,
+    event_delay: Duration,
+}
+
+impl MockResponse {
+    fn new(status: StatusCode, body: ResponseBody) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body,
+            delay: Duration::ZERO,
+            event_delay: Duration::ZERO,
+        }
+    }
+
+    /// 200 with a JSON body.
+    pub fn json(body: Value) -> Self {
+        Self::new(StatusCode::OK, ResponseBody::Bytes(body.to_string().into()))
+            .header("content-type", "application/json")
+    }
+
+    /// 200 with a plain-text body.
+    pub fn text(body: impl Into<String>) -> Self {
+        Self::new(StatusCode::OK, ResponseBody::Bytes(body.into().into()))
+            .header("content-type", "text/plain")
+    }
+
+    /// 200 with a binary body, e.g. a generated image.
+    pub fn bytes(content_type: &str, body: impl Into<Bytes>) -> Self {
+        Self::new(StatusCode::OK, ResponseBody::Bytes(body.into()))
+            .header("content-type", content_type)
+    }
+
+    /// Empty response with the given status.
+    pub fn empty(status: u16) -> Self {
+        Self::new(status_code(status), ResponseBody::Bytes(Bytes::new()))
+    }
+
+    /// Error response with a JSON body (see the `fixtures::*_error` helpers).
+    pub fn error(status: u16, body: Value) -> Self {
+        Self::json(body).status(status)
+    }
+
+    /// 200 `text/event-stream`; each item is sent as one `data:` event, verbatim.
+    pub fn sse<I, S>(events: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::new(
+            StatusCode::OK,
+            ResponseBody::Sse(events.into_iter().map(Into::into).collect()),
+        )
+        .header("content-type", "text/event-stream")
+        .header("cache-control", "no-cache")
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status_code(status);
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("valid header name");
+        let value = HeaderValue::from_str(value).expect("valid header value");
+        self.headers.retain(|(n, _)| *n != name);
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Wait before sending the response head.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Wait before each SSE event.
+    pub fn event_delay(mut self, delay: Duration) -> Self {
+        self.event_delay = delay;
+        self
+    }
+
+    pub(crate) async fn into_response(self) -> Response {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+
+        let body = match self.body {
+            ResponseBody::Bytes(bytes) => Body::from(bytes),
+            ResponseBody::Sse(events) => {
+                let event_delay = self.event_delay;
+                let stream = futures::stream::iter(events).then(move |event| async move {
+                    if !event_delay.is_zero() {
+                        tokio::time::sleep(event_delay).await;
+                    }
+                    Ok::<_, Infallible>(Bytes::from(format!("data: {event}\n\n")))
+                });
+                Body::from_stream(stream)
+            }
+        };
+
+        let mut response = Response::new(body);
+        *response.status_mut() = self.status;
+        for (name, value) in self.headers {
+            response.headers_mut().insert(name, value);
+        }
+        if !response.headers().contains_key(header::CONTENT_TYPE) {
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/octet-stream"),
+            );
+        }
+        response
+    }
+}
+
+fn status_code(status: u16) -> StatusCode {
+    StatusCode::from_u16(status).expect("valid HTTP status")
+}
+
+type Responder = Arc<dyn Fn(&ReceivedRequest) -> MockResponse + Send + Sync>;
+
+/// Matches requests by method, path and body content, and produces a response.
+#[derive(Clone)]
+pub struct Mock {
+    method: Option<Method>,
+    path: String,
+    body_contains: Vec<String>,
+    responder: Responder,
+    remaining: Option<usize>,
+}
+
+impl fmt::Debug for Mock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mock")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("body_contains", &self.body_contains)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+impl Mock {
+    /// Match `path` for any method. Responds with an empty 200 until configured.
+    pub fn path(path: &str) -> Self {
+        Self {
+            method: None,
+            path: normalize_path(path),
+            body_contains: Vec::new(),
+            responder: Arc::new(|_| MockResponse::empty(200)),
+            remaining: None,
+        }
+    }
+
+    pub fn get(path: &str) -> Self {
+        Self::path(path).method(Method::GET)
+    }
+
+    pub fn post(path: &str) -> Self {
+        Self::path(path).method(Method::POST)
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Only match requests whose body contains `needle`.
+    pub fn body_contains(mut self, needle: impl Into<String>) -> Self {
+        self.body_contains.push(needle.into());
+        self
+    }
+
+    /// Stop matching after `n` requests.
+    pub fn times(mut self, n: usize) -> Self {
+        self.remaining = Some(n);
+        self
+    }
+
+    pub fn once(self) -> Self {
+        self.times(1)
+    }
+
+    pub fn respond(mut self, response: MockResponse) -> Self {
+        self.responder = Arc::new(move |_| response.clone());
+        self
+    }
+
+    /// Build the response from the received request.
+    pub fn respond_with<F>(mut self, responder: F) -> Self
+    where
+        F: Fn(&ReceivedRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        self.responder = Arc::new(responder);
+        self
+    }
+
+    pub(crate) fn matches(&self, request: &ReceivedRequest) -> bool {
+        if self.remaining == Some(0) || request.path != self.path {
+            return false;
+        }
+        if let Some(method) = &self.method
+            && *method != request.method
+        {
+            return false;
+        }
+        let body = request.body_text();
+        self.body_contains
+            .iter()
+            .all(|needle| body.contains(needle.as_str()))
+    }
+
+    /// Consume one use and produce the response.
+    pub(crate) fn take(&mut self, request: &ReceivedRequest) -> MockResponse {
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        (self.responder)(request)
+    }
+}
+
+/// Paths are compared without trailing slashes (DashScope URLs are used both ways).
+pub(crate) fn normalize_path(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
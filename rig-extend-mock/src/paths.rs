@@ -0,0 +1,28 @@
+//! Category: paths.rs (endpoint paths served by the mock)
+
+/// DashScope compatible-mode base path (OpenAI-compatible).
+pub const DASHSCOPE_COMPAT: &str = "/compatible-mode/v1";
+pub const DASHSCOPE_CHAT: &str = "/compatible-mode/v1/chat/completions";
+pub const DASHSCOPE_EMBEDDINGS: &str = "/compatible-mode/v1/embeddings";
+/// DashScope native text rerank service (gte-rerank-v2).
+pub const DASHSCOPE_RERANK: &str = "/api/v1/services/rerank/text-rerank/text-rerank";
+/// DashScope native text-to-image service; tasks are submitted with `X-DashScope-Async`.
+pub const DASHSCOPE_IMAGE_SYNTHESIS: &str = "/api/v1/services/aigc/text2image/image-synthesis";
+/// DashScope async task status, `GET {DASHSCOPE_TASKS}/{task_id}`.
+pub const DASHSCOPE_TASKS: &str = "/api/v1/tasks";
+
+/// Volcengine Ark base path (OpenAI-compatible).
+pub const ARK: &str = "/api/v3";
+pub const ARK_CHAT: &str = "/api/v3/chat/completions";
+pub const ARK_EMBEDDINGS: &str = "/api/v3/embeddings";
+/// Ark image generation (Seedream, SeedEdit).
+pub const ARK_IMAGES: &str = "/api/v3/images/generations";
+
+/// Text Embeddings Inference router endpoints (served at the root).
+pub const TEI_EMBED: &str = "/embed";
+pub const TEI_RERANK: &str = "/rerank";
+pub const TEI_PREDICT: &str = "/predict";
+pub const TEI_HEALTH: &str = "/health";
+
+/// Generated images served by the mock, `GET {FILES}/{name}`; result URLs point here.
+pub const FILES: &str = "/files";
@@ -0,0 +1,159 @@
+//! Category: server.rs (in-process HTTP server serving scripted and default responses)
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, Method, Uri};
+use axum::response::Response;
+use tokio::sync::oneshot;
+
+use super::defaults;
+use super::mock::{Mock, ReceivedRequest, normalize_path};
+use super::paths;
+
+#[derive(Debug, Default)]
+struct Shared {
+    mocks: Mutex<Vec<Mock>>,
+    requests: Mutex<Vec<ReceivedRequest>>,
+}
+
+/// Mock provider server bound to an ephemeral port on 127.0.0.1.
+///
+/// Every request is recorded. The first scripted [`Mock`] that matches answers it; otherwise
+/// the built-in imitation of the provider endpoint does (see [`defaults`]). The server stops
+/// when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Start the server on the current tokio runtime.
+    pub async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server address");
+
+        let shared = Arc::new(Shared::default());
+        let app = Router::new().fallback(handle).with_state(shared.clone());
+
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = stopped.await;
+                })
+                .await;
+        });
+
+        Self {
+            addr,
+            shared,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Root URL, e.g. `http://127.0.0.1:41234`. TEI endpoints are served here.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Base URL for `rig_bailian::Client` (DashScope compatible-mode).
+    pub fn dashscope_base_url(&self) -> String {
+        format!("{}{}", self.url(), paths::DASHSCOPE_COMPAT)
+    }
+
+    /// Full endpoint for `rig_bailian::RerankModel`.
+    pub fn dashscope_rerank_url(&self) -> String {
+        format!("{}{}", self.url(), paths::DASHSCOPE_RERANK)
+    }
+
+    /// Full endpoint for `rig_bailian::ImageGenerationModel`.
+    pub fn dashscope_image_synthesis_url(&self) -> String {
+        format!("{}{}", self.url(), paths::DASHSCOPE_IMAGE_SYNTHESIS)
+    }
+
+    /// Base URL for `rig_volcengine::Client` (Ark).
+    pub fn ark_base_url(&self) -> String {
+        format!("{}{}", self.url(), paths::ARK)
+    }
+
+    /// Base URL for `rig_tei::Client`.
+    pub fn tei_base_url(&self) -> String {
+        self.url()
+    }
+
+    /// Add a scripted mock; mocks are tried in the order they were added.
+    pub fn mock(&self, mock: Mock) -> &Self {
+        self.shared.mocks.lock().unwrap().push(mock);
+        self
+    }
+
+    /// Remove scripted mocks and forget recorded requests.
+    pub fn reset(&self) {
+        self.shared.mocks.lock().unwrap().clear();
+        self.shared.requests.lock().unwrap().clear();
+    }
+
+    /// All requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.shared.requests.lock().unwrap().clone()
+    }
+
+    /// Requests received on `path` (trailing slash ignored).
+    pub fn requests_to(&self, path: &str) -> Vec<ReceivedRequest> {
+        let path = normalize_path(path);
+        self.requests()
+            .into_iter()
+            .filter(|r| r.path == path)
+            .collect()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+async fn handle(
+    State(shared): State<Arc<Shared>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request = ReceivedRequest {
+        method,
+        path: normalize_path(uri.path()),
+        query: uri.query().map(str::to_string),
+        headers,
+        body,
+    };
+    shared.requests.lock().unwrap().push(request.clone());
+
+    let scripted = shared
+        .mocks
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .find(|mock| mock.matches(&request))
+        .map(|mock| mock.take(&request));
+
+    scripted
+        .unwrap_or_else(|| defaults::respond(&request))
+        .into_response()
+        .await
+}
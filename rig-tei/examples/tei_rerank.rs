@@ -11,7 +11,7 @@ async fn main() -> Result<(), anyhow::Error> {
         "Reranking orders documents by relevance.".to_string(),
     ];
     let results = client
-        .rerank("what is a transformer?", docs, Some(2))
+        .rerank_documents("what is a transformer?", docs, Some(2))
         .await?;
     for r in results {
         println!("#{}/{} {:?}", r.index, r.relevance_score, r.text);
@@ -1,15 +1,41 @@
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+use std::any::Any;
+
 use rig::client::{EmbeddingsClient, ProviderClient, VerifyClient, VerifyError};
-use rig::http_client::{self};
+use rig::http_client::{self, HttpClientExt};
+use rig_extend_core::circuit_breaker::{CircuitBreaker, CircuitBreakerPolicy, verify_probe};
+#[cfg(feature = "reqwest")]
+use rig_extend_core::config::{
+    ClientConfig, ConfigError, FromClientConfig, FromModelConfig, ModelConfig, ModelKind,
+};
+use rig_extend_core::error::{ErrorClass, ProviderErrorExt};
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+use rig_extend_core::http::{HttpOptions, HttpOptionsExt};
+use rig_extend_core::rate_limit::RateLimits;
+#[cfg(feature = "reqwest")]
+use rig_extend_core::registry::ProviderFactory;
+use rig_extend_core::retry::RetryPolicy;
+use rig_extend_core::transport::Transport;
+use rig_extend_core::usage::{Operation, TokenUsage, UsageLedger};
 
-use super::TEI_DEFAULT_BASE_URL;
 use super::embedding::EmbeddingModel;
+use super::{TEI_BASE_URL_ENV, TEI_DEFAULT_BASE_URL};
+
+/// Provider name in usage ledgers and config files.
+const PROVIDER: &str = "tei";
 
 /// Provider client: Client<T>
 /// Note: base_url is resolved into concrete endpoints during build, so we don't store base_url.
 #[derive(Clone, Debug)]
-pub struct Client<T = reqwest::Client> {
-    pub(crate) http_client: T,
+pub struct Client<
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
+    pub(crate) http_client: Transport<T>,
     pub(crate) endpoints: Endpoints,
+    model_id: Option<String>,
+    usage: Option<UsageLedger>,
+    usage_tag: Option<String>,
 }
 
 /// Resolved endpoints for TEI features.
@@ -18,6 +44,8 @@ pub struct Endpoints {
     pub embed: String,
     pub rerank: String,
     pub predict: String,
+    /// Checked by `verify` and by half-open circuit probes.
+    pub health: String,
 }
 
 impl Endpoints {
@@ -27,18 +55,31 @@ impl Endpoints {
             embed: format!("{}/embed", base),
             rerank: format!("{}/rerank", base),
             predict: format!("{}/predict", base),
+            health: format!("{}/health", base),
         }
     }
 }
 
 /// Client builder: ClientBuilder<'a, T>
-pub struct ClientBuilder<'a, T = reqwest::Client> {
+pub struct ClientBuilder<
+    'a,
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
     base_url: &'a str,
     http_client: T,
+    #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+    http_options: Option<HttpOptions>,
+    retry_policy: RetryPolicy,
+    rate_limits: RateLimits,
+    circuit_breaker: Option<CircuitBreakerPolicy>,
+    model_id: Option<&'a str>,
+    usage: Option<UsageLedger>,
     // Optional endpoint overrides
     embed_endpoint: Option<&'a str>,
     rerank_endpoint: Option<&'a str>,
     predict_endpoint: Option<&'a str>,
+    health_endpoint: Option<&'a str>,
 }
 
 impl<'a, T> ClientBuilder<'a, T>
@@ -49,29 +90,106 @@ where
         Self {
             base_url: TEI_DEFAULT_BASE_URL,
             http_client: Default::default(),
+            #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+            http_options: None,
+            retry_policy: RetryPolicy::none(),
+            rate_limits: RateLimits::default(),
+            circuit_breaker: None,
+            model_id: None,
+            usage: None,
             embed_endpoint: None,
             rerank_endpoint: None,
             predict_endpoint: None,
+            health_endpoint: None,
         }
     }
 }
 
+impl<T> Default for ClientBuilder<'_, T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a, T> ClientBuilder<'a, T> {
     pub fn base_url(mut self, base_url: &'a str) -> Self {
         self.base_url = base_url;
         self
     }
 
+    /// Use a prebuilt HTTP client; this drops any HTTP options set so far.
     pub fn with_client<U>(self, http_client: U) -> ClientBuilder<'a, U> {
         ClientBuilder {
             base_url: self.base_url,
             http_client,
+            #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+            http_options: None,
+            retry_policy: self.retry_policy,
+            rate_limits: self.rate_limits,
+            circuit_breaker: self.circuit_breaker,
+            model_id: self.model_id,
+            usage: self.usage,
             embed_endpoint: self.embed_endpoint,
             rerank_endpoint: self.rerank_endpoint,
             predict_endpoint: self.predict_endpoint,
+            health_endpoint: self.health_endpoint,
         }
     }
 
+    /// Retry policy applied to embed, rerank and predict calls (default: no retries).
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Requests per minute across embed, rerank and predict calls.
+    pub fn requests_per_minute(mut self, rpm: u32) -> Self {
+        self.rate_limits = self.rate_limits.requests_per_minute(rpm);
+        self
+    }
+
+    /// Estimated tokens per minute across embed, rerank and predict calls.
+    pub fn tokens_per_minute(mut self, tpm: u32) -> Self {
+        self.rate_limits = self.rate_limits.tokens_per_minute(tpm);
+        self
+    }
+
+    /// Maximum concurrent requests; match the server's `--max-concurrent-requests`.
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        self.rate_limits = self.rate_limits.max_in_flight(max);
+        self
+    }
+
+    /// Replace all client-side rate limits at once (default: unlimited).
+    pub fn rate_limits(mut self, rate_limits: RateLimits) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Fail fast while the server keeps failing (default: off). Half-open circuits are
+    /// probed with `verify`, a GET of the health endpoint.
+    pub fn circuit_breaker(mut self, policy: CircuitBreakerPolicy) -> Self {
+        self.circuit_breaker = Some(policy);
+        self
+    }
+
+    /// Name of the model the server hosts; rerank usage is recorded under it (default: the
+    /// rerank endpoint URL).
+    pub fn model_id(mut self, model_id: &'a str) -> Self {
+        self.model_id = Some(model_id);
+        self
+    }
+
+    /// Record usage of every embed and rerank call. Token counts come from TEI's
+    /// `x-compute-tokens` header; without it only the request is counted.
+    pub fn usage_ledger(mut self, ledger: UsageLedger) -> Self {
+        self.usage = Some(ledger);
+        self
+    }
+
     // Custom endpoint overrides
     pub fn embed_endpoint(mut self, url: &'a str) -> Self {
         self.embed_endpoint = Some(url);
@@ -88,7 +206,43 @@ impl<'a, T> ClientBuilder<'a, T> {
         self
     }
 
-    pub fn build(self) -> Client<T> {
+    pub fn health_endpoint(mut self, url: &'a str) -> Self {
+        self.health_endpoint = Some(url);
+        self
+    }
+
+    /// Build the client.
+    ///
+    /// # Panics
+    ///
+    /// If HTTP options were set and the `reqwest::Client` cannot be built (e.g. the TLS
+    /// backend fails to initialise); see [`try_build`](Self::try_build).
+    pub fn build(self) -> Client<T>
+    where
+        T: HttpClientExt + Clone + Send + Sync + 'static,
+    {
+        self.try_build()
+            .unwrap_or_else(|err| panic!("failed to build HTTP client: {err}"))
+    }
+
+    /// Build the client, reporting HTTP options the `reqwest::Client` rejects (e.g. an
+    /// invalid certificate) instead of panicking.
+    #[cfg_attr(
+        any(not(feature = "reqwest"), target_family = "wasm"),
+        allow(unused_mut)
+    )]
+    pub fn try_build(mut self) -> http_client::Result<Client<T>>
+    where
+        T: HttpClientExt + Clone + Send + Sync + 'static,
+    {
+        #[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+        if let Some(options) = &self.http_options
+            && let Some(client) = (&mut self.http_client as &mut dyn Any).downcast_mut()
+        {
+            *client = options
+                .build()
+                .map_err(|err| http_client::Error::Instance(err.into()))?;
+        }
         let mut endpoints = Endpoints::with_base(self.base_url);
         if let Some(url) = self.embed_endpoint {
             endpoints.embed = url.to_string();
@@ -99,38 +253,115 @@ impl<'a, T> ClientBuilder<'a, T> {
         if let Some(url) = self.predict_endpoint {
             endpoints.predict = url.to_string();
         }
+        if let Some(url) = self.health_endpoint {
+            endpoints.health = url.to_string();
+        }
 
-        Client {
-            http_client: self.http_client,
+        let mut client = Client {
+            http_client: Transport::new(self.http_client, self.retry_policy)
+                .with_rate_limits(self.rate_limits),
             endpoints,
+            model_id: self.model_id.map(str::to_string),
+            usage: self.usage,
+            usage_tag: None,
+        };
+        if let Some(policy) = self.circuit_breaker {
+            let mut probe = client.clone();
+            probe.http_client = client.http_client.unguarded();
+            client.http_client = client
+                .http_client
+                .with_circuit_breaker(policy)
+                .with_probe(verify_probe(probe));
         }
+        Ok(client)
+    }
+}
+
+/// HTTP options for the default `reqwest::Client`; see [`HttpOptionsExt`].
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+impl HttpOptionsExt for ClientBuilder<'_, reqwest::Client> {
+    fn map_http_options(mut self, f: impl FnOnce(HttpOptions) -> HttpOptions) -> Self {
+        self.http_options = Some(f(self.http_options.take().unwrap_or_default()));
+        self
     }
 }
 
 impl<T> Default for Client<T>
 where
-    T: Default,
+    T: HttpClientExt + Clone + Default + Send + Sync + 'static,
 {
     fn default() -> Self {
-        Self::new()
+        ClientBuilder::new().build()
     }
 }
 
-impl<T> Client<T>
-where
-    T: Default,
-{
-    pub fn builder<'a>() -> ClientBuilder<'a, T> {
+/// Constructors for the default `reqwest::Client`, so `Client::builder()` and
+/// `Client::from_env()` need no type annotation. For another backend call `with_client` on
+/// the builder.
+#[cfg(feature = "reqwest")]
+impl Client<reqwest::Client> {
+    pub fn builder<'a>() -> ClientBuilder<'a> {
         ClientBuilder::new()
     }
 
     pub fn new() -> Self {
         Self::builder().build()
     }
+
+    /// Base URL from `TEI_BASE_URL`, else the local default.
+    pub fn from_env() -> Self {
+        <Self as ProviderClient>::from_env()
+    }
 }
 
 // Build a POST request using a full URL (used when endpoints are overridden).
 impl<T> Client<T> {
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.http_client.circuit_breaker()
+    }
+
+    /// Model name rerank usage is recorded under.
+    pub fn model_id(&self) -> &str {
+        self.model_id.as_deref().unwrap_or(&self.endpoints.rerank)
+    }
+
+    pub fn usage_ledger(&self) -> Option<&UsageLedger> {
+        self.usage.as_ref()
+    }
+
+    /// A copy of the client whose usage is recorded under `tag` (e.g. a tenant or feature).
+    /// It shares the ledger, retries and rate limits with the original.
+    pub fn with_usage_tag(&self, tag: impl Into<String>) -> Self
+    where
+        Self: Clone,
+    {
+        Self {
+            usage_tag: Some(tag.into()),
+            ..self.clone()
+        }
+    }
+
+    pub fn usage_tag(&self) -> Option<&str> {
+        self.usage_tag.as_deref()
+    }
+
+    /// Add one request's usage to the client's ledger, if any.
+    pub(crate) fn record_usage(
+        &self,
+        model: &str,
+        operation: Operation,
+        headers: &http_client::HeaderMap,
+    ) {
+        if let Some(ledger) = &self.usage {
+            let usage = headers
+                .get("x-compute-tokens")
+                .and_then(|value| value.to_str().ok()?.parse().ok())
+                .map(TokenUsage::input_only)
+                .unwrap_or_default();
+            ledger.record(PROVIDER, model, operation, self.usage_tag.as_deref(), usage);
+        }
+    }
+
     pub(crate) fn post_full(&self, url: &str) -> http_client::Builder {
         http_client::Builder::new()
             .method(http_client::Method::POST)
@@ -138,13 +369,16 @@ impl<T> Client<T> {
     }
 }
 
-impl ProviderClient for Client<reqwest::Client> {
+impl<T> ProviderClient for Client<T>
+where
+    T: HttpClientExt + Clone + Default + Send + Sync + 'static,
+{
     type Input = String;
 
     fn from_env() -> Self {
         let base_url =
-            std::env::var("TEI_BASE_URL").unwrap_or_else(|_| TEI_DEFAULT_BASE_URL.to_string());
-        Self::builder().base_url(&base_url).build()
+            std::env::var(TEI_BASE_URL_ENV).unwrap_or_else(|_| TEI_DEFAULT_BASE_URL.to_string());
+        ClientBuilder::new().base_url(&base_url).build()
     }
 
     fn from_val(input: String) -> Self {
@@ -152,18 +386,102 @@ impl ProviderClient for Client<reqwest::Client> {
     }
 }
 
-impl VerifyClient for Client<reqwest::Client> {
+/// `provider = "tei"`. TEI has no API key; `endpoints` may override `embed`, `rerank`,
+/// `predict` and `health`.
+#[cfg(feature = "reqwest")]
+impl FromClientConfig for Client<reqwest::Client> {
+    const PROVIDER: &'static str = PROVIDER;
+
+    fn from_client_config(name: &str, config: &ClientConfig) -> Result<Self, ConfigError> {
+        let base_url = config.resolve_base_url(TEI_BASE_URL_ENV, TEI_DEFAULT_BASE_URL);
+        let mut builder = Self::builder()
+            .base_url(&base_url)
+            .with_client(config.http_client(name)?)
+            .retry_policy(config.retry_policy())
+            .rate_limits(config.rate_limits());
+        if let Some(policy) = config.circuit_breaker() {
+            builder = builder.circuit_breaker(policy);
+        }
+        if let Some(url) = config.endpoint("embed") {
+            builder = builder.embed_endpoint(url);
+        }
+        if let Some(url) = config.endpoint("rerank") {
+            builder = builder.rerank_endpoint(url);
+        }
+        if let Some(url) = config.endpoint("predict") {
+            builder = builder.predict_endpoint(url);
+        }
+        if let Some(url) = config.endpoint("health") {
+            builder = builder.health_endpoint(url);
+        }
+        Ok(builder.build())
+    }
+}
+
+/// A TEI server hosts one reranker, so a `kind = "rerank"` model is the client itself,
+/// recording usage under the configured model name.
+#[cfg(feature = "reqwest")]
+impl FromModelConfig for Client<reqwest::Client> {
+    type Client = Self;
+    const KIND: ModelKind = ModelKind::Rerank;
+
+    fn from_model_config(client: &Self, config: &ModelConfig) -> Result<Self, ConfigError> {
+        Ok(Self {
+            model_id: Some(config.model.clone()),
+            ..client.clone()
+        })
+    }
+}
+
+/// Registry entry for `provider = "tei"`: embedding and rerank models.
+#[cfg(feature = "reqwest")]
+pub fn provider() -> ProviderFactory {
+    ProviderFactory::new::<Client>()
+        .embedding::<EmbeddingModel>()
+        .rerank::<Client>()
+}
+
+/// `GET {endpoints.health}`: 200 once the model is loaded, 503 while it is not.
+impl<T> VerifyClient for Client<T>
+where
+    T: HttpClientExt + Clone + Send + Sync + 'static,
+{
     async fn verify(&self) -> Result<(), VerifyError> {
-        // TEI local router often has no auth and no health endpoint needed.
-        Ok(())
+        let req = http_client::Builder::new()
+            .method(http_client::Method::GET)
+            .uri(self.endpoints.health.clone())
+            .body(http_client::NoBody)
+            .map_err(http_client::Error::from)?;
+
+        let response = match HttpClientExt::send(&self.http_client, req).await {
+            Ok(response) => response,
+            Err(err) => {
+                return Err(match err.error_class() {
+                    Some(ErrorClass::Auth) => VerifyError::InvalidAuthentication,
+                    Some(ErrorClass::Server) => VerifyError::ProviderError(err.to_string()),
+                    _ => VerifyError::HttpError(err),
+                });
+            }
+        };
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status if status.as_u16() == 401 => Err(VerifyError::InvalidAuthentication),
+            status => {
+                let text = http_client::text(response).await?;
+                Err(VerifyError::ProviderError(format!("{status}: {text}")))
+            }
+        }
     }
 }
 
-impl EmbeddingsClient for Client<reqwest::Client> {
-    type EmbeddingModel = EmbeddingModel<reqwest::Client>;
+impl<T> EmbeddingsClient for Client<T>
+where
+    T: HttpClientExt + Clone + std::fmt::Debug + Default + Send + 'static,
+{
+    type EmbeddingModel = EmbeddingModel<T>;
 
     fn embedding_model(&self, model: impl Into<String>) -> Self::EmbeddingModel {
-        EmbeddingModel::new(self.clone(), &model.into(), 0)
+        EmbeddingModel::new(self.clone(), model.into(), 0)
     }
 
     fn embedding_model_with_ndims(
@@ -171,6 +489,6 @@ impl EmbeddingsClient for Client<reqwest::Client> {
         model: impl Into<String>,
         ndims: usize,
     ) -> Self::EmbeddingModel {
-        EmbeddingModel::new(self.clone(), &model.into(), ndims)
+        EmbeddingModel::new(self.clone(), model.into(), ndims)
     }
 }
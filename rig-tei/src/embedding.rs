@@ -1,10 +1,18 @@
 use rig::embeddings::{self, EmbeddingError};
 use rig::http_client::{self, HttpClientExt};
+use rig_extend_core::batching::{EmbeddingBatchError, EmbeddingBatching, embed_in_batches};
+#[cfg(feature = "reqwest")]
+use rig_extend_core::config::{ConfigError, FromModelConfig, ModelConfig, ModelKind};
+use rig_extend_core::error::ProviderError;
+use rig_extend_core::usage::Operation;
 use serde::Deserialize;
 use serde_json::{Value, json};
 
 use super::client::Client;
 
+/// TEI's default `--max-client-batch-size`.
+pub const TEI_DEFAULT_BATCH_SIZE: usize = 32;
+
 #[derive(Debug, Deserialize)]
 struct MultiEmbeddings {
     embeddings: Vec<Vec<f32>>,
@@ -24,10 +32,14 @@ enum EmbeddingResponse {
 }
 
 #[derive(Clone)]
-pub struct EmbeddingModel<T = reqwest::Client> {
+pub struct EmbeddingModel<
+    #[cfg(feature = "reqwest")] T = reqwest::Client,
+    #[cfg(not(feature = "reqwest"))] T,
+> {
     pub(crate) client: Client<T>,
     pub model: String,
     ndims: usize,
+    batching: EmbeddingBatching,
 }
 
 impl<T> EmbeddingModel<T> {
@@ -36,34 +48,66 @@ impl<T> EmbeddingModel<T> {
             client,
             model: model.into(),
             ndims,
+            batching: EmbeddingBatching::new(TEI_DEFAULT_BATCH_SIZE),
         }
     }
-}
 
-impl<T> embeddings::EmbeddingModel for EmbeddingModel<T>
-where
-    T: HttpClientExt + Clone + std::fmt::Debug + Default + Send + 'static,
-{
-    const MAX_DOCUMENTS: usize = 1024;
+    /// Inputs per request; match the server's `--max-client-batch-size`.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batching = self.batching.batch_size(batch_size);
+        self
+    }
 
-    type Client = Client<T>;
+    /// Requests in flight at once for one `embed_texts` call.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.batching = self.batching.concurrency(concurrency);
+        self
+    }
 
-    fn make(client: &Self::Client, model: impl Into<String>, ndims: Option<usize>) -> Self {
-        let model = model.into();
-        let dims = ndims.unwrap_or(0);
-        Self::new(client.clone(), model, dims)
+    pub fn batching(&self) -> EmbeddingBatching {
+        self.batching
     }
+}
 
-    fn ndims(&self) -> usize {
-        self.ndims
+#[cfg(feature = "reqwest")]
+impl FromModelConfig for EmbeddingModel<reqwest::Client> {
+    type Client = Client<reqwest::Client>;
+    const KIND: ModelKind = ModelKind::Embedding;
+
+    fn from_model_config(client: &Self::Client, config: &ModelConfig) -> Result<Self, ConfigError> {
+        let mut model = Self::new(
+            client.clone(),
+            &config.model,
+            config.dimensions.unwrap_or(0),
+        );
+        if let Some(batch_size) = config.batch_size {
+            model = model.with_batch_size(batch_size);
+        }
+        if let Some(concurrency) = config.concurrency {
+            model = model.with_concurrency(concurrency);
+        }
+        Ok(model)
     }
+}
 
-    async fn embed_texts(
+impl<T> EmbeddingModel<T>
+where
+    T: HttpClientExt + Clone + std::fmt::Debug + Default + Send + 'static,
+{
+    /// Embed `documents` in batches, keeping the embeddings of batches that succeeded when
+    /// others fail.
+    pub async fn embed_batched(
         &self,
         documents: impl IntoIterator<Item = String>,
-    ) -> Result<Vec<embeddings::Embedding>, EmbeddingError> {
-        let docs: Vec<String> = documents.into_iter().collect();
+    ) -> Result<Vec<embeddings::Embedding>, EmbeddingBatchError> {
+        let documents = documents.into_iter().collect();
+        embed_in_batches(documents, self.batching, |batch| self.embed_batch(batch)).await
+    }
 
+    async fn embed_batch(
+        &self,
+        docs: Vec<String>,
+    ) -> Result<Vec<embeddings::Embedding>, EmbeddingError> {
         let inputs_value: Value = if docs.len() == 1 {
             json!({ "inputs": docs[0] })
         } else {
@@ -81,16 +125,22 @@ where
             .map_err(|e| EmbeddingError::HttpError(e.into()))?;
 
         let response = HttpClientExt::send(&self.client.http_client, req).await?;
+        let headers = response.headers().clone();
 
         if !response.status().is_success() {
+            let status = response.status();
             let text = http_client::text(response).await?;
-            return Err(EmbeddingError::ProviderError(text));
+            return Err(EmbeddingError::HttpError(
+                ProviderError::from_response(status, &headers, &text).into(),
+            ));
         }
 
         let bytes: Vec<u8> = response.into_body().await?;
         let parsed: EmbeddingResponse = serde_json::from_slice(&bytes).map_err(|e| {
             EmbeddingError::ResponseError(format!("Failed to parse TEI embeddings: {e}"))
         })?;
+        self.client
+            .record_usage(&self.model, Operation::Embedding, &headers);
 
         let embeddings: Vec<Vec<f64>> = match parsed {
             EmbeddingResponse::Multi(m) => m
@@ -115,8 +165,36 @@ where
 
         Ok(embeddings
             .into_iter()
-            .zip(docs.into_iter())
+            .zip(docs)
             .map(|(vec, document)| embeddings::Embedding { document, vec })
             .collect())
     }
 }
+
+impl<T> embeddings::EmbeddingModel for EmbeddingModel<T>
+where
+    T: HttpClientExt + Clone + std::fmt::Debug + Default + Send + 'static,
+{
+    const MAX_DOCUMENTS: usize = 1024;
+
+    type Client = Client<T>;
+
+    fn make(client: &Self::Client, model: impl Into<String>, ndims: Option<usize>) -> Self {
+        let model = model.into();
+        let dims = ndims.unwrap_or(0);
+        Self::new(client.clone(), model, dims)
+    }
+
+    fn ndims(&self) -> usize {
+        self.ndims
+    }
+
+    async fn embed_texts(
+        &self,
+        documents: impl IntoIterator<Item = String>,
+    ) -> Result<Vec<embeddings::Embedding>, EmbeddingError> {
+        self.embed_batched(documents)
+            .await
+            .map_err(EmbeddingBatchError::into_first_error)
+    }
+}
@@ -10,10 +10,25 @@ pub mod embedding;
 pub mod predict;
 pub mod rerank;
 
+#[cfg(feature = "reqwest")]
+pub use client::provider;
 pub use client::{Client, Endpoints};
-pub use embedding::EmbeddingModel;
+pub use embedding::{EmbeddingModel, TEI_DEFAULT_BATCH_SIZE};
 pub use predict::{LabelScore, PredictError, PredictResponse};
 pub use rerank::{RerankError, RerankResult};
+pub use rig_extend_core::batching::{BatchFailure, EmbeddingBatchError, EmbeddingBatching};
+pub use rig_extend_core::circuit_breaker::{CircuitBreakerPolicy, CircuitState};
+pub use rig_extend_core::config::{Config, ConfigError};
+pub use rig_extend_core::error::{ErrorClass, ProviderError, ProviderErrorExt};
+#[cfg(feature = "workers")]
+pub use rig_extend_core::fetch::FetchClient;
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+pub use rig_extend_core::http::{HttpOptions, HttpOptionsExt};
+pub use rig_extend_core::registry::ProviderRegistry;
+pub use rig_extend_core::rerank::{Reranker, RerankerDyn};
+pub use rig_extend_core::usage::{ModelPrice, Operation, PriceTable, UsageLedger, UsageSnapshot};
 
 // Default local TEI base URL
 pub const TEI_DEFAULT_BASE_URL: &str = "http://127.0.0.1:8080";
+/// Environment variable overriding the base URL in `from_env`.
+pub const TEI_BASE_URL_ENV: &str = "TEI_BASE_URL";
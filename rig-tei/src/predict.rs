@@ -1,4 +1,5 @@
 use rig::http_client::{self, HttpClientExt};
+use rig_extend_core::error::{ProviderError, ProviderErrorExt};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -35,19 +36,32 @@ enum PredictResponseInternal {
     Items(ItemsShape),
     Predictions(PredictionsShape),
     Arrays(ArraysShape),
+    /// TEI's native shape: `[{label, score}]` for one input, `[[...], ...]` for a batch.
+    Bare(Vec<LabelScore>),
+    Batch(Vec<Vec<LabelScore>>),
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum PredictError {
     #[error("http error: {0}")]
     Http(#[from] http_client::Error),
-    #[error("provider error: {0}")]
-    Provider(String),
     #[error("response error: {0}")]
     Response(String),
 }
 
-impl Client<reqwest::Client> {
+impl ProviderErrorExt for PredictError {
+    fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            PredictError::Http(err) => err.provider_error(),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Client<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
     /// Predict/classify inputs using TEI router endpoint (customizable via ClientBuilder)
     pub async fn predict(
         &self,
@@ -71,8 +85,12 @@ impl Client<reqwest::Client> {
 
         let response = HttpClientExt::send(&self.http_client, req).await?;
         if !response.status().is_success() {
+            let status = response.status();
+            let headers = response.headers().clone();
             let text = http_client::text(response).await?;
-            return Err(PredictError::Provider(text));
+            return Err(PredictError::Http(
+                ProviderError::from_response(status, &headers, &text).into(),
+            ));
         }
 
         let bytes: Vec<u8> = response.into_body().await?;
@@ -91,10 +109,13 @@ impl Client<reqwest::Client> {
                 }
                 x.labels
                     .into_iter()
-                    .zip(x.scores.into_iter())
+                    .zip(x.scores)
                     .map(|(label, score)| LabelScore { label, score })
                     .collect()
             }
+            PredictResponseInternal::Bare(items) => items,
+            // Batches are flattened in input order.
+            PredictResponseInternal::Batch(batch) => batch.into_iter().flatten().collect(),
         };
 
         Ok(PredictResponse { items })
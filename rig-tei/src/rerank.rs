@@ -1,31 +1,31 @@
 use rig::http_client::{self, HttpClientExt};
-use serde::{Deserialize, Serialize};
+use rig_extend_core::error::ProviderError;
+use rig_extend_core::rerank::{self, Reranker};
+use rig_extend_core::usage::Operation;
+use serde::Deserialize;
 use serde_json::json;
 
+pub use rig_extend_core::rerank::{RerankError, RerankResult};
+
 use super::client::Client;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct RerankResult {
-    pub index: usize,
+/// Raw TEI `/rerank` item (score is f32 on the wire)
+#[derive(Debug, Deserialize)]
+struct RerankItem {
+    index: usize,
     #[serde(default)]
-    pub text: Option<String>,
+    text: Option<String>,
     #[serde(alias = "score", alias = "relevance_score")]
-    pub relevance_score: f32,
-}
-
-#[derive(thiserror::Error, Debug)]
-pub enum RerankError {
-    #[error("http error: {0}")]
-    Http(#[from] http_client::Error),
-    #[error("provider error: {0}")]
-    Provider(String),
-    #[error("response error: {0}")]
-    Response(String),
+    relevance_score: f32,
 }
 
-impl Client<reqwest::Client> {
-    /// Rerank endpoint (customizable via ClientBuilder): POST {endpoints.rerank}
-    pub async fn rerank(
+impl<T> Client<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    /// Rerank endpoint (customizable via ClientBuilder): POST {endpoints.rerank}. Unlike
+    /// [`Reranker::rerank`], the inputs are not validated and `top_n` is left to TEI.
+    pub async fn rerank_documents(
         &self,
         query: &str,
         texts: impl IntoIterator<Item = String>,
@@ -41,25 +41,59 @@ impl Client<reqwest::Client> {
             payload["top_n"] = json!(k);
         }
 
-        let body =
-            serde_json::to_vec(&payload).map_err(|e| RerankError::Response(e.to_string()))?;
+        let body = serde_json::to_vec(&payload)?;
 
         let req = self
             .post_full(&self.endpoints.rerank)
             .header("Content-Type", "application/json")
             .body(body)
-            .map_err(|e| RerankError::Http(e.into()))?;
+            .map_err(|e| RerankError::HttpError(e.into()))?;
 
         let response = HttpClientExt::send(&self.http_client, req).await?;
+        let headers = response.headers().clone();
         if !response.status().is_success() {
+            let status = response.status();
             let text = http_client::text(response).await?;
-            return Err(RerankError::Provider(text));
+            return Err(RerankError::HttpError(
+                ProviderError::from_response(status, &headers, &text).into(),
+            ));
         }
 
         let bytes: Vec<u8> = response.into_body().await?;
-        let parsed: Vec<RerankResult> = serde_json::from_slice(&bytes).map_err(|e| {
-            RerankError::Response(format!("Failed to parse TEI rerank response: {e}"))
+        let parsed: Vec<RerankItem> = serde_json::from_slice(&bytes).map_err(|e| {
+            RerankError::ResponseError(format!("Failed to parse TEI rerank response: {e}"))
         })?;
-        Ok(parsed)
+        self.record_usage(self.model_id(), Operation::Rerank, &headers);
+        Ok(parsed
+            .into_iter()
+            .map(|item| RerankResult {
+                index: item.index,
+                relevance_score: item.relevance_score as f64,
+                text: item.text,
+            })
+            .collect())
+    }
+}
+
+impl<T> Reranker for Client<T>
+where
+    T: HttpClientExt + Clone + 'static,
+{
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[String],
+        top_n: Option<usize>,
+    ) -> Result<Vec<RerankResult>, RerankError> {
+        rerank::validate(query, documents)?;
+
+        let mut results = self
+            .rerank_documents(query, documents.to_vec(), top_n)
+            .await?;
+        // Some TEI versions ignore `top_n`; enforce it client-side.
+        if let Some(n) = top_n {
+            results.truncate(n);
+        }
+        Ok(results)
     }
 }
@@ -0,0 +1,311 @@
+use rig::client::VerifyError;
+use rig::embeddings::EmbeddingModel as _;
+use rig::prelude::*;
+use rig_extend_mock::{Mock, MockResponse, MockServer, Recorder, fixtures, paths};
+use rig_tei::{Client, HttpOptionsExt, Operation, RerankError, Reranker, UsageLedger};
+
+/// TEI returns f32 vectors; compare at that precision.
+fn as_f32(vec: &[f64]) -> Vec<f32> {
+    vec.iter().map(|x| *x as f32).collect()
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::builder().base_url(&server.tei_base_url()).build()
+}
+
+#[tokio::test]
+async fn embed_single_and_batch() {
+    let server = MockServer::start().await;
+    let model = client(&server).embedding_model("bge-m3");
+
+    let one = model.embed_text("hello").await.unwrap();
+    assert_eq!(
+        as_f32(&one.vec),
+        as_f32(&fixtures::embedding("hello", fixtures::DEFAULT_DIMS))
+    );
+
+    let batch = model
+        .embed_texts(vec!["a".to_string(), "b".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(batch.len(), 2);
+    assert_eq!(
+        as_f32(&batch[1].vec),
+        as_f32(&fixtures::embedding("b", fixtures::DEFAULT_DIMS))
+    );
+
+    let requests = server.requests_to(paths::TEI_EMBED);
+    assert_eq!(requests[0].json()["inputs"], "hello");
+    assert_eq!(requests[1].json()["inputs"][1], "b");
+    assert_eq!(requests[0].header("authorization"), None);
+}
+
+#[tokio::test]
+async fn embed_splits_at_the_batch_size() {
+    let server = MockServer::start().await;
+    let model = client(&server).embedding_model("bge-m3");
+    assert_eq!(model.batching().batch_size, rig_tei::TEI_DEFAULT_BATCH_SIZE);
+
+    let docs: Vec<String> = (0..5).map(|i| format!("doc {i}")).collect();
+    let embeddings = model
+        .with_batch_size(2)
+        .embed_texts(docs.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(server.requests_to(paths::TEI_EMBED).len(), 3);
+    for (embedding, doc) in embeddings.iter().zip(&docs) {
+        assert_eq!(&embedding.document, doc);
+        assert_eq!(
+            as_f32(&embedding.vec),
+            as_f32(&fixtures::embedding(doc, fixtures::DEFAULT_DIMS))
+        );
+    }
+}
+
+#[tokio::test]
+async fn rerank_scores_and_top_n() {
+    let server = MockServer::start().await;
+    let client = client(&server);
+    let texts = vec![
+        "cats purr".to_string(),
+        "dogs bark loudly".to_string(),
+        "birds sing".to_string(),
+    ];
+
+    let results = client.rerank("dogs bark", &texts, Some(1)).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].index, 1);
+    assert_eq!(server.requests_to(paths::TEI_RERANK)[0].json()["top_n"], 1);
+}
+
+#[tokio::test]
+async fn rerank_validation_error_is_surfaced() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::TEI_RERANK).respond(MockResponse::error(
+        413,
+        fixtures::tei_error(
+            "batch size 64 > maximum allowed batch size 32",
+            "Validation",
+        ),
+    )));
+
+    let err = client(&server)
+        .rerank_documents("q", vec!["a".to_string()], None)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("maximum allowed batch size"),
+        "{err}"
+    );
+
+    let err = Reranker::rerank(&client(&server), "", &["a".to_string()], None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RerankError::ValidationError(_)));
+}
+
+#[tokio::test]
+async fn predict_single_and_batch() {
+    let server = MockServer::start().await;
+    let client = client(&server);
+
+    let single = client.predict(vec!["great".to_string()]).await.unwrap();
+    assert_eq!(single.items.len(), 2);
+    assert_eq!(single.items[0].label, "positive");
+
+    let batch = client
+        .predict(vec!["great".to_string(), "awful".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(batch.items.len(), 4);
+}
+
+#[tokio::test]
+async fn usage_is_recorded_from_compute_tokens() {
+    let server = MockServer::start().await;
+    let ledger = UsageLedger::new();
+    let client = Client::builder()
+        .base_url(&server.tei_base_url())
+        .model_id("bge-reranker-v2-m3")
+        .usage_ledger(ledger.clone())
+        .build();
+
+    client
+        .embedding_model("bge-m3")
+        .embed_texts(vec!["a".to_string(), "b".to_string()])
+        .await
+        .unwrap();
+    let texts = vec!["dogs bark".to_string(), "cats meow".to_string()];
+    Reranker::rerank(&client.with_usage_tag("search"), "dogs", &texts, None)
+        .await
+        .unwrap();
+    // Without the header only the request is counted.
+    server.mock(
+        Mock::post(paths::TEI_EMBED)
+            .respond(MockResponse::json(fixtures::tei_embeddings(&[vec![0.0]])))
+            .once(),
+    );
+    client
+        .embedding_model("bge-m3")
+        .embed_text("c")
+        .await
+        .unwrap();
+
+    let snapshot = ledger.snapshot();
+    assert_eq!(snapshot.entries.len(), 2);
+    let embed = &snapshot.entries[0];
+    assert_eq!(
+        (
+            embed.provider.as_str(),
+            embed.model.as_str(),
+            embed.operation
+        ),
+        ("tei", "bge-m3", Operation::Embedding)
+    );
+    assert_eq!(embed.requests, 2);
+    assert_eq!(embed.usage.prompt_tokens, 8);
+    let rerank = &snapshot.entries[1];
+    assert_eq!(rerank.model, "bge-reranker-v2-m3");
+    assert_eq!(rerank.operation, Operation::Rerank);
+    assert_eq!(rerank.tag.as_deref(), Some("search"));
+    assert_eq!(rerank.usage.total_tokens, 8);
+}
+
+#[tokio::test]
+async fn rerank_and_predict_work_with_another_http_backend() {
+    let server = MockServer::start().await;
+    let path = std::env::temp_dir().join(format!("rig-tei-backend-{}.json", std::process::id()));
+    let recorder = Recorder::record(reqwest::Client::new(), &path);
+    let client: Client<Recorder> = Client::builder()
+        .base_url(&server.tei_base_url())
+        .with_client(recorder.clone())
+        .build();
+
+    let texts = vec!["dogs bark".to_string(), "cats meow".to_string()];
+    let results = Reranker::rerank(&client, "dogs bark", &texts, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    let predicted = client.predict(vec!["great".to_string()]).await.unwrap();
+    assert_eq!(predicted.items[0].label, "positive");
+
+    assert_eq!(recorder.cassette().interactions.len(), 2);
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn registry_builds_tei_models() {
+    let server = MockServer::start().await;
+    let registry = rig_tei::ProviderRegistry::new().with(rig_tei::provider());
+    let config = rig_tei::Config::from_yaml_str(&format!(
+        "clients:\n  local:\n    provider: tei\n    base_url: {}\nmodels:\n  \
+         vectors: {{ client: local, kind: embedding, model: bge-m3, batch_size: 1 }}\n  \
+         ranker: {{ client: local, kind: rerank, model: bge-reranker }}\n",
+        server.tei_base_url()
+    ))
+    .unwrap();
+
+    let vectors = registry.embedding_model(&config, "vectors").unwrap();
+    let embeddings = vectors
+        .embed_texts(vec!["a".to_string(), "b".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(embeddings.len(), 2);
+    assert_eq!(server.requests_to(paths::TEI_EMBED).len(), 2);
+
+    let ranker = registry.rerank_model(&config, "ranker").unwrap();
+    let documents = vec!["cats purr".to_string(), "dogs bark".to_string()];
+    let results = ranker.rerank("dogs", &documents, Some(1)).await.unwrap();
+    assert_eq!(results[0].index, 1);
+
+    let err = registry
+        .client(&config, "local")
+        .unwrap()
+        .completion_model("any")
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "provider `tei` has no completion models");
+}
+
+#[tokio::test]
+async fn builder_sets_headers_for_a_secured_server() {
+    let server = MockServer::start().await;
+    let client = Client::builder()
+        .base_url(&server.tei_base_url())
+        .header(
+            reqwest::header::AUTHORIZATION,
+            reqwest::header::HeaderValue::from_static("Bearer tei-key"),
+        )
+        .no_proxy()
+        .build();
+
+    client
+        .embedding_model("bge-m3")
+        .embed_text("hello")
+        .await
+        .unwrap();
+    let request = &server.requests_to(paths::TEI_EMBED)[0];
+    assert_eq!(request.header("authorization"), Some("Bearer tei-key"));
+}
+
+#[tokio::test]
+async fn verify_checks_the_health_endpoint() {
+    let server = MockServer::start().await;
+    client(&server).verify().await.unwrap();
+    assert_eq!(server.requests_to(paths::TEI_HEALTH).len(), 1);
+
+    server.mock(Mock::get(paths::TEI_HEALTH).respond(MockResponse::error(
+        503,
+        fixtures::tei_error("Model is not ready", "unhealthy"),
+    )));
+    let err = client(&server).verify().await.unwrap_err();
+    assert!(matches!(err, VerifyError::ProviderError(_)), "{err}");
+}
+
+#[tokio::test]
+async fn circuit_breaker_probes_the_health_endpoint() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::TEI_EMBED)
+            .respond(MockResponse::error(
+                503,
+                fixtures::tei_error("Model is overloaded", "overloaded"),
+            ))
+            .times(2),
+    );
+    server.mock(
+        Mock::get(paths::TEI_HEALTH)
+            .respond(MockResponse::empty(503))
+            .once(),
+    );
+    let client = Client::builder()
+        .base_url(&server.tei_base_url())
+        .circuit_breaker(
+            rig_tei::CircuitBreakerPolicy::default()
+                .consecutive_failures(2)
+                .open_for(std::time::Duration::from_millis(50)),
+        )
+        .build();
+    let model = client.embedding_model("bge-m3");
+
+    model.embed_text("a").await.unwrap_err();
+    model.embed_text("a").await.unwrap_err();
+    model.embed_text("a").await.unwrap_err();
+    assert_eq!(server.requests_to(paths::TEI_EMBED).len(), 2);
+
+    // The first probe fails, so the circuit opens again without sending the request.
+    tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+    model.embed_text("a").await.unwrap_err();
+    assert_eq!(server.requests_to(paths::TEI_EMBED).len(), 2);
+
+    // The second probe succeeds and the request goes through.
+    tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+    model.embed_text("a").await.unwrap();
+    assert_eq!(server.requests_to(paths::TEI_HEALTH).len(), 2);
+    let endpoint = format!("{}{}", server.tei_base_url(), paths::TEI_EMBED);
+    assert_eq!(
+        client.circuit_breaker().unwrap().state(&endpoint),
+        rig_tei::CircuitState::Closed
+    );
+}
@@ -1,20 +1,40 @@
 //! Category: mod.rs (top-level module and constant exports)
-//! Structure mirrors rig-bedrock:
-//! - client.rs: Client and Builder; implements Provider/Verify/Completion/Embedding
-//! - completion.rs: Chat completion model (OpenAI-compatible)
-//! - embedding.rs: Text embeddings (OpenAI-compatible)
-//! - streaming.rs: Streaming (OpenAI-compatible, same flags)
-//! - types/mod.rs: Internal shared types (error response, tool choice mapping)
+//! Chat, embeddings and streaming come from rig-extend-core; this crate is the Ark profile:
+//! - client.rs: Volcengine profile, Client/ClientBuilder aliases
+//! - completion.rs: Chat completion model alias (OpenAI-compatible)
+//! - embedding.rs: Text embedding model alias and model constants
+//! - image_generation.rs: Seedream / SeedEdit image generation (feature `image`)
 
 pub mod client;
 pub mod completion;
 pub mod embedding;
-pub mod streaming;
-pub mod types;
+#[cfg(feature = "image")]
+pub mod image_generation;
 
-pub use client::Client;
+#[cfg(feature = "reqwest")]
+pub use client::provider;
+pub use client::{Client, ClientBuilder, Volcengine};
 pub use completion::CompletionModel;
 pub use embedding::{EmbeddingModel, TEXT_DOUBAO_EMBEDDING, TEXT_DOUBAO_EMBEDDING_LARGE};
+#[cfg(feature = "image")]
+pub use image_generation::{
+    DOUBAO_SEEDEDIT_3_0_I2I, DOUBAO_SEEDREAM_3_0_T2I, DOUBAO_SEEDREAM_4_0, ImageGenerationModel,
+    ImageResponseFormat, ImagesResponse,
+};
+pub use rig_extend_core::batching::{BatchFailure, EmbeddingBatchError, EmbeddingBatching};
+pub use rig_extend_core::circuit_breaker::{CircuitBreakerPolicy, CircuitState};
+pub use rig_extend_core::completion::Thinking;
+pub use rig_extend_core::config::{Config, ConfigError};
+pub use rig_extend_core::error::{ErrorClass, ProviderError, ProviderErrorExt};
+#[cfg(feature = "workers")]
+pub use rig_extend_core::fetch::FetchClient;
+#[cfg(all(feature = "reqwest", not(target_family = "wasm")))]
+pub use rig_extend_core::http::{HttpOptions, HttpOptionsExt};
+pub use rig_extend_core::registry::ProviderRegistry;
+pub use rig_extend_core::structured::{ResponseFormat, StructuredExtractor, StructuredOutputError};
+pub use rig_extend_core::usage::{ModelPrice, Operation, PriceTable, UsageLedger, UsageSnapshot};
+pub use rig_extend_core::vision::{self, ImageLimits, VisionError};
+pub use schemars;
 
 // Constants (aligned with original single-file version)
 pub const VOLCENGINE_API_BASE_URL: &str = "https://ark.cn-beijing.volces.com/api/v3";
@@ -0,0 +1,182 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use futures::StreamExt;
+use rig::OneOrMany;
+use rig::completion::{AssistantContent, CompletionModel as _, Message, Prompt};
+use rig::embeddings::EmbeddingModel as _;
+use rig::message::{ImageDetail, UserContent};
+use rig::prelude::*;
+use rig::streaming::StreamedAssistantContent;
+use rig_extend_mock::{Mock, MockResponse, MockServer, defaults, fixtures, paths};
+use rig_volcengine::{Client, DOUBAO_SEED, TEXT_DOUBAO_EMBEDDING, Thinking, UsageLedger};
+use serde_json::json;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder("test-key")
+        .base_url(&server.ark_base_url())
+        .build()
+}
+
+#[tokio::test]
+async fn agent_prompt_returns_reply() {
+    let server = MockServer::start().await;
+    let agent = client(&server).agent(DOUBAO_SEED).build();
+
+    let reply = agent.prompt("hi").await.unwrap();
+    assert_eq!(reply, defaults::DEFAULT_REPLY);
+
+    let requests = server.requests_to(paths::ARK_CHAT);
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+    assert_eq!(requests[0].json()["model"], DOUBAO_SEED);
+}
+
+#[tokio::test]
+async fn streaming_yields_text_and_final_response() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_CHAT).respond(MockResponse::sse(fixtures::chat_stream(
+            DOUBAO_SEED,
+            &["Hello", ", ", "Ark"],
+        ))),
+    );
+
+    let model = client(&server).completion_model(DOUBAO_SEED);
+    let mut stream = model.completion_request("hi").stream().await.unwrap();
+
+    let mut text = String::new();
+    let mut finished = false;
+    while let Some(item) = stream.next().await {
+        match item.unwrap() {
+            StreamedAssistantContent::Text(t) => text.push_str(&t.text),
+            StreamedAssistantContent::Final(_) => finished = true,
+            _ => {}
+        }
+    }
+    assert_eq!(text, "Hello, Ark");
+    assert!(finished);
+}
+
+#[tokio::test]
+async fn embeddings_are_returned_in_order() {
+    let server = MockServer::start().await;
+    let model = client(&server).embedding_model(TEXT_DOUBAO_EMBEDDING);
+
+    let docs = vec!["豆包".to_string(), "方舟".to_string(), "火山".to_string()];
+    let embeddings = model.embed_texts(docs.clone()).await.unwrap();
+
+    let got: Vec<&str> = embeddings.iter().map(|e| e.document.as_str()).collect();
+    assert_eq!(got, ["豆包", "方舟", "火山"]);
+    assert_eq!(
+        embeddings[1].vec,
+        fixtures::embedding("方舟", fixtures::DEFAULT_DIMS)
+    );
+    assert_eq!(server.requests_to(paths::ARK_EMBEDDINGS).len(), 1);
+}
+
+#[tokio::test]
+async fn thinking_mode_is_sent_and_reasoning_is_streamed() {
+    let server = MockServer::start().await;
+    server.mock(
+        Mock::post(paths::ARK_CHAT)
+            .respond(MockResponse::sse(fixtures::chat_reasoning_stream(
+                DOUBAO_SEED,
+                &["Let me ", "think."],
+                &["Done."],
+            )))
+            .times(1),
+    );
+    let ledger = UsageLedger::new();
+    let model = Client::builder("test-key")
+        .base_url(&server.ark_base_url())
+        .usage_ledger(ledger.clone())
+        .build()
+        .completion_model(DOUBAO_SEED)
+        .with_thinking_mode(Thinking::Auto);
+
+    let mut stream = model.completion_request("hi").stream().await.unwrap();
+    let (mut reasoning, mut text) = (String::new(), String::new());
+    while let Some(item) = stream.next().await {
+        match item.unwrap() {
+            StreamedAssistantContent::Reasoning(r) => reasoning.push_str(&r.reasoning.concat()),
+            StreamedAssistantContent::Text(t) => text.push_str(&t.text),
+            _ => {}
+        }
+    }
+    assert_eq!(
+        (reasoning.as_str(), text.as_str()),
+        ("Let me think.", "Done.")
+    );
+    assert_eq!(
+        ledger.snapshot().total().usage.reasoning_tokens,
+        fixtures::REASONING_TOKENS
+    );
+
+    model
+        .with_thinking_budget(1024)
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    let requests = server.requests_to(paths::ARK_CHAT);
+    assert_eq!(requests[0].json()["thinking"], json!({ "type": "auto" }));
+    assert_eq!(requests[1].json()["thinking"], json!({ "type": "enabled" }));
+    assert!(requests[1].json().get("thinking_budget").is_none());
+}
+
+#[tokio::test]
+async fn reasoning_content_is_returned_before_the_answer() {
+    let server = MockServer::start().await;
+    server.mock(Mock::post(paths::ARK_CHAT).respond(MockResponse::json(
+        fixtures::chat_reasoning_completion(DOUBAO_SEED, "Simple greeting.", "Hi!"),
+    )));
+
+    let response = client(&server)
+        .completion_model(DOUBAO_SEED)
+        .with_thinking(false)
+        .completion_request("hi")
+        .send()
+        .await
+        .unwrap();
+    assert!(matches!(
+        response.choice.first(),
+        AssistantContent::Reasoning(r) if r.reasoning.concat() == "Simple greeting."
+    ));
+    assert_eq!(
+        server.requests_to(paths::ARK_CHAT)[0].json()["thinking"],
+        json!({ "type": "disabled" })
+    );
+}
+
+#[tokio::test]
+async fn images_are_sent_to_doubao_vision() {
+    let server = MockServer::start().await;
+    let gif = b"GIF89a\x01\x00\x01\x00".to_vec();
+    let message = Message::User {
+        content: OneOrMany::many(vec![
+            UserContent::image_url("https://example.com/a.png", None, Some(ImageDetail::Low)),
+            UserContent::image_raw(gif.clone(), None, Some(ImageDetail::Auto)),
+            UserContent::text("Compare them."),
+        ])
+        .unwrap(),
+    };
+
+    client(&server)
+        .completion_model(DOUBAO_SEED)
+        .completion_request(message)
+        .send()
+        .await
+        .unwrap();
+
+    let body = server.requests_to(paths::ARK_CHAT)[0].json();
+    let content = &body["messages"][0]["content"];
+    assert_eq!(content[0]["image_url"]["detail"], "low");
+    assert_eq!(
+        content[1]["image_url"],
+        json!({
+            "url": format!("data:image/gif;base64,{}", STANDARD.encode(&gif)),
+            "detail": "auto",
+        })
+    );
+    assert_eq!(content[2]["type"], "text");
+}
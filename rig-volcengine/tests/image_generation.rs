@@ -0,0 +1,157 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rig::image_generation::{ImageGenerationError, ImageGenerationModel as _};
+use rig::prelude::*;
+use rig_extend_mock::{Mock, MockResponse, MockServer, fixtures, paths};
+use rig_volcengine::{
+    Client, DOUBAO_SEEDEDIT_3_0_I2I, DOUBAO_SEEDREAM_3_0_T2I, DOUBAO_SEEDREAM_4_0,
+    ImageResponseFormat, Operation, ProviderErrorExt, UsageLedger, VisionError,
+};
+use serde_json::json;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder("test-key")
+        .base_url(&server.ark_base_url())
+        .build()
+}
+
+#[tokio::test]
+async fn generates_and_downloads_the_image() {
+    let server = MockServer::start().await;
+    let ledger = UsageLedger::new();
+    let model = Client::builder("test-key")
+        .base_url(&server.ark_base_url())
+        .usage_ledger(ledger.clone())
+        .build()
+        .image_generation_model(DOUBAO_SEEDREAM_3_0_T2I)
+        .with_seed(42)
+        .with_guidance_scale(2.5)
+        .with_watermark(false);
+
+    let response = model
+        .image_generation_request()
+        .prompt("A red panda reading a map")
+        .width(1024)
+        .height(1024)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.image, fixtures::IMAGE_PNG);
+    assert_eq!(response.response.urls().count(), 1);
+
+    let request = &server.requests_to(paths::ARK_IMAGES)[0];
+    assert_eq!(request.header("authorization"), Some("Bearer test-key"));
+    assert_eq!(
+        request.json(),
+        json!({
+            "model": DOUBAO_SEEDREAM_3_0_T2I,
+            "prompt": "A red panda reading a map",
+            "size": "1024x1024",
+            "response_format": "url",
+            "seed": 42,
+            "guidance_scale": 2.5,
+            "watermark": false,
+        })
+    );
+    // Result URLs are pre-signed; the API key must not leak to them.
+    let download = &server.requests_to(&format!("{}/0.png", paths::FILES))[0];
+    assert_eq!(download.header("authorization"), None);
+
+    let entries = ledger.snapshot().entries;
+    assert_eq!(entries[0].operation, Operation::ImageGeneration);
+    assert_eq!(entries[0].usage.completion_tokens, 4096);
+}
+
+#[tokio::test]
+async fn reference_images_and_b64_json() {
+    let server = MockServer::start().await;
+    let model = client(&server)
+        .image_generation_model(DOUBAO_SEEDREAM_4_0)
+        .with_response_format(ImageResponseFormat::B64Json)
+        .with_reference_image_url("https://example.com/style.png")
+        .with_reference_image(fixtures::IMAGE_PNG);
+
+    let response = model
+        .image_generation_request()
+        .prompt("Put the character from image 2 into the style of image 1")
+        .additional_params(json!({ "size": "2K" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.image, fixtures::IMAGE_PNG);
+    assert!(
+        server
+            .requests()
+            .iter()
+            .all(|r| !r.path.starts_with(paths::FILES))
+    );
+
+    let body = server.requests_to(paths::ARK_IMAGES)[0].json();
+    assert_eq!(body["response_format"], "b64_json");
+    assert_eq!(body["size"], "2K");
+    assert_eq!(
+        body["image"],
+        json!([
+            "https://example.com/style.png",
+            format!(
+                "data:image/png;base64,{}",
+                STANDARD.encode(fixtures::IMAGE_PNG)
+            ),
+        ])
+    );
+
+    // SeedEdit takes a single image as a string.
+    client(&server)
+        .image_generation_model(DOUBAO_SEEDEDIT_3_0_I2I)
+        .with_reference_image_url("https://example.com/photo.jpg")
+        .image_generation_request()
+        .prompt("Make it snow")
+        .send()
+        .await
+        .unwrap();
+    let body = server.requests_to(paths::ARK_IMAGES)[1].json();
+    assert_eq!(body["image"], "https://example.com/photo.jpg");
+}
+
+#[tokio::test]
+async fn invalid_references_and_provider_errors() {
+    let server = MockServer::start().await;
+
+    let err = client(&server)
+        .image_generation_model(DOUBAO_SEEDEDIT_3_0_I2I)
+        .with_reference_image(b"not an image".to_vec())
+        .image_generation_request()
+        .prompt("Make it snow")
+        .send()
+        .await
+        .unwrap_err();
+    let ImageGenerationError::RequestError(err) = err else {
+        panic!("expected a request error, got {err:?}");
+    };
+    assert!(matches!(
+        *err.downcast::<VisionError>().unwrap(),
+        VisionError::UnsupportedFormat(..)
+    ));
+    assert!(server.requests_to(paths::ARK_IMAGES).is_empty());
+
+    server.mock(Mock::post(paths::ARK_IMAGES).respond(MockResponse::error(
+        400,
+        fixtures::openai_error(
+            "The request failed because the size is invalid.",
+            "BadRequest",
+            "InvalidParameter",
+        ),
+    )));
+    let err = client(&server)
+        .image_generation_model(DOUBAO_SEEDREAM_3_0_T2I)
+        .image_generation_request()
+        .prompt("A red panda")
+        .width(10)
+        .height(10)
+        .send()
+        .await
+        .unwrap_err();
+    let provider = err.provider_error().expect("provider error");
+    assert_eq!(provider.status, Some(400));
+    assert_eq!(provider.code.as_deref(), Some("InvalidParameter"));
+}